@@ -0,0 +1,26 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use tis_cli::WORKLOADS;
+
+/// How many cycles are run per iteration, so loading the program isn't part of what's measured.
+const CYCLES: u64 = 1000;
+
+fn workloads(c: &mut Criterion) {
+    for workload in &WORKLOADS {
+        let mut group = c.benchmark_group(workload.name);
+        group.throughput(Throughput::Elements(CYCLES));
+        for &size in workload.sizes {
+            let mut tis = workload.load(size);
+            group.bench_function(BenchmarkId::from_parameter(size), |b| {
+                b.iter(|| {
+                    for _ in 0..CYCLES {
+                        tis.step();
+                    }
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, workloads);
+criterion_main!(benches);
@@ -0,0 +1,247 @@
+use alloc::{borrow::ToOwned, format, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{fs, io::Write};
+
+use enum_iterator::all;
+
+use crate::{direction::Direction, node::DirectionGiving, number::Number, position::Position};
+
+/// Written at the start of every checkpoint file, bumped whenever the layout changes.
+const MAGIC: &[u8; 8] = b"TISCHK2\n";
+
+/// The state of a whole machine at the end of a cycle.
+pub struct Checkpoint {
+    /// A hash of the program the machine was running, see [`Tis::program_hash`].
+    ///
+    /// [`Tis::program_hash`]: crate::tis::Tis::program_hash
+    pub(crate) program: u64,
+    pub(crate) cycle: usize,
+    pub(crate) nodes: Vec<(Position, Vec<i32>)>,
+}
+
+impl Checkpoint {
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&self.program.to_le_bytes());
+        bytes.extend_from_slice(&(self.cycle as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.nodes.len() as u32).to_le_bytes());
+        for (position, state) in &self.nodes {
+            for coordinate in [position.x, position.y, position.z] {
+                bytes.extend_from_slice(&coordinate.to_le_bytes());
+            }
+            bytes.extend_from_slice(&(state.len() as u32).to_le_bytes());
+            for word in state {
+                bytes.extend_from_slice(&word.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut reader = Reader(bytes);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return None;
+        }
+        let program = u64::from_le_bytes(reader.array()?);
+        let cycle = u64::from_le_bytes(reader.array()?) as usize;
+
+        let count = u32::from_le_bytes(reader.array()?);
+        let mut nodes = Vec::new();
+        for _ in 0..count {
+            let mut coordinate = || Some(i32::from_le_bytes(reader.array()?));
+            let position = Position::new(coordinate()?, coordinate()?).with_z(coordinate()?);
+
+            let len = u32::from_le_bytes(reader.array()?);
+            let state = (0..len)
+                .map(|_| Some(i32::from_le_bytes(reader.array()?)))
+                .collect::<Option<_>>()?;
+            nodes.push((position, state));
+        }
+
+        reader.0.is_empty().then_some(Self {
+            program,
+            cycle,
+            nodes,
+        })
+    }
+
+    #[cfg(feature = "std")]
+    pub fn read(path: &str) -> Result<Self, String> {
+        let bytes =
+            fs::read(path).map_err(|e| format!("Couldn't read checkpoint {}: {}", path, e))?;
+        Self::decode(&bytes).ok_or_else(|| format!("{} is not a valid checkpoint", path))
+    }
+
+    /// Writes the checkpoint to `path`. The old file is only replaced once the new one has been
+    /// written in full, so a crash halfway through never leaves a broken checkpoint behind.
+    #[cfg(feature = "std")]
+    pub fn write(&self, path: &str) -> Result<(), String> {
+        let error = |e| format!("Couldn't write checkpoint {}: {}", path, e);
+
+        let temporary = format!("{}.tmp", path);
+        let mut file = fs::File::create(&temporary).map_err(error)?;
+        file.write_all(&self.encode()).map_err(error)?;
+        file.sync_all().map_err(error)?;
+        fs::rename(&temporary, path).map_err(error)
+    }
+}
+
+/// Writes a checkpoint every so many cycles.
+pub struct Checkpointer {
+    every: usize,
+    path: String,
+}
+
+impl Checkpointer {
+    pub fn new(every: usize, path: String) -> Self {
+        Self { every, path }
+    }
+
+    /// Whether a checkpoint is due after `cycle`.
+    pub fn is_due(&self, cycle: usize) -> bool {
+        cycle.is_multiple_of(self.every)
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+/// Reads the bytes of a checkpoint from front to back.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.0.len() < len {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.take(N)?.try_into().ok()
+    }
+}
+
+/// Hashes with FNV-1a, which unlike the hasher of the standard library gives the same hash no
+/// matter what version of tis-cli wrote a checkpoint.
+pub fn hash(bytes: &[u8], hash: u64) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// The hash to start from when hashing with [`hash`].
+pub const HASH_START: u64 = 0xcbf29ce484222325;
+
+pub fn save_direction(state: &mut Vec<i32>, direction: Option<Direction>) {
+    state.push(match direction {
+        Some(direction) => all::<Direction>().position(|d| d == direction).unwrap() as i32,
+        None => -1,
+    });
+}
+
+/// Saves a value being handed to another node, which takes up the same amount of words no matter
+/// what state the hand-off is in.
+pub fn save_transfer(
+    state: &mut Vec<i32>,
+    give: &DirectionGiving,
+    giving_to: Option<Direction>,
+    give_value: Option<Number>,
+) {
+    match give {
+        DirectionGiving::None => state.extend([0, -1]),
+        DirectionGiving::Any => state.extend([1, -1]),
+        DirectionGiving::Direction(direction) => {
+            state.push(2);
+            save_direction(state, Some(*direction));
+        }
+        DirectionGiving::Given => state.extend([3, -1]),
+    }
+    save_direction(state, giving_to);
+    match give_value {
+        Some(value) => state.extend([1, value.value() as i32]),
+        None => state.extend([0, 0]),
+    }
+}
+
+/// Reads the state saved by a node, one word at a time.
+pub struct StateReader<'a> {
+    state: &'a [i32],
+}
+
+impl<'a> StateReader<'a> {
+    pub fn new(state: &'a [i32]) -> Self {
+        Self { state }
+    }
+
+    pub fn word(&mut self) -> Result<i32, String> {
+        let (&word, rest) = self
+            .state
+            .split_first()
+            .ok_or("The saved state ends too early")?;
+        self.state = rest;
+        Ok(word)
+    }
+
+    pub fn number(&mut self) -> Result<Number, String> {
+        let word = self.word()?;
+        i16::try_from(word)
+            .ok()
+            .filter(|value| (-999..=999).contains(value))
+            .map(Number::from)
+            .ok_or_else(|| format!("{} is not a valid value", word))
+    }
+
+    pub fn direction(&mut self) -> Result<Option<Direction>, String> {
+        let word = self.word()?;
+        if word == -1 {
+            return Ok(None);
+        }
+        usize::try_from(word)
+            .ok()
+            .and_then(|index| all::<Direction>().nth(index))
+            .map(Some)
+            .ok_or_else(|| format!("{} is not a valid direction", word))
+    }
+
+    /// Reads back what [`save_transfer`] saved.
+    pub fn transfer(
+        &mut self,
+    ) -> Result<(DirectionGiving, Option<Direction>, Option<Number>), String> {
+        let (give, direction) = (self.word()?, self.direction()?);
+        let give = match (give, direction) {
+            (0, None) => DirectionGiving::None,
+            (1, None) => DirectionGiving::Any,
+            (2, Some(direction)) => DirectionGiving::Direction(direction),
+            (3, None) => DirectionGiving::Given,
+            _ => return Err(format!("{} is not a valid transfer state", give)),
+        };
+        let giving_to = self.direction()?;
+        let give_value = match (self.word()?, self.number()?) {
+            (0, _) => None,
+            (_, value) => Some(value),
+        };
+        Ok((give, giving_to, give_value))
+    }
+
+    /// Whether everything that was saved has been read.
+    pub fn is_empty(&self) -> bool {
+        self.state.is_empty()
+    }
+
+    /// Reads every word that's left as a value.
+    pub fn numbers(mut self) -> Result<Vec<Number>, String> {
+        (0..self.state.len()).map(|_| self.number()).collect()
+    }
+
+    /// Makes sure everything that was saved has been read.
+    pub fn finish(self) -> Result<(), String> {
+        if !self.state.is_empty() {
+            return Err("The saved state is too long".to_owned());
+        }
+        Ok(())
+    }
+}
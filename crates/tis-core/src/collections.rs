@@ -0,0 +1,7 @@
+//! The maps and sets the machine keeps track of its nodes in. Without std there's nothing to hash
+//! with, so they're ordered by key instead.
+
+#[cfg(not(feature = "std"))]
+pub(crate) use alloc::collections::{BTreeMap as Map, BTreeSet as Set};
+#[cfg(feature = "std")]
+pub(crate) use std::collections::{HashMap as Map, HashSet as Set};
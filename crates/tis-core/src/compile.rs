@@ -0,0 +1,144 @@
+use alloc::{boxed::Box, vec::Vec};
+
+use num_traits::{zero, Zero};
+
+use crate::{
+    instruction::Instruction,
+    number::Number,
+    register::{Register, RegisterOrNumber},
+};
+
+/// How many instructions a node runs ahead of the machine at most, before it has to catch up.
+pub const RUN_AHEAD: usize = 4096;
+
+/// What an instruction only using ACC and BAK does to them, giving back the instruction to run
+/// after it, which may be past the end of the code. Steps hold nothing but plain values, so the
+/// node can run them on any thread.
+pub type Step = Box<dyn Fn(&mut Number, &mut Number) -> usize + Send>;
+
+/// Where an instruction only using ACC and BAK reads a value from.
+#[derive(Clone, Copy)]
+enum Value {
+    Number(Number),
+    Accumulator,
+    Backup,
+}
+
+impl Value {
+    fn of(source: RegisterOrNumber) -> Option<Self> {
+        match source {
+            RegisterOrNumber::Number(number) => Some(Self::Number(number)),
+            RegisterOrNumber::Register(Register::Accumulator) => Some(Self::Accumulator),
+            RegisterOrNumber::Register(Register::Backup) => Some(Self::Backup),
+            RegisterOrNumber::Register(Register::Nil) => Some(Self::Number(zero())),
+            RegisterOrNumber::Register(_) => None,
+        }
+    }
+
+    fn read(self, accumulator: Number, backup: Number) -> Number {
+        match self {
+            Self::Number(number) => number,
+            Self::Accumulator => accumulator,
+            Self::Backup => backup,
+        }
+    }
+}
+
+/// Compiles every instruction that doesn't read or write a port into a [`Step`], so loops that
+/// don't talk to any other node can be run many times over without going through the machine.
+/// Instructions using ports, or anything of the node besides ACC and BAK, are `None`, which is
+/// where running ahead has to stop.
+pub fn compile(instructions: &[Instruction]) -> Vec<Option<Step>> {
+    instructions
+        .iter()
+        .enumerate()
+        .map(|(i, &instruction)| compile_instruction(i, instruction))
+        .collect()
+}
+
+fn compile_instruction(i: usize, instruction: Instruction) -> Option<Step> {
+    let next = i + 1;
+    Some(match instruction {
+        Instruction::Noop => Box::new(move |_, _| next),
+        Instruction::Move(source, Register::Accumulator) => {
+            let source = Value::of(source)?;
+            Box::new(move |accumulator, backup| {
+                *accumulator = source.read(*accumulator, *backup);
+                next
+            })
+        }
+        Instruction::Move(source, Register::Nil) => {
+            Value::of(source)?;
+            Box::new(move |_, _| next)
+        }
+        Instruction::Move(..) => return None,
+
+        Instruction::Swap => Box::new(move |accumulator, backup| {
+            (*accumulator, *backup) = (*backup, *accumulator);
+            next
+        }),
+        Instruction::Save => Box::new(move |accumulator, backup| {
+            *backup = *accumulator;
+            next
+        }),
+
+        Instruction::Add(source) => {
+            let source = Value::of(source)?;
+            Box::new(move |accumulator, backup| {
+                *accumulator += source.read(*accumulator, *backup);
+                next
+            })
+        }
+        Instruction::Subtract(source) => {
+            let source = Value::of(source)?;
+            Box::new(move |accumulator, backup| {
+                *accumulator -= source.read(*accumulator, *backup);
+                next
+            })
+        }
+        Instruction::Negate => Box::new(move |accumulator, _| {
+            *accumulator = -*accumulator;
+            next
+        }),
+
+        Instruction::Jump(target) => Box::new(move |_, _| target),
+        Instruction::JumpEqualZero(target) => {
+            Box::new(move |accumulator, _| if accumulator.is_zero() { target } else { next })
+        }
+        Instruction::JumpNotZero(target) => {
+            Box::new(move |accumulator, _| if accumulator.is_zero() { next } else { target })
+        }
+        Instruction::JumpGreaterThanZero(target) => {
+            Box::new(move |accumulator, _| if *accumulator > zero() { target } else { next })
+        }
+        Instruction::JumpLessThanZero(target) => {
+            Box::new(move |accumulator, _| if *accumulator < zero() { target } else { next })
+        }
+        // The stacks and data aren't among what a step gets
+        Instruction::Push(_)
+        | Instruction::Pop(_)
+        | Instruction::Call(_)
+        | Instruction::Return
+        | Instruction::ReadNext(_)
+        | Instruction::ReadIndexed(_) => return None,
+        Instruction::MoveEqualZero(source, destination)
+        | Instruction::MoveNotZero(source, destination) => {
+            // The `mov` it makes, which only runs when ACC is as it needs to be
+            let moved = compile_instruction(i, Instruction::Move(source, destination))?;
+            let if_zero = matches!(instruction, Instruction::MoveEqualZero(..));
+            Box::new(move |accumulator, backup| {
+                if accumulator.is_zero() == if_zero {
+                    moved(accumulator, backup)
+                } else {
+                    next
+                }
+            })
+        }
+        Instruction::JumpRelative(source) => {
+            let source = Value::of(source)?;
+            Box::new(move |accumulator, backup| {
+                (i as i32 + source.read(*accumulator, *backup).value() as i32).max(0) as usize
+            })
+        }
+    })
+}
@@ -0,0 +1,63 @@
+use core::fmt::{self, Display, Formatter};
+
+use enum_iterator::Sequence;
+
+#[derive(Debug, Clone, Copy, Sequence, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    Up,
+    Left,
+    Right,
+    Down,
+    // Only used by layered grids, so they come after the directions of the plane
+    Above,
+    Below,
+}
+
+impl Direction {
+    #[doc(hidden)]
+    pub fn opposite(&self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+            Self::Down => Self::Up,
+            Self::Above => Self::Below,
+            Self::Below => Self::Above,
+        }
+    }
+
+    /// The direction a quarter turn clockwise from this one, looking down on the grid.
+    #[doc(hidden)]
+    pub fn turned(&self) -> Self {
+        match self {
+            Self::Up => Self::Right,
+            Self::Right => Self::Down,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+            direction => *direction,
+        }
+    }
+
+    /// The direction with left and right swapped.
+    #[doc(hidden)]
+    pub fn mirrored(&self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+            direction => *direction,
+        }
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Up => write!(f, "up"),
+            Self::Left => write!(f, "left"),
+            Self::Right => write!(f, "right"),
+            Self::Down => write!(f, "down"),
+            Self::Above => write!(f, "above"),
+            Self::Below => write!(f, "below"),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use crate::{number::Number, position::Position};
+
+/// Something that happened inside the machine, see [`Tis::observe`].
+///
+/// [`Tis::observe`]: crate::Tis::observe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A node finished running the instruction at `pointer`.
+    InstructionExecuted { position: Position, pointer: usize },
+    /// A value was handed from one node to another.
+    ValueTransferred {
+        from: Position,
+        to: Position,
+        value: Number,
+    },
+    /// A node spent the cycle waiting on a port.
+    NodeBlocked { position: Position },
+    /// A node stopped for good, like an input node whose input has ended.
+    NodeHalted { position: Position },
+}
@@ -0,0 +1,80 @@
+use alloc::{format, string::String, vec::Vec};
+
+use enum_iterator::{all, Sequence};
+
+/// Additions to the game's language, which programs can only use once they're turned on. Programs
+/// written for the game mean the same with or without them.
+#[derive(Debug, Clone, Copy, Sequence, PartialEq, Eq)]
+pub enum Extension {
+    /// `bak` can be read like `acc`, though it's still only written by `sav` and `swp`.
+    ReadableBak,
+    /// Every node has [`GENERAL_REGISTERS`](crate::register::GENERAL_REGISTERS) more registers,
+    /// `r0` and up, which are read and written like `acc`.
+    Registers,
+    /// `mez` and `mnz`, which work like `mov` when ACC is zero or isn't, and do nothing otherwise.
+    ConditionalMove,
+    /// Every node has a stack of its own, which `push` puts values on and `pop` takes them off.
+    Stack,
+    /// `call`, which jumps to a label like `jmp` but keeps where it was, and `ret`, which goes back
+    /// to the instruction after the last `call`. Every node keeps up to
+    /// [`CALL_DEPTH`](crate::node::instruction_node::CALL_DEPTH) of them.
+    Call,
+    /// `mov` can move a string like `"Hi!\n"`, which stands for a `mov` of the code of each of its
+    /// characters in turn. Ending it with `\0` moves a 0 after them.
+    Strings,
+    /// `.dat` gives a node a table of values, which `rdn` reads one after the other, starting over
+    /// after the last, and `rdx` reads at the index in ACC.
+    Data,
+}
+
+impl Extension {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        all::<Self>()
+            .find(|extension| extension.name() == name)
+            .ok_or_else(|| {
+                let names = all::<Self>().map(Self::name).collect::<Vec<_>>();
+                format!(
+                    "Unknown extension: '{}', expected {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+
+    /// The name the extension is turned on by, like `bak` in `--extension bak`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::ReadableBak => "bak",
+            Self::Registers => "regs",
+            Self::ConditionalMove => "cmov",
+            Self::Stack => "stack",
+            Self::Call => "call",
+            Self::Strings => "strings",
+            Self::Data => "dat",
+        }
+    }
+
+    fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+/// The extensions a machine's programs may use, a bit each. None are on by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Extensions(u32);
+
+impl Extensions {
+    pub fn with(self, extension: Extension) -> Self {
+        Self(self.0 | extension.bit())
+    }
+
+    pub fn contains(self, extension: Extension) -> bool {
+        self.0 & extension.bit() != 0
+    }
+}
+
+impl FromIterator<Extension> for Extensions {
+    fn from_iter<I: IntoIterator<Item = Extension>>(extensions: I) -> Self {
+        extensions.into_iter().fold(Self::default(), Self::with)
+    }
+}
@@ -0,0 +1,160 @@
+use alloc::{vec, vec::Vec};
+
+use num_traits::Zero;
+
+use crate::{
+    direction::Direction,
+    number::Number,
+    register::{Register, RegisterOrNumber},
+};
+
+/// Every instruction, for suggesting one when an unknown one is used.
+pub const MNEMONICS: [&str; 21] = [
+    "nop", "mov", "swp", "sav", "add", "sub", "neg", "jmp", "jez", "jnz", "jgz", "jlz", "jro",
+    "mez", "mnz", "push", "pop", "call", "ret", "rdn", "rdx",
+];
+
+/// An instruction as it's run, with labels already turned into where they point, so it can be
+/// copied out of a node every cycle.
+#[derive(Debug, Clone, Copy)]
+pub enum Instruction {
+    Noop,
+    Move(RegisterOrNumber, Register),
+
+    // Backup register instructions
+    Swap,
+    Save,
+
+    // Math instructions
+    Add(RegisterOrNumber),
+    Subtract(RegisterOrNumber),
+    Negate,
+
+    // Jump instructions
+    Jump(usize),
+
+    JumpEqualZero(usize),
+    JumpNotZero(usize),
+
+    JumpGreaterThanZero(usize),
+    JumpLessThanZero(usize),
+
+    JumpRelative(RegisterOrNumber),
+
+    // Conditional move instructions, with the cmov extension
+    MoveEqualZero(RegisterOrNumber, Register),
+    MoveNotZero(RegisterOrNumber, Register),
+
+    // Stack instructions, with the stack extension
+    Push(RegisterOrNumber),
+    Pop(Register),
+
+    // Subroutine instructions, with the call extension
+    Call(usize),
+    Return,
+
+    // Data instructions, with the dat extension
+    ReadNext(Register),
+    ReadIndexed(Register),
+}
+
+impl Instruction {
+    /// The `mov` a conditional move makes when ACC is as it needs to be, or the instruction itself
+    /// if it isn't a conditional move. A conditional move that doesn't happen does nothing.
+    pub fn conditional_move(self, accumulator: Number) -> Option<Self> {
+        match self {
+            Instruction::MoveEqualZero(source, destination) if accumulator.is_zero() => {
+                Some(Instruction::Move(source, destination))
+            }
+            Instruction::MoveNotZero(source, destination) if !accumulator.is_zero() => {
+                Some(Instruction::Move(source, destination))
+            }
+            Instruction::MoveEqualZero(..) | Instruction::MoveNotZero(..) => None,
+            instruction => Some(instruction),
+        }
+    }
+
+    /// How the instruction is written.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Instruction::Noop => "nop",
+            Instruction::Move(..) => "mov",
+            Instruction::Swap => "swp",
+            Instruction::Save => "sav",
+            Instruction::Add(_) => "add",
+            Instruction::Subtract(_) => "sub",
+            Instruction::Negate => "neg",
+            Instruction::Jump(_) => "jmp",
+            Instruction::JumpEqualZero(_) => "jez",
+            Instruction::JumpNotZero(_) => "jnz",
+            Instruction::JumpGreaterThanZero(_) => "jgz",
+            Instruction::JumpLessThanZero(_) => "jlz",
+            Instruction::JumpRelative(_) => "jro",
+            Instruction::MoveEqualZero(..) => "mez",
+            Instruction::MoveNotZero(..) => "mnz",
+            Instruction::Push(_) => "push",
+            Instruction::Pop(_) => "pop",
+            Instruction::Call(_) => "call",
+            Instruction::Return => "ret",
+            Instruction::ReadNext(_) => "rdn",
+            Instruction::ReadIndexed(_) => "rdx",
+        }
+    }
+
+    /// The ports the instruction reads from or writes to by their direction.
+    pub fn ports(&self) -> Vec<Direction> {
+        let registers = match self {
+            Instruction::Move(source, destination)
+            | Instruction::MoveEqualZero(source, destination)
+            | Instruction::MoveNotZero(source, destination) => {
+                vec![*source, RegisterOrNumber::Register(*destination)]
+            }
+            Instruction::Add(source)
+            | Instruction::Subtract(source)
+            | Instruction::JumpRelative(source)
+            | Instruction::Push(source) => vec![*source],
+            Instruction::Pop(destination)
+            | Instruction::ReadNext(destination)
+            | Instruction::ReadIndexed(destination) => {
+                vec![RegisterOrNumber::Register(*destination)]
+            }
+            _ => Vec::new(),
+        };
+        registers
+            .into_iter()
+            .filter_map(|register| match register {
+                RegisterOrNumber::Register(Register::Direction(direction)) => Some(direction),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The instruction with every port it uses by direction swapped for the one `map` gives.
+    pub fn map_ports(self, map: impl Fn(Direction) -> Direction) -> Self {
+        let register = |register| match register {
+            Register::Direction(direction) => Register::Direction(map(direction)),
+            register => register,
+        };
+        let source = |source| match source {
+            RegisterOrNumber::Register(source) => RegisterOrNumber::Register(register(source)),
+            number => number,
+        };
+        match self {
+            Instruction::Move(from, to) => Instruction::Move(source(from), register(to)),
+            Instruction::MoveEqualZero(from, to) => {
+                Instruction::MoveEqualZero(source(from), register(to))
+            }
+            Instruction::MoveNotZero(from, to) => {
+                Instruction::MoveNotZero(source(from), register(to))
+            }
+            Instruction::Add(from) => Instruction::Add(source(from)),
+            Instruction::Subtract(from) => Instruction::Subtract(source(from)),
+            Instruction::JumpRelative(by) => Instruction::JumpRelative(source(by)),
+            Instruction::Push(from) => Instruction::Push(source(from)),
+            Instruction::Pop(to) => Instruction::Pop(register(to)),
+            Instruction::ReadNext(to) => Instruction::ReadNext(register(to)),
+            Instruction::ReadIndexed(to) => Instruction::ReadIndexed(register(to)),
+            instruction => instruction,
+        }
+    }
+}
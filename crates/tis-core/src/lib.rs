@@ -0,0 +1,52 @@
+//! The TIS-100 machine behind `tis-cli`: nodes, the grid they sit on and running them cycle by
+//! cycle. Programs are loaded by `tis-parse`.
+//!
+//! Without the default `std` feature only the machine itself is built, which needs nothing but
+//! `alloc`. Every node talking to the outside world needs `std`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// Much of the machine is only used by the parts that need std
+#![cfg_attr(not(feature = "std"), allow(dead_code))]
+
+extern crate alloc;
+
+// Only public so tis-parse and tis-cli can use them
+#[doc(hidden)]
+pub mod checkpoint;
+mod collections;
+#[doc(hidden)]
+pub mod compile;
+#[doc(hidden)]
+pub mod direction;
+#[doc(hidden)]
+pub mod event;
+#[doc(hidden)]
+pub mod extension;
+#[doc(hidden)]
+pub mod instruction;
+#[doc(hidden)]
+pub mod node;
+#[doc(hidden)]
+pub mod number;
+#[doc(hidden)]
+pub mod optimize;
+#[doc(hidden)]
+pub mod position;
+#[doc(hidden)]
+pub mod register;
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub mod stream;
+#[doc(hidden)]
+pub mod tis;
+#[doc(hidden)]
+pub mod topology;
+
+pub use crate::{
+    direction::Direction,
+    event::Event,
+    node::custom_node::CustomNode,
+    number::Number,
+    position::Position,
+    tis::{NodeView, Output, Registers, TickReport, TickStatus, Tis},
+};
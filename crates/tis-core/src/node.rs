@@ -0,0 +1,228 @@
+#[cfg(feature = "audio")]
+pub mod audio_node;
+pub mod bus_node;
+#[cfg(feature = "std")]
+pub mod console_node;
+#[cfg(feature = "std")]
+pub mod csv_node;
+pub mod custom_node;
+pub mod fused_node;
+#[cfg(feature = "std")]
+pub mod http_node;
+#[cfg(feature = "std")]
+pub mod image_node;
+pub mod instruction_node;
+#[cfg(feature = "std")]
+pub mod log_node;
+pub mod math_node;
+#[cfg(feature = "std")]
+pub mod memory_node;
+#[cfg(feature = "std")]
+pub mod number_console_node;
+#[cfg(feature = "plugins")]
+pub mod plugin_node;
+pub mod portal_node;
+#[cfg(feature = "scripting")]
+pub mod script_node;
+pub mod split_node;
+pub mod stack_node;
+#[cfg(feature = "window")]
+pub mod window_node;
+
+use alloc::{borrow::ToOwned, rc::Rc, string::String, vec::Vec};
+use core::cell::RefCell;
+#[cfg(not(feature = "std"))]
+use core::sync::atomic::{AtomicI32, Ordering};
+#[cfg(feature = "std")]
+use std::cell::Cell;
+
+use crate::{direction::Direction, number::Number, position::Position, tis::NodeView};
+
+use self::instruction_node::InstructionNode;
+
+pub trait Node {
+    fn position(&self) -> Position;
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>);
+
+    fn give(&self) -> &DirectionGiving;
+    fn giving_to(&self) -> Option<Direction>;
+    fn set_giving_to(&mut self, direction: Direction);
+    fn give_value(&mut self) -> &mut Option<Number>;
+
+    fn tick(&mut self);
+    fn handle_give(&mut self);
+    fn post_handle_give(&mut self) -> Option<Position>;
+    fn post_post_handle_give(&mut self);
+
+    /// Whether this node is waiting on a port instead of executing.
+    fn is_blocked(&self) -> bool {
+        false
+    }
+
+    /// Values this node has written out since the last call.
+    fn take_outputs(&mut self) -> Vec<Number> {
+        Vec::new()
+    }
+
+    /// The pointer of the instruction this node finished running since the last call.
+    fn take_executed(&mut self) -> Option<usize> {
+        None
+    }
+
+    /// Whether running this node would do nothing until a neighbor starts writing, or reads what
+    /// this node is writing. Nodes talking to the outside world never are.
+    fn is_stuck(&self) -> bool {
+        false
+    }
+
+    /// Whether this node is only catching up on instructions it already ran, see
+    /// [`InstructionNode::run_ahead`], so it doesn't touch any of its ports.
+    fn is_running_ahead(&self) -> bool {
+        false
+    }
+
+    /// What this node is waiting on once nothing else in the machine is happening.
+    fn waiting(&self) -> Waiting {
+        Waiting::Nothing
+    }
+
+    /// Blocks until something from outside the machine arrives for this node, for nodes
+    /// [`Waiting::Input`].
+    fn wait_for_input(&mut self) {}
+
+    /// The code the program should exit with, once this node has stopped the whole machine.
+    fn exit_code(&self) -> Option<i32> {
+        None
+    }
+
+    /// What this node ran into that it can't go on from, once it has, which stops the whole
+    /// machine.
+    fn trap(&self) -> Option<&str> {
+        None
+    }
+
+    /// Whether this node has stopped for good.
+    fn is_halted(&self) -> bool {
+        false
+    }
+
+    /// This node, if it runs instructions.
+    fn as_instruction_node(&mut self) -> Option<&mut InstructionNode> {
+        None
+    }
+
+    /// The state of this node for checkpoints. Nodes talking to the outside world have nothing
+    /// that could be saved, so they leave this empty.
+    fn save_state(&self) -> Vec<i32> {
+        Vec::new()
+    }
+
+    /// Restores the state saved by [`Node::save_state`].
+    fn load_state(&mut self, state: &[i32]) -> Result<(), String> {
+        if !state.is_empty() {
+            return Err("This kind of node has no state to restore".to_owned());
+        }
+        Ok(())
+    }
+
+    /// A look at this node from outside the machine.
+    fn view(&self) -> NodeView {
+        NodeView {
+            position: self.position(),
+            registers: None,
+            pending: None,
+            blocked: self.is_blocked(),
+        }
+    }
+
+    /// Called once the machine has stopped running.
+    fn finish(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+    // What the last node to receive a value took, so the machine can tell what was handed over
+    static TAKEN: Cell<Option<Number>> = const { Cell::new(None) };
+}
+
+// Without std there are no thread locals, but there's only ever one thread either. Nothing is
+// taken while it's `NOTHING_TAKEN`, which no value can be
+#[cfg(not(feature = "std"))]
+static TAKEN: AtomicI32 = AtomicI32::new(NOTHING_TAKEN);
+#[cfg(not(feature = "std"))]
+const NOTHING_TAKEN: i32 = i32::MIN;
+
+/// Takes the value `giver` is giving, for the node receiving it.
+pub fn take_value(giver: &mut dyn Node) -> Option<Number> {
+    let value = giver.give_value().take();
+    #[cfg(feature = "std")]
+    TAKEN.set(value);
+    #[cfg(not(feature = "std"))]
+    TAKEN.store(
+        value.map_or(NOTHING_TAKEN, |value| value.value().into()),
+        Ordering::Relaxed,
+    );
+    value
+}
+
+/// Reads from `node`, the neighbor in `direction`: asks for what it's writing that way, and takes it
+/// once it's being handed over.
+pub fn read_from(node: &mut dyn Node, direction: Direction) -> Option<Number> {
+    match node.give() {
+        DirectionGiving::None => None,
+        DirectionGiving::Any => match node.giving_to() {
+            None => {
+                node.set_giving_to(direction.opposite());
+                None
+            }
+            Some(prev_direction) => {
+                node.set_giving_to(prev_direction.min(direction.opposite()));
+                None
+            }
+        },
+        DirectionGiving::Direction(giving_direction) => {
+            if giving_direction == &direction.opposite() {
+                node.set_giving_to(direction.opposite());
+            }
+            None
+        }
+        DirectionGiving::Given => take_value(node),
+    }
+}
+
+/// The value taken by [`take_value`] since the last call, if any.
+#[cfg(feature = "std")]
+pub(crate) fn taken() -> Option<Number> {
+    TAKEN.take()
+}
+
+/// The value taken by [`take_value`] since the last call, if any.
+#[cfg(not(feature = "std"))]
+pub(crate) fn taken() -> Option<Number> {
+    // Only loading and storing, since not every microcontroller can swap atomically
+    let taken = TAKEN.load(Ordering::Relaxed);
+    TAKEN.store(NOTHING_TAKEN, Ordering::Relaxed);
+    (taken != NOTHING_TAKEN).then(|| Number::from(taken))
+}
+
+/// What a node is waiting on when the rest of the machine is quiet.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Waiting {
+    /// Nothing, so it won't do anything until its neighbors do.
+    Nothing,
+    /// Something from outside the machine, which can be blocked on with [`Node::wait_for_input`].
+    Input,
+    /// Nothing in particular, but it checks on something every cycle and may start doing something
+    /// at any time.
+    Polling,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DirectionGiving {
+    None,
+    Any,
+    Direction(Direction),
+    Given,
+}
@@ -0,0 +1,200 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    FromSample, SampleFormat, SizedSample, Stream, StreamConfig,
+};
+use enum_iterator::all;
+
+use crate::{direction::Direction, number::Number, position::Position};
+
+use super::{take_value, DirectionGiving, Node, Waiting};
+
+/// How many seconds of samples can be queued before the node stops accepting more, which keeps
+/// the program in step with what's being played.
+const BUFFERED_SECONDS: usize = 1;
+
+/// A node playing every value it receives as a sample, with -999 to 999 covering the full range.
+pub struct AudioOutNode {
+    position: Position,
+    samples: Arc<Mutex<VecDeque<f32>>>,
+    capacity: usize,
+    // Playback stops once the stream is dropped
+    _stream: Stream,
+
+    // Directions
+    up: Option<Rc<RefCell<dyn Node>>>,
+    down: Option<Rc<RefCell<dyn Node>>>,
+    left: Option<Rc<RefCell<dyn Node>>>,
+    right: Option<Rc<RefCell<dyn Node>>>,
+    above: Option<Rc<RefCell<dyn Node>>>,
+    below: Option<Rc<RefCell<dyn Node>>>,
+}
+
+impl AudioOutNode {
+    /// Plays samples at `rate` samples per second through the default audio device.
+    pub fn new(position: Position, rate: &str) -> Result<Self, String> {
+        let rate = rate
+            .parse::<u32>()
+            .ok()
+            .filter(|&rate| rate > 0)
+            .ok_or_else(|| format!("Invalid sample rate: '{}'", rate))?;
+        let error = |e: &dyn std::fmt::Display| format!("Couldn't play audio: {}", e);
+
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or_else(|| error(&"no output device"))?;
+        let supported = device.default_output_config().map_err(|e| error(&e))?;
+        let format = supported.sample_format();
+        let config = StreamConfig::from(supported);
+
+        let samples = Arc::new(Mutex::new(VecDeque::new()));
+        let stream = match format {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, rate, samples.clone()),
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, rate, samples.clone()),
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, rate, samples.clone()),
+            format => return Err(error(&format!("unsupported sample format {}", format))),
+        }
+        .map_err(|e| error(&e))?;
+        stream.play().map_err(|e| error(&e))?;
+
+        Ok(Self {
+            position,
+            samples,
+            capacity: rate as usize * BUFFERED_SECONDS,
+            _stream: stream,
+
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            above: None,
+            below: None,
+        })
+    }
+}
+
+/// Builds a stream resampling the queued samples from `rate` to whatever the device runs at.
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    rate: u32,
+    samples: Arc<Mutex<VecDeque<f32>>>,
+) -> Result<Stream, cpal::BuildStreamError>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    let step = rate as f64 / config.sample_rate.0 as f64;
+    let mut progress = 0.0;
+    let mut current = 0.0;
+
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _| {
+            let mut samples = samples.lock().unwrap();
+            for frame in data.chunks_mut(channels) {
+                progress += step;
+                while progress >= 1.0 {
+                    progress -= 1.0;
+                    // Running out of samples plays silence rather than repeating the last one
+                    current = samples.pop_front().unwrap_or(0.0);
+                }
+                frame.fill(T::from_sample(current));
+            }
+        },
+        |e| eprintln!("Audio playback failed: {}", e),
+        None,
+    )
+}
+
+impl Node for AudioOutNode {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        match dir {
+            Direction::Up => self.up = node,
+            Direction::Down => self.down = node,
+            Direction::Left => self.left = node,
+            Direction::Right => self.right = node,
+            Direction::Above => self.above = node,
+            Direction::Below => self.below = node,
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &DirectionGiving::None
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        None
+    }
+
+    fn set_giving_to(&mut self, _direction: Direction) {}
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        unreachable!("AudioOutNode does not give values");
+    }
+
+    fn tick(&mut self) {
+        let mut samples = self.samples.lock().unwrap();
+        if samples.len() >= self.capacity {
+            // Wait for the device to catch up, blocking the writers like a full port would
+            return;
+        }
+
+        for direction in all::<Direction>() {
+            if let Some(node) = match direction {
+                Direction::Up => self.up.as_mut(),
+                Direction::Down => self.down.as_mut(),
+                Direction::Left => self.left.as_mut(),
+                Direction::Right => self.right.as_mut(),
+                Direction::Above => self.above.as_mut(),
+                Direction::Below => self.below.as_mut(),
+            } {
+                let mut node = node.borrow_mut();
+                match node.give() {
+                    DirectionGiving::None => {}
+                    DirectionGiving::Any => match node.giving_to() {
+                        None => {
+                            node.set_giving_to(direction.opposite());
+                        }
+                        Some(prev_direction) => {
+                            node.set_giving_to(prev_direction.min(direction.opposite()));
+                        }
+                    },
+                    DirectionGiving::Direction(giving_direction) => {
+                        if giving_direction == &direction.opposite() {
+                            node.set_giving_to(direction.opposite());
+                        }
+                    }
+                    DirectionGiving::Given => {
+                        if let Some(value) = take_value(&mut *node) {
+                            samples.push_back(value.value() as f32 / 999.0);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_give(&mut self) {}
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        None
+    }
+
+    fn post_post_handle_give(&mut self) {}
+
+    // The device plays what's buffered on its own, making room for more
+    fn waiting(&self) -> Waiting {
+        Waiting::Polling
+    }
+}
@@ -0,0 +1,184 @@
+use alloc::{rc::Rc, vec::Vec};
+use core::cell::RefCell;
+
+use enum_iterator::all;
+
+use crate::{direction::Direction, number::Number, position::Position};
+
+use super::{take_value, DirectionGiving, Node};
+
+/// A node offering every value written to it to all of its other neighbors, only taking the next
+/// value once each of them (or the quorum, if set) has read it.
+pub struct BusNode {
+    position: Position,
+    value: Option<Number>,
+    // Neighbors that haven't read the current value yet
+    unread: Vec<Direction>,
+    // How many more reads complete the current value
+    remaining: usize,
+    quorum: Option<usize>,
+
+    // Directions
+    up: Option<Rc<RefCell<dyn Node>>>,
+    down: Option<Rc<RefCell<dyn Node>>>,
+    left: Option<Rc<RefCell<dyn Node>>>,
+    right: Option<Rc<RefCell<dyn Node>>>,
+    above: Option<Rc<RefCell<dyn Node>>>,
+    below: Option<Rc<RefCell<dyn Node>>>,
+
+    // Direction transmition
+    give: DirectionGiving,
+    giving_to: Option<Direction>,
+    give_value: Option<Number>,
+}
+
+impl BusNode {
+    pub fn new(position: Position) -> Self {
+        Self {
+            position,
+            value: None,
+            unread: Vec::new(),
+            remaining: 0,
+            quorum: None,
+
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            above: None,
+            below: None,
+
+            give: DirectionGiving::None,
+            giving_to: None,
+            give_value: None,
+        }
+    }
+
+    /// Completes a value once `quorum` neighbors have read it, instead of all of them.
+    pub fn with_quorum(mut self, quorum: usize) -> Self {
+        self.quorum = Some(quorum);
+        self
+    }
+
+    fn receive(&mut self, value: Number, from: Direction) {
+        self.unread = all::<Direction>()
+            .filter(|&direction| direction != from && self.neighbor(direction).is_some())
+            .collect();
+        self.remaining = match self.quorum {
+            Some(quorum) => quorum.min(self.unread.len()),
+            None => self.unread.len(),
+        };
+
+        // With nobody to read it the value just disappears
+        if self.remaining > 0 {
+            self.value = Some(value);
+            self.give = DirectionGiving::Any;
+        }
+    }
+
+    fn neighbor(&self, direction: Direction) -> Option<Rc<RefCell<dyn Node>>> {
+        match direction {
+            Direction::Up => self.up.clone(),
+            Direction::Down => self.down.clone(),
+            Direction::Left => self.left.clone(),
+            Direction::Right => self.right.clone(),
+            Direction::Above => self.above.clone(),
+            Direction::Below => self.below.clone(),
+        }
+    }
+}
+
+impl Node for BusNode {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        match dir {
+            Direction::Up => self.up = node,
+            Direction::Down => self.down = node,
+            Direction::Left => self.left = node,
+            Direction::Right => self.right = node,
+            Direction::Above => self.above = node,
+            Direction::Below => self.below = node,
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &self.give
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        self.giving_to
+    }
+
+    fn set_giving_to(&mut self, direction: Direction) {
+        // Every neighbor only gets to read each value once
+        if self.unread.contains(&direction) {
+            self.giving_to = Some(direction);
+        }
+    }
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        self.give_value = self.value;
+        &mut self.give_value
+    }
+
+    fn tick(&mut self) {
+        // The current value hasn't been read by everyone yet
+        if self.value.is_some() {
+            return;
+        }
+
+        for direction in all::<Direction>() {
+            if let Some(node) = self.neighbor(direction) {
+                let mut node = node.borrow_mut();
+                match node.give() {
+                    DirectionGiving::None => {}
+                    DirectionGiving::Any => match node.giving_to() {
+                        None => {
+                            node.set_giving_to(direction.opposite());
+                        }
+                        Some(prev_direction) => {
+                            node.set_giving_to(prev_direction.min(direction.opposite()));
+                        }
+                    },
+                    DirectionGiving::Direction(giving_direction) => {
+                        if giving_direction == &direction.opposite() {
+                            node.set_giving_to(direction.opposite());
+                        }
+                    }
+                    DirectionGiving::Given => {
+                        if let Some(value) = take_value(&mut *node) {
+                            self.receive(value, direction);
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_give(&mut self) {}
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        let giving_to = self.giving_to?;
+        self.give = DirectionGiving::Given;
+        Some(self.position.in_direction(giving_to))
+    }
+
+    fn post_post_handle_give(&mut self) {
+        if let Some(giving_to) = self.giving_to.take() {
+            self.unread.retain(|&direction| direction != giving_to);
+            self.remaining -= 1;
+        }
+
+        if self.remaining == 0 {
+            self.value = None;
+            self.unread.clear();
+            self.give = DirectionGiving::None;
+        } else {
+            self.give = DirectionGiving::Any;
+        }
+    }
+}
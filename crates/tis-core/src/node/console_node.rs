@@ -0,0 +1,382 @@
+use std::{cell::RefCell, io::Write, mem, rc::Rc};
+
+use enum_iterator::all;
+
+use crate::{
+    direction::Direction,
+    number::Number,
+    position::Position,
+    stream::{unless_interrupted, InputStream, OutputStream, Stdin},
+};
+
+use super::{take_value, DirectionGiving, Node, Waiting};
+
+pub struct ConsoleOutNode {
+    position: Position,
+    output: OutputStream,
+    outputs: Vec<Number>,
+    // Whether a neighbor was writing during the last tick while it was over the output rate
+    throttled: bool,
+    // Whether values are written as raw bytes instead of characters
+    binary: bool,
+    // The line written so far, if whole lines are written at once
+    line: Option<String>,
+
+    // Directions
+    up: Option<Rc<RefCell<dyn Node>>>,
+    down: Option<Rc<RefCell<dyn Node>>>,
+    left: Option<Rc<RefCell<dyn Node>>>,
+    right: Option<Rc<RefCell<dyn Node>>>,
+    above: Option<Rc<RefCell<dyn Node>>>,
+    below: Option<Rc<RefCell<dyn Node>>>,
+}
+
+impl ConsoleOutNode {
+    pub fn new(position: Position) -> Self {
+        Self {
+            position,
+            output: OutputStream::stdout(),
+            outputs: Vec::new(),
+            throttled: false,
+            binary: false,
+            line: None,
+
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            above: None,
+            below: None,
+        }
+    }
+
+    pub fn with_output(mut self, output: OutputStream) -> Self {
+        self.output = output;
+        self
+    }
+
+    pub fn with_binary(mut self, binary: bool) -> Self {
+        self.binary = binary;
+        self
+    }
+
+    /// Holds characters back until a 0 or a newline ends the line, then writes it all at once.
+    pub fn with_line_buffer(mut self, line_buffer: bool) -> Self {
+        self.line = line_buffer.then(String::new);
+        self
+    }
+
+    fn write(&mut self, value: i16) {
+        // Values that aren't bytes can't be written
+        if !(0..256).contains(&value) {
+            return;
+        }
+
+        if let Some(line) = &mut self.line {
+            if value == 0 || value == b'\n' as i16 {
+                writeln!(self.output, "{}", line).unwrap();
+                line.clear();
+            } else {
+                line.push(value as u8 as char);
+                return;
+            }
+        } else if self.binary {
+            self.output.write_all(&[value as u8]).unwrap();
+        } else {
+            write!(self.output, "{}", value as u8 as char).unwrap();
+        }
+        self.output.flush().unwrap();
+    }
+}
+
+impl Node for ConsoleOutNode {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        match dir {
+            Direction::Up => self.up = node,
+            Direction::Down => self.down = node,
+            Direction::Left => self.left = node,
+            Direction::Right => self.right = node,
+            Direction::Above => self.above = node,
+            Direction::Below => self.below = node,
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &DirectionGiving::None
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        None
+    }
+
+    fn set_giving_to(&mut self, _direction: Direction) {}
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        unreachable!("NumberConsoleOutNode does not give values");
+    }
+
+    fn tick(&mut self) {
+        // Over the rate, whatever's writing to this node has to wait like it would for a busy port
+        if !self.output.is_ready() {
+            let neighbors = [
+                &self.up,
+                &self.down,
+                &self.left,
+                &self.right,
+                &self.above,
+                &self.below,
+            ];
+            self.throttled = neighbors
+                .into_iter()
+                .flatten()
+                .any(|node| *node.borrow().give() != DirectionGiving::None);
+            return;
+        }
+        self.throttled = false;
+        for direction in all::<Direction>() {
+            if let Some(node) = match direction {
+                Direction::Up => self.up.clone(),
+                Direction::Down => self.down.clone(),
+                Direction::Left => self.left.clone(),
+                Direction::Right => self.right.clone(),
+                Direction::Above => self.above.clone(),
+                Direction::Below => self.below.clone(),
+            } {
+                let mut node = node.borrow_mut();
+                match node.give() {
+                    DirectionGiving::None => {}
+                    DirectionGiving::Any => match node.giving_to() {
+                        None => {
+                            node.set_giving_to(direction.opposite());
+                        }
+                        Some(prev_direction) => {
+                            node.set_giving_to(prev_direction.min(direction.opposite()));
+                        }
+                    },
+                    DirectionGiving::Direction(giving_direction) => {
+                        if giving_direction == &direction.opposite() {
+                            node.set_giving_to(direction.opposite());
+                        }
+                    }
+                    DirectionGiving::Given => {
+                        if let Some(value) = take_value(&mut *node) {
+                            self.outputs.push(value);
+                            self.output.count_value();
+                            self.write(value.value());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_give(&mut self) {}
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        None
+    }
+
+    fn post_post_handle_give(&mut self) {}
+
+    fn take_outputs(&mut self) -> Vec<Number> {
+        mem::take(&mut self.outputs)
+    }
+
+    fn waiting(&self) -> Waiting {
+        // Nothing else may be happening, but the next value can be written soon
+        if self.throttled {
+            Waiting::Polling
+        } else {
+            Waiting::Nothing
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        // Whatever's left of the last line still gets written
+        if let Some(line) = self.line.take().filter(|line| !line.is_empty()) {
+            write!(self.output, "{}", line)
+                .and_then(|_| self.output.flush())
+                .map_err(|e| format!("Couldn't write output: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+pub struct ConsoleInNode {
+    position: Position,
+    input: InputStream,
+    prompt: Option<String>,
+    echo: bool,
+    // Set once the input has ended, so it isn't read (and prompted for) again
+    ended: bool,
+    // Whether a signal came in before the last read could read anything, so input is still to come
+    interrupted: bool,
+    text_buffer: Option<String>,
+    // Whether input is read as raw bytes instead of lines of characters
+    binary: bool,
+
+    // Directions
+    up: Option<Rc<RefCell<dyn Node>>>,
+    down: Option<Rc<RefCell<dyn Node>>>,
+    left: Option<Rc<RefCell<dyn Node>>>,
+    right: Option<Rc<RefCell<dyn Node>>>,
+    above: Option<Rc<RefCell<dyn Node>>>,
+    below: Option<Rc<RefCell<dyn Node>>>,
+
+    // Direction transmition
+    give: DirectionGiving,
+    giving_to: Option<Direction>,
+    give_value: Option<Number>,
+}
+
+impl ConsoleInNode {
+    pub fn new(position: Position) -> Self {
+        Self {
+            position,
+            input: Box::new(Stdin),
+            prompt: None,
+            echo: true,
+            ended: false,
+            interrupted: false,
+            text_buffer: None,
+            binary: false,
+
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            above: None,
+            below: None,
+
+            give: DirectionGiving::Any,
+            giving_to: None,
+            give_value: None,
+        }
+    }
+
+    pub fn with_input(mut self, input: InputStream) -> Self {
+        self.input = input;
+        self
+    }
+
+    pub fn with_prompt(mut self, prompt: String) -> Self {
+        self.prompt = Some(prompt);
+        self
+    }
+
+    pub fn with_echo(mut self, echo: bool) -> Self {
+        self.echo = echo;
+        self
+    }
+
+    pub fn with_binary(mut self, binary: bool) -> Self {
+        self.binary = binary;
+        self
+    }
+}
+
+impl Node for ConsoleInNode {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        match dir {
+            Direction::Up => self.up = node,
+            Direction::Down => self.down = node,
+            Direction::Left => self.left = node,
+            Direction::Right => self.right = node,
+            Direction::Above => self.above = node,
+            Direction::Below => self.below = node,
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &self.give
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        self.giving_to
+    }
+
+    fn set_giving_to(&mut self, direction: Direction) {
+        self.giving_to = Some(direction);
+    }
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        self.interrupted = false;
+        if self.binary {
+            let Some(byte) = unless_interrupted(self.input.read_byte()) else {
+                self.interrupted = true;
+                return &mut self.give_value;
+            };
+            // Nothing is given once the input has ended
+            self.give_value = byte.map(i16::from).map(Number::from);
+            self.ended = self.give_value.is_none();
+            return &mut self.give_value;
+        }
+
+        if self.text_buffer.is_none() {
+            let mut input = String::new();
+            let Some(read) = unless_interrupted(self.input.read_line_prompted(
+                self.prompt.as_deref(),
+                self.echo,
+                &mut input,
+            )) else {
+                self.interrupted = true;
+                return &mut self.give_value;
+            };
+            if read == 0 {
+                // The input has ended, so there's nothing left to give
+                self.ended = true;
+                return &mut self.give_value;
+            }
+            self.text_buffer = Some(input.chars().rev().collect::<String>());
+        }
+
+        if let Some(text_buffer) = &mut self.text_buffer {
+            self.give_value = Some((text_buffer.pop().unwrap() as u8 as i16).into());
+            if text_buffer.is_empty() {
+                self.text_buffer = None;
+            }
+        }
+
+        &mut self.give_value
+    }
+
+    fn tick(&mut self) {}
+
+    fn handle_give(&mut self) {}
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        let giving_to = self.giving_to?;
+        self.give = DirectionGiving::Given;
+        Some(self.position.in_direction(giving_to))
+    }
+
+    fn post_post_handle_give(&mut self) {
+        self.give = if self.ended {
+            DirectionGiving::None
+        } else {
+            DirectionGiving::Any
+        };
+        self.giving_to = None;
+    }
+
+    fn is_halted(&self) -> bool {
+        self.ended
+    }
+
+    fn waiting(&self) -> Waiting {
+        // The read is tried again the next time a neighbor reads
+        if self.interrupted {
+            Waiting::Input
+        } else {
+            Waiting::Nothing
+        }
+    }
+}
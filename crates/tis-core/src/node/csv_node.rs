@@ -0,0 +1,157 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    fs::File,
+    io::{BufRead, BufReader},
+    rc::Rc,
+};
+
+use crate::{
+    direction::Direction,
+    number::{parse_numbers, Number},
+    position::Position,
+};
+
+use super::{DirectionGiving, Node};
+
+/// A node giving the numbers in a file row by row, where each row holds numbers separated by
+/// commas and/or whitespace. If a separator is set, it's given after every row.
+pub struct CsvInNode {
+    position: Position,
+    path: String,
+    reader: BufReader<File>,
+    separator: Option<Number>,
+    row: usize,
+    pending: VecDeque<Number>,
+
+    // Directions
+    up: Option<Rc<RefCell<dyn Node>>>,
+    down: Option<Rc<RefCell<dyn Node>>>,
+    left: Option<Rc<RefCell<dyn Node>>>,
+    right: Option<Rc<RefCell<dyn Node>>>,
+    above: Option<Rc<RefCell<dyn Node>>>,
+    below: Option<Rc<RefCell<dyn Node>>>,
+
+    // Direction transmition
+    give: DirectionGiving,
+    giving_to: Option<Direction>,
+    give_value: Option<Number>,
+}
+
+impl CsvInNode {
+    pub fn new(position: Position, path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("Couldn't open {}: {}", path, e))?;
+
+        let mut node = Self {
+            position,
+            path: path.to_owned(),
+            reader: BufReader::new(file),
+            separator: None,
+            row: 0,
+            pending: VecDeque::new(),
+
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            above: None,
+            below: None,
+
+            give: DirectionGiving::None,
+            giving_to: None,
+            give_value: None,
+        };
+        node.refill();
+        Ok(node)
+    }
+
+    pub fn with_separator(mut self, separator: Number) -> Self {
+        self.separator = Some(separator);
+        // The first row has already been read without it
+        if !self.pending.is_empty() {
+            self.pending.push_back(separator);
+        }
+        self
+    }
+
+    /// Reads rows until one has numbers in it, or the file ends.
+    fn refill(&mut self) {
+        let mut line = String::new();
+        while self.pending.is_empty() {
+            line.clear();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    eprintln!("Couldn't read {}: {}", self.path, e);
+                    break;
+                }
+            }
+            self.row += 1;
+
+            match parse_numbers(&line) {
+                Ok(numbers) if numbers.is_empty() => {}
+                Ok(numbers) => {
+                    self.pending.extend(numbers);
+                    self.pending.extend(self.separator);
+                }
+                Err(e) => eprintln!("Skipping row {} of {}: {}", self.row, self.path, e),
+            }
+        }
+
+        self.give = if self.pending.is_empty() {
+            DirectionGiving::None
+        } else {
+            DirectionGiving::Any
+        };
+    }
+}
+
+impl Node for CsvInNode {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        match dir {
+            Direction::Up => self.up = node,
+            Direction::Down => self.down = node,
+            Direction::Left => self.left = node,
+            Direction::Right => self.right = node,
+            Direction::Above => self.above = node,
+            Direction::Below => self.below = node,
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &self.give
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        self.giving_to
+    }
+
+    fn set_giving_to(&mut self, direction: Direction) {
+        self.giving_to = Some(direction);
+    }
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        self.give_value = self.pending.pop_front();
+        &mut self.give_value
+    }
+
+    fn tick(&mut self) {}
+
+    fn handle_give(&mut self) {}
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        let giving_to = self.giving_to?;
+        self.give = DirectionGiving::Given;
+        Some(self.position.in_direction(giving_to))
+    }
+
+    fn post_post_handle_give(&mut self) {
+        self.refill();
+        self.giving_to = None;
+    }
+}
@@ -0,0 +1,204 @@
+use alloc::{boxed::Box, rc::Rc, string::String};
+use core::cell::RefCell;
+
+use enum_iterator::all;
+
+use crate::{direction::Direction, number::Number, position::Position};
+
+use super::{take_value, DirectionGiving, Node, Waiting};
+
+/// A special node defined outside this crate, registered with [`Tis::with_node_type`].
+///
+/// Values written to the node from any side are handed to [`CustomNode::receive`], and values
+/// from [`CustomNode::next_value`] can be read from any side, so the node never has to deal with
+/// the ports itself.
+///
+/// [`Tis::with_node_type`]: crate::Tis::with_node_type
+pub trait CustomNode {
+    /// Called with every value written to the node.
+    fn receive(&mut self, _value: Number) {}
+
+    /// The next value to give to whichever node reads this one, if there's one ready. Once a
+    /// value is given out, it's held on to until it's read.
+    fn next_value(&mut self) -> Option<Number> {
+        None
+    }
+
+    /// Called once every cycle.
+    fn tick(&mut self) {}
+
+    /// Once this gives a code, the machine stops, and the program exits with the code.
+    fn exit_code(&self) -> Option<i32> {
+        None
+    }
+
+    /// Once this says what went wrong, the machine stops with it as an error.
+    fn trap(&self) -> Option<&str> {
+        None
+    }
+
+    /// Called once the machine has stopped running.
+    fn finish(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Runs a [`CustomNode`] inside the machine.
+pub struct CustomNodeAdapter {
+    position: Position,
+    node: Box<dyn CustomNode>,
+    // The value from the node that's waiting to be read
+    pending: Option<Number>,
+
+    // Directions
+    up: Option<Rc<RefCell<dyn Node>>>,
+    down: Option<Rc<RefCell<dyn Node>>>,
+    left: Option<Rc<RefCell<dyn Node>>>,
+    right: Option<Rc<RefCell<dyn Node>>>,
+    above: Option<Rc<RefCell<dyn Node>>>,
+    below: Option<Rc<RefCell<dyn Node>>>,
+
+    // Direction transmition
+    give: DirectionGiving,
+    giving_to: Option<Direction>,
+    give_value: Option<Number>,
+}
+
+impl CustomNodeAdapter {
+    pub fn new(position: Position, node: Box<dyn CustomNode>) -> Self {
+        Self {
+            position,
+            node,
+            pending: None,
+
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            above: None,
+            below: None,
+
+            give: DirectionGiving::None,
+            giving_to: None,
+            give_value: None,
+        }
+    }
+}
+
+impl Node for CustomNodeAdapter {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        match dir {
+            Direction::Up => self.up = node,
+            Direction::Down => self.down = node,
+            Direction::Left => self.left = node,
+            Direction::Right => self.right = node,
+            Direction::Above => self.above = node,
+            Direction::Below => self.below = node,
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &self.give
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        self.giving_to
+    }
+
+    fn set_giving_to(&mut self, direction: Direction) {
+        self.giving_to = Some(direction);
+    }
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        self.give_value = self.pending.take();
+        &mut self.give_value
+    }
+
+    fn tick(&mut self) {
+        for direction in all::<Direction>() {
+            if let Some(node) = match direction {
+                Direction::Up => self.up.clone(),
+                Direction::Down => self.down.clone(),
+                Direction::Left => self.left.clone(),
+                Direction::Right => self.right.clone(),
+                Direction::Above => self.above.clone(),
+                Direction::Below => self.below.clone(),
+            } {
+                let mut node = node.borrow_mut();
+                match node.give() {
+                    DirectionGiving::None => {}
+                    DirectionGiving::Any => match node.giving_to() {
+                        None => {
+                            node.set_giving_to(direction.opposite());
+                        }
+                        Some(prev_direction) => {
+                            node.set_giving_to(prev_direction.min(direction.opposite()));
+                        }
+                    },
+                    DirectionGiving::Direction(giving_direction) => {
+                        if giving_direction == &direction.opposite() {
+                            node.set_giving_to(direction.opposite());
+                        }
+                    }
+                    DirectionGiving::Given => {
+                        if let Some(value) = take_value(&mut *node) {
+                            self.node.receive(value);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Nothing new is asked for while a value is being read
+        if self.giving_to.is_none() {
+            if self.pending.is_none() {
+                self.pending = self.node.next_value();
+            }
+            self.give = match self.pending {
+                Some(_) => DirectionGiving::Any,
+                None => DirectionGiving::None,
+            };
+        }
+    }
+
+    // Nodes can be ticked more than once a cycle, but this only happens once
+    fn handle_give(&mut self) {
+        self.node.tick();
+    }
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        let giving_to = self.giving_to?;
+        self.give = DirectionGiving::Given;
+        Some(self.position.in_direction(giving_to))
+    }
+
+    fn post_post_handle_give(&mut self) {
+        // The reader wasn't ready to take the value after all, so it's given again later
+        if let Some(value) = self.give_value.take() {
+            self.pending = Some(value);
+        }
+        self.give = DirectionGiving::None;
+        self.giving_to = None;
+    }
+
+    fn exit_code(&self) -> Option<i32> {
+        self.node.exit_code()
+    }
+
+    fn trap(&self) -> Option<&str> {
+        self.node.trap()
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.node.finish()
+    }
+
+    // Nothing says what the node does every cycle, so it could be anything
+    fn waiting(&self) -> Waiting {
+        Waiting::Polling
+    }
+}
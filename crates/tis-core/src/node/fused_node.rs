@@ -0,0 +1,244 @@
+use alloc::{format, rc::Rc, string::String, vec, vec::Vec};
+use core::{
+    cell::{Cell, RefCell},
+    mem,
+};
+
+use crate::{
+    checkpoint::{save_transfer, StateReader},
+    direction::Direction,
+    number::Number,
+    position::Position,
+    tis::NodeView,
+};
+
+use super::{read_from, DirectionGiving, Node};
+
+/// Where a value is in one of the nodes of a chain, which each run `mov <port> <port>` and nothing
+/// else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    /// Waiting for the node before it to write something.
+    Reading,
+    /// Took a value during this cycle, which it only starts to write next cycle.
+    Taken(Number),
+    /// Took a value during the last cycle, and starts to write it in this one.
+    Read(Number),
+    /// Writing a value to the node after it.
+    Writing(Number),
+}
+
+/// A chain of nodes that only pass values on, run as a single node, see
+/// [`Tis::fuse`](crate::tis::Tis::fuse). Values move along the chain exactly as fast as they would
+/// through the nodes it stands in for, without running any of them. It sits at the position of the
+/// last node of the chain, with its input wired to whatever the first one read from.
+pub struct FusedNode {
+    position: Position,
+    input: Direction,
+    output: Direction,
+    source: Option<Rc<RefCell<dyn Node>>>,
+
+    // Every node of the chain, from the first to the last
+    stages: Vec<Stage>,
+    // The cycle the chain last ran in, so it can tell the node before it handing over a value apart
+    // from running
+    clock: Rc<Cell<usize>>,
+    cycle: usize,
+    // Whether a value moved along the chain during this cycle
+    moved: bool,
+
+    // Direction transmition
+    give: DirectionGiving,
+    give_value: Option<Number>,
+    giving_to: Option<Direction>,
+    handing_over: DirectionGiving,
+}
+
+impl FusedNode {
+    /// A chain of `length` nodes, the first reading from `input` and the last at `position` writing
+    /// to `output`.
+    pub fn new(
+        position: Position,
+        length: usize,
+        input: Direction,
+        output: Direction,
+        clock: Rc<Cell<usize>>,
+    ) -> Self {
+        Self {
+            position,
+            input,
+            output,
+            source: None,
+
+            stages: vec![Stage::Reading; length],
+            clock,
+            cycle: 0,
+            moved: false,
+
+            give: DirectionGiving::None,
+            give_value: None,
+            giving_to: None,
+            handing_over: DirectionGiving::None,
+        }
+    }
+
+    /// Has the first node of the chain read from the node before it, if it's waiting to.
+    fn read(&mut self) -> Option<Number> {
+        if self.stages[0] != Stage::Reading {
+            return None;
+        }
+        let source = self.source.clone()?;
+        let value = read_from(&mut *source.borrow_mut(), self.input);
+        value
+    }
+}
+
+impl Node for FusedNode {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        // Only the first node of the chain reads from anything
+        if dir == self.input {
+            self.source = node;
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &self.give
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        self.giving_to
+    }
+
+    fn set_giving_to(&mut self, direction: Direction) {
+        self.giving_to = Some(direction);
+    }
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        &mut self.give_value
+    }
+
+    fn tick(&mut self) {
+        // Ticked again during the same cycle by the node before the chain, handing over what the
+        // first node asked for. It only starts writing it next cycle, like any node would.
+        if self.cycle == self.clock.get() {
+            if let Some(value) = self.read() {
+                self.stages[0] = Stage::Read(value);
+                self.moved = true;
+            }
+            return;
+        }
+        self.cycle = self.clock.get();
+        self.moved = false;
+
+        if let Some(value) = self.read() {
+            self.stages[0] = Stage::Taken(value);
+            self.moved = true;
+        }
+
+        // Values are handed on between nodes of the chain as they were at the start of the cycle,
+        // so a node can't take a value the one before it took during the same cycle
+        for i in 1..self.stages.len() {
+            if let (Stage::Writing(value), Stage::Reading) = (self.stages[i - 1], self.stages[i]) {
+                self.stages[i - 1] = Stage::Reading;
+                self.stages[i] = Stage::Taken(value);
+                self.moved = true;
+            }
+        }
+        // The last node starts writing with everything else, see `handle_give`
+        let last = self.stages.len() - 1;
+        for stage in &mut self.stages[..last] {
+            *stage = match *stage {
+                Stage::Taken(value) => Stage::Read(value),
+                Stage::Read(value) => {
+                    self.moved = true;
+                    Stage::Writing(value)
+                }
+                stage => stage,
+            };
+        }
+    }
+
+    fn handle_give(&mut self) {
+        let last = self.stages.last_mut().unwrap();
+        match *last {
+            Stage::Taken(value) => *last = Stage::Read(value),
+            Stage::Read(value) => {
+                *last = Stage::Writing(value);
+                self.give = DirectionGiving::Direction(self.output);
+                self.give_value = Some(value);
+            }
+            Stage::Reading | Stage::Writing(_) => {}
+        }
+    }
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        let giving_to = self.giving_to?;
+        self.handing_over = mem::replace(&mut self.give, DirectionGiving::Given);
+
+        Some(self.position.in_direction(giving_to))
+    }
+
+    fn post_post_handle_give(&mut self) {
+        let handed_over = mem::replace(&mut self.handing_over, DirectionGiving::None);
+        if self.give_value.is_some() {
+            // The reader wasn't ready to take the value after all, so it's still being written
+            self.give = handed_over;
+        } else {
+            *self.stages.last_mut().unwrap() = Stage::Reading;
+            self.give = DirectionGiving::None;
+            self.moved = true;
+        }
+        self.giving_to = None;
+    }
+
+    fn is_blocked(&self) -> bool {
+        !self.moved
+    }
+
+    /// The chain counts as running its `mov` every cycle a value moves along it.
+    fn take_executed(&mut self) -> Option<usize> {
+        self.moved.then_some(0)
+    }
+
+    fn save_state(&self) -> Vec<i32> {
+        let mut state = Vec::new();
+        for stage in &self.stages {
+            state.extend(match *stage {
+                Stage::Reading => [0, 0],
+                Stage::Taken(value) => [1, value.value() as i32],
+                Stage::Read(value) => [2, value.value() as i32],
+                Stage::Writing(value) => [3, value.value() as i32],
+            });
+        }
+        save_transfer(&mut state, &self.give, self.giving_to, self.give_value);
+        state
+    }
+
+    fn load_state(&mut self, state: &[i32]) -> Result<(), String> {
+        let mut state = StateReader::new(state);
+        for stage in &mut self.stages {
+            *stage = match (state.word()?, state.number()?) {
+                (0, _) => Stage::Reading,
+                (1, value) => Stage::Taken(value),
+                (2, value) => Stage::Read(value),
+                (3, value) => Stage::Writing(value),
+                (kind, _) => return Err(format!("{} is not a valid stage of a chain", kind)),
+            };
+        }
+        (self.give, self.giving_to, self.give_value) = state.transfer()?;
+        state.finish()
+    }
+
+    fn view(&self) -> NodeView {
+        NodeView {
+            position: self.position,
+            registers: None,
+            pending: self.give_value,
+            blocked: self.is_blocked(),
+        }
+    }
+}
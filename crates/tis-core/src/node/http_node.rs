@@ -0,0 +1,365 @@
+#[cfg(feature = "async")]
+mod async_server;
+
+use std::{cell::RefCell, collections::VecDeque, net::TcpListener, rc::Rc};
+#[cfg(not(feature = "async"))]
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+use enum_iterator::all;
+
+use crate::{direction::Direction, number::Number, position::Position};
+
+use super::{take_value, DirectionGiving, Node, Waiting};
+
+// Requests are either read on the machine's thread, holding it up until they've been read in full,
+// or in the background with the async feature
+#[cfg(feature = "async")]
+use self::async_server::{Request as Connection, Server};
+#[cfg(not(feature = "async"))]
+type Connection = TcpStream;
+#[cfg(not(feature = "async"))]
+type Server = TcpListener;
+
+/// Ends both the request body given to the grid and the response body written by the grid.
+const SENTINEL: i16 = -1;
+
+/// The longest request body that's accepted. Its length comes straight from the client, and the
+/// grid only takes a byte a cycle anyway.
+const MAX_BODY: usize = 64 * 1024;
+
+/// How long a client can go without sending anything while its request is read, as the machine
+/// waits for it.
+#[cfg(not(feature = "async"))]
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Why a request was turned away before reaching the grid.
+#[derive(Debug, Clone, Copy)]
+enum Rejection {
+    /// The Content-Length header isn't a length.
+    BadRequest,
+    /// The body is longer than [`MAX_BODY`].
+    TooLarge,
+}
+
+impl Rejection {
+    /// The whole response turning the request away.
+    fn response(self) -> &'static str {
+        match self {
+            Self::BadRequest => {
+                "HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            }
+            Self::TooLarge => {
+                "HTTP/1.1 413 Content Too Large\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            }
+        }
+    }
+}
+
+/// A node serving HTTP requests one at a time. The body of each request is given to the grid a
+/// byte at a time followed by -1, and the bytes written back until a -1 are sent as the response.
+pub struct HttpNode {
+    position: Position,
+    server: Server,
+    // Where the response to the request being handled goes
+    connection: Option<Connection>,
+    request: VecDeque<Number>,
+    response: Vec<u8>,
+
+    // Directions
+    up: Option<Rc<RefCell<dyn Node>>>,
+    down: Option<Rc<RefCell<dyn Node>>>,
+    left: Option<Rc<RefCell<dyn Node>>>,
+    right: Option<Rc<RefCell<dyn Node>>>,
+    above: Option<Rc<RefCell<dyn Node>>>,
+    below: Option<Rc<RefCell<dyn Node>>>,
+
+    // Direction transmition
+    give: DirectionGiving,
+    giving_to: Option<Direction>,
+    give_value: Option<Number>,
+}
+
+impl HttpNode {
+    /// Listens on `address`, which is either a port on localhost or a full `host:port`.
+    pub fn new(position: Position, address: &str) -> Result<Self, String> {
+        let error = |e| format!("Couldn't listen on {}: {}", address, e);
+
+        let listener = if address.contains(':') {
+            TcpListener::bind(address)
+        } else {
+            let port = address
+                .parse::<u16>()
+                .map_err(|_| format!("Invalid port: {}", address))?;
+            TcpListener::bind(("127.0.0.1", port))
+        }
+        .map_err(error)?;
+        #[cfg(feature = "async")]
+        let server = Server::new(listener).map_err(error)?;
+        #[cfg(not(feature = "async"))]
+        let server = {
+            listener.set_nonblocking(true).map_err(error)?;
+            listener
+        };
+
+        Ok(Self {
+            position,
+            server,
+            connection: None,
+            request: VecDeque::new(),
+            response: Vec::new(),
+
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            above: None,
+            below: None,
+
+            give: DirectionGiving::None,
+            giving_to: None,
+            give_value: None,
+        })
+    }
+
+    #[cfg(not(feature = "async"))]
+    fn accept(&mut self) {
+        let Ok((stream, _)) = self.server.accept() else {
+            return;
+        };
+        match read_request(&stream) {
+            Ok(Ok(body)) => self.start(body, stream),
+            Ok(Err(rejection)) => reject(stream, rejection),
+            Err(_) => {}
+        }
+    }
+
+    #[cfg(feature = "async")]
+    fn accept(&mut self) {
+        if let Some(request) = self.server.poll() {
+            self.start_request(request);
+        }
+    }
+
+    #[cfg(feature = "async")]
+    fn start_request(&mut self, mut request: Connection) {
+        let body = std::mem::take(&mut request.body);
+        self.start(body, request);
+    }
+
+    /// Starts giving `body` to the grid, with the response going to `connection`.
+    fn start(&mut self, body: Vec<u8>, connection: Connection) {
+        self.request = body
+            .into_iter()
+            .map(Number::from)
+            .chain([Number::from(SENTINEL)])
+            .collect();
+        self.connection = Some(connection);
+        self.give = DirectionGiving::Any;
+    }
+
+    fn receive(&mut self, value: Number) {
+        match value.value() {
+            SENTINEL => {
+                if let Some(connection) = self.connection.take() {
+                    respond(connection, &self.response);
+                }
+                self.request.clear();
+                self.response.clear();
+                self.give = DirectionGiving::None;
+            }
+            value @ 0..=255 => self.response.push(value as u8),
+            _ => {}
+        }
+    }
+}
+
+/// Everything in a response before its body.
+fn response_head(length: usize) -> String {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        length
+    )
+}
+
+/// The length of the body, if `line` is the header giving it.
+fn content_length(line: &str) -> Option<Result<usize, Rejection>> {
+    let (name, value) = line.split_once(':')?;
+    if !name.eq_ignore_ascii_case("content-length") {
+        return None;
+    }
+    Some(match value.trim().parse() {
+        Ok(length) if length > MAX_BODY => Err(Rejection::TooLarge),
+        Ok(length) => Ok(length),
+        Err(_) => Err(Rejection::BadRequest),
+    })
+}
+
+#[cfg(not(feature = "async"))]
+fn respond(mut connection: Connection, body: &[u8]) {
+    // The client may have given up already, which isn't our problem
+    let _ = connection
+        .write_all(response_head(body.len()).as_bytes())
+        .and_then(|_| connection.write_all(body));
+}
+
+#[cfg(not(feature = "async"))]
+fn reject(mut connection: Connection, rejection: Rejection) {
+    // The client may have given up already, which isn't our problem
+    let _ = connection.write_all(rejection.response().as_bytes());
+}
+
+#[cfg(feature = "async")]
+fn respond(connection: Connection, body: &[u8]) {
+    connection.respond(body.to_vec());
+}
+
+/// Reads a request up to the end of its body, returning the body, or why it's turned away once
+/// its headers have been read.
+#[cfg(not(feature = "async"))]
+fn read_request(stream: &TcpStream) -> io::Result<Result<Vec<u8>, Rejection>> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+
+    let mut length = Ok(0);
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(content_length) = content_length(line) {
+            length = content_length;
+        }
+    }
+
+    let length = match length {
+        Ok(length) => length,
+        Err(rejection) => return Ok(Err(rejection)),
+    };
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(Ok(body))
+}
+
+impl Node for HttpNode {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        match dir {
+            Direction::Up => self.up = node,
+            Direction::Down => self.down = node,
+            Direction::Left => self.left = node,
+            Direction::Right => self.right = node,
+            Direction::Above => self.above = node,
+            Direction::Below => self.below = node,
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &self.give
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        self.giving_to
+    }
+
+    fn set_giving_to(&mut self, direction: Direction) {
+        self.giving_to = Some(direction);
+    }
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        self.give_value = self.request.pop_front();
+        &mut self.give_value
+    }
+
+    fn tick(&mut self) {
+        if self.connection.is_none() {
+            self.accept();
+            return;
+        }
+
+        for direction in all::<Direction>() {
+            if let Some(node) = match direction {
+                Direction::Up => self.up.clone(),
+                Direction::Down => self.down.clone(),
+                Direction::Left => self.left.clone(),
+                Direction::Right => self.right.clone(),
+                Direction::Above => self.above.clone(),
+                Direction::Below => self.below.clone(),
+            } {
+                let mut node = node.borrow_mut();
+                match node.give() {
+                    DirectionGiving::None => {}
+                    DirectionGiving::Any => match node.giving_to() {
+                        None => {
+                            node.set_giving_to(direction.opposite());
+                        }
+                        Some(prev_direction) => {
+                            node.set_giving_to(prev_direction.min(direction.opposite()));
+                        }
+                    },
+                    DirectionGiving::Direction(giving_direction) => {
+                        if giving_direction == &direction.opposite() {
+                            node.set_giving_to(direction.opposite());
+                        }
+                    }
+                    DirectionGiving::Given => {
+                        if let Some(value) = take_value(&mut *node) {
+                            self.receive(value);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_give(&mut self) {}
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        let giving_to = self.giving_to?;
+        self.give = DirectionGiving::Given;
+        Some(self.position.in_direction(giving_to))
+    }
+
+    fn post_post_handle_give(&mut self) {
+        self.give = if self.request.is_empty() || self.connection.is_none() {
+            DirectionGiving::None
+        } else {
+            DirectionGiving::Any
+        };
+        self.giving_to = None;
+    }
+
+    fn waiting(&self) -> Waiting {
+        match self.connection {
+            Some(_) => Waiting::Nothing,
+            None => Waiting::Input,
+        }
+    }
+
+    #[cfg(not(feature = "async"))]
+    fn wait_for_input(&mut self) {
+        // A failed switch only means spinning on the listener like before
+        if self.server.set_nonblocking(false).is_ok() {
+            self.accept();
+            let _ = self.server.set_nonblocking(true);
+        }
+    }
+
+    #[cfg(feature = "async")]
+    fn wait_for_input(&mut self) {
+        if let Some(request) = self.server.wait() {
+            self.start_request(request);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    io,
+    net::TcpListener,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        OnceLock,
+    },
+};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    runtime::{Builder, Runtime},
+    sync::oneshot,
+};
+
+use super::{content_length, response_head, Rejection};
+
+/// The runtime every server runs on, started the first time it's needed.
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("tis-io")
+            .enable_io()
+            .build()
+            .expect("Couldn't start the I/O runtime")
+    })
+}
+
+/// A request read in full in the background, waiting for the machine to write its response.
+pub(super) struct Request {
+    pub(super) body: Vec<u8>,
+    respond: oneshot::Sender<Vec<u8>>,
+}
+
+impl Request {
+    /// Sends `body` back to the client, if it's still there.
+    pub(super) fn respond(self, body: Vec<u8>) {
+        let _ = self.respond.send(body);
+    }
+}
+
+/// Accepts connections and reads requests in the background, handing them to the machine in the
+/// order they were read.
+pub(super) struct Server {
+    requests: Receiver<Request>,
+}
+
+impl Server {
+    pub(super) fn new(listener: TcpListener) -> io::Result<Self> {
+        let (sender, requests) = mpsc::channel();
+        listener.set_nonblocking(true)?;
+        let listener = {
+            let _guard = runtime().enter();
+            tokio::net::TcpListener::from_std(listener)?
+        };
+
+        runtime().spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, sender.clone()));
+            }
+        });
+        Ok(Self { requests })
+    }
+
+    /// The next request that's been read in full, if there is one.
+    pub(super) fn poll(&self) -> Option<Request> {
+        self.requests.try_recv().ok()
+    }
+
+    /// Blocks until a request has been read in full.
+    pub(super) fn wait(&self) -> Option<Request> {
+        self.requests.recv().ok()
+    }
+}
+
+/// Reads a request from `stream`, then writes back whatever the machine responds with.
+async fn serve(stream: TcpStream, requests: Sender<Request>) {
+    let mut stream = BufReader::new(stream);
+    let body = match read_request(&mut stream).await {
+        Ok(Ok(body)) => body,
+        Ok(Err(rejection)) => {
+            let _ = stream
+                .into_inner()
+                .write_all(rejection.response().as_bytes())
+                .await;
+            return;
+        }
+        Err(_) => return,
+    };
+
+    let (respond, response) = oneshot::channel();
+    if requests.send(Request { body, respond }).is_err() {
+        return;
+    }
+    let Ok(response) = response.await else {
+        return;
+    };
+    // The client may have given up already, which isn't our problem
+    let mut stream = stream.into_inner();
+    let _ = stream
+        .write_all(response_head(response.len()).as_bytes())
+        .await;
+    let _ = stream.write_all(&response).await;
+}
+
+/// Reads a request up to the end of its body, returning the body, or why it's turned away once
+/// its headers have been read.
+async fn read_request(stream: &mut BufReader<TcpStream>) -> io::Result<Result<Vec<u8>, Rejection>> {
+    let mut length = Ok(0);
+    loop {
+        let mut line = String::new();
+        if stream.read_line(&mut line).await? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(content_length) = content_length(line) {
+            length = content_length;
+        }
+    }
+
+    let length = match length {
+        Ok(length) => length,
+        Err(rejection) => return Ok(Err(rejection)),
+    };
+    let mut body = vec![0; length];
+    stream.read_exact(&mut body).await?;
+    Ok(Ok(body))
+}
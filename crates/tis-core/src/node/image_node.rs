@@ -0,0 +1,213 @@
+use std::{cell::RefCell, fs::File, io::BufWriter, rc::Rc};
+
+use enum_iterator::all;
+
+use crate::{direction::Direction, number::Number, position::Position};
+
+use super::{take_value, DirectionGiving, Node};
+
+const WIDTH: usize = 30;
+const HEIGHT: usize = 18;
+/// How many pixels wide and tall each cell is in a screenshot.
+const SCALE: usize = 8;
+
+/// Black, dark grey, light grey, white and red, just like the visualization module.
+const PALETTE: [[u8; 3]; 5] = [
+    [0x00, 0x00, 0x00],
+    [0x46, 0x46, 0x46],
+    [0x9c, 0x9c, 0x9c],
+    [0xfb, 0xfb, 0xfb],
+    [0xa5, 0x0e, 0x0e],
+];
+
+/// Saves a screenshot in addition to ending the current run of pixels.
+const SCREENSHOT: i16 = -2;
+
+/// A 30x18 image drawn into like the visualization module: the first two values are the x and y
+/// to start at, then every value after that colors the next cell to the right until a negative
+/// value is received.
+pub struct ImageNode {
+    position: Position,
+    cells: [[u8; WIDTH]; HEIGHT],
+    x: Option<i16>,
+    y: Option<i16>,
+    screenshot: Option<String>,
+
+    // Directions
+    up: Option<Rc<RefCell<dyn Node>>>,
+    down: Option<Rc<RefCell<dyn Node>>>,
+    left: Option<Rc<RefCell<dyn Node>>>,
+    right: Option<Rc<RefCell<dyn Node>>>,
+    above: Option<Rc<RefCell<dyn Node>>>,
+    below: Option<Rc<RefCell<dyn Node>>>,
+}
+
+impl ImageNode {
+    pub fn new(position: Position) -> Self {
+        Self {
+            position,
+            cells: [[0; WIDTH]; HEIGHT],
+            x: None,
+            y: None,
+            screenshot: None,
+
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            above: None,
+            below: None,
+        }
+    }
+
+    /// Sets where screenshots are saved to.
+    pub fn with_screenshot(mut self, path: String) -> Self {
+        self.screenshot = Some(path);
+        self
+    }
+
+    fn receive(&mut self, value: Number) {
+        let value = value.value();
+        if value < 0 {
+            self.x = None;
+            self.y = None;
+            if value == SCREENSHOT {
+                if let Err(e) = self.save_screenshot() {
+                    eprintln!("{}", e);
+                }
+            }
+            return;
+        }
+
+        match (self.x, self.y) {
+            (None, _) => self.x = Some(value),
+            (Some(_), None) => self.y = Some(value),
+            (Some(x), Some(y)) => {
+                if let Some(cell) = self
+                    .cells
+                    .get_mut(y as usize)
+                    .and_then(|row| row.get_mut(x as usize))
+                {
+                    // Unknown colors are drawn as black
+                    *cell = if (value as usize) < PALETTE.len() {
+                        value as u8
+                    } else {
+                        0
+                    };
+                }
+                self.x = Some(x.saturating_add(1));
+            }
+        }
+    }
+
+    fn save_screenshot(&self) -> Result<(), String> {
+        let Some(path) = &self.screenshot else {
+            return Ok(());
+        };
+        let error = |e: &dyn std::fmt::Display| format!("Couldn't save screenshot {}: {}", path, e);
+
+        let file = File::create(path).map_err(|e| error(&e))?;
+        let mut encoder = png::Encoder::new(
+            BufWriter::new(file),
+            (WIDTH * SCALE) as u32,
+            (HEIGHT * SCALE) as u32,
+        );
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut pixels = Vec::with_capacity(WIDTH * HEIGHT * SCALE * SCALE * 3);
+        for row in &self.cells {
+            for _ in 0..SCALE {
+                for &cell in row {
+                    for _ in 0..SCALE {
+                        pixels.extend_from_slice(&PALETTE[cell as usize]);
+                    }
+                }
+            }
+        }
+
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&pixels))
+            .map_err(|e| error(&e))
+    }
+}
+
+impl Node for ImageNode {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        match dir {
+            Direction::Up => self.up = node,
+            Direction::Down => self.down = node,
+            Direction::Left => self.left = node,
+            Direction::Right => self.right = node,
+            Direction::Above => self.above = node,
+            Direction::Below => self.below = node,
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &DirectionGiving::None
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        None
+    }
+
+    fn set_giving_to(&mut self, _direction: Direction) {}
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        unreachable!("ImageNode does not give values");
+    }
+
+    fn tick(&mut self) {
+        for direction in all::<Direction>() {
+            if let Some(node) = match direction {
+                Direction::Up => self.up.clone(),
+                Direction::Down => self.down.clone(),
+                Direction::Left => self.left.clone(),
+                Direction::Right => self.right.clone(),
+                Direction::Above => self.above.clone(),
+                Direction::Below => self.below.clone(),
+            } {
+                let mut node = node.borrow_mut();
+                match node.give() {
+                    DirectionGiving::None => {}
+                    DirectionGiving::Any => match node.giving_to() {
+                        None => {
+                            node.set_giving_to(direction.opposite());
+                        }
+                        Some(prev_direction) => {
+                            node.set_giving_to(prev_direction.min(direction.opposite()));
+                        }
+                    },
+                    DirectionGiving::Direction(giving_direction) => {
+                        if giving_direction == &direction.opposite() {
+                            node.set_giving_to(direction.opposite());
+                        }
+                    }
+                    DirectionGiving::Given => {
+                        if let Some(value) = take_value(&mut *node) {
+                            self.receive(value);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_give(&mut self) {}
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        None
+    }
+
+    fn post_post_handle_give(&mut self) {}
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.save_screenshot()
+    }
+}
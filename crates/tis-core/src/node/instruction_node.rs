@@ -0,0 +1,789 @@
+use alloc::{collections::VecDeque, format, rc::Rc, string::String, vec, vec::Vec};
+use core::{cell::RefCell, mem};
+
+use enum_iterator::all;
+use num_traits::{zero, Zero};
+
+use crate::{
+    checkpoint::{save_direction, save_transfer, StateReader},
+    compile::{compile, Step, RUN_AHEAD},
+    direction::Direction,
+    instruction::Instruction,
+    number::Number,
+    optimize::{optimize, Rewrite},
+    position::Position,
+    register::{Register, RegisterOrNumber, GENERAL_REGISTERS},
+    tis::{NodeView, Registers},
+};
+
+use super::{
+    read_from,
+    stack_node::{overflow, Overflow, DEFAULT_DEPTH},
+    take_value, DirectionGiving, Node,
+};
+
+/// How many `call`s a node can be in at once, before it stops the machine.
+pub const CALL_DEPTH: usize = 64;
+
+pub struct InstructionNode {
+    // Directions
+    ports: Ports,
+
+    core: Core,
+}
+
+/// The nodes an instruction node reads from, in every direction.
+#[derive(Default)]
+struct Ports {
+    up: Option<Rc<RefCell<dyn Node>>>,
+    down: Option<Rc<RefCell<dyn Node>>>,
+    left: Option<Rc<RefCell<dyn Node>>>,
+    right: Option<Rc<RefCell<dyn Node>>>,
+    above: Option<Rc<RefCell<dyn Node>>>,
+    below: Option<Rc<RefCell<dyn Node>>>,
+}
+
+/// An instruction node without its ports. It doesn't hold on to any other node, so it can tick on
+/// any thread, at least until it has to read from a port, see [`Core::tick_alone`].
+pub(crate) struct Core {
+    position: Position,
+
+    // Instructions
+    instructions: Vec<Instruction>,
+    ptr: usize,
+    // The instructions not using any port, once the node runs loops of them ahead of the machine
+    compiled: Option<Vec<Option<Step>>>,
+    // The instructions already run ahead, which are passed off as run one every cycle
+    ahead: VecDeque<usize>,
+
+    // Registers
+    accumulator: Number,
+    backup: Number,
+    general: [Number; GENERAL_REGISTERS],
+    last: Option<Direction>,
+
+    // The stack of the stack extension, along with how many values it holds and what happens when
+    // one more is pushed
+    stack: Vec<Number>,
+    depth: usize,
+    overflow: Overflow,
+    // Where every `call` the node is in returns to, the last one made being last
+    calls: Vec<usize>,
+    // The data of the dat extension, along with the value `rdn` reads next
+    data: Vec<Number>,
+    cursor: usize,
+
+    // What the node ran into that it can't go on from, once it has
+    trap: Option<String>,
+    // Whether the last instruction couldn't complete because a read had nothing to read
+    blocked: bool,
+    // Whether the last tick stopped at reading from a port, as it had none to read from
+    at_port: bool,
+    // The pointer of the last instruction that completed, until the machine asks for it
+    executed: Option<usize>,
+
+    // Direction transmition
+    give: DirectionGiving,
+    give_value: Option<Number>,
+    giving_to: Option<Direction>,
+    // Where the value the last instruction started writing goes, until it's being written
+    write: DirectionGiving,
+    // How the value being handed over was written, in case the reader doesn't take it after all
+    handing_over: DirectionGiving,
+}
+
+impl InstructionNode {
+    pub fn new(position: Position, instructions: Vec<Instruction>) -> Self {
+        Self {
+            ports: Ports::default(),
+
+            core: Core::new(position, instructions),
+        }
+    }
+
+    pub fn with_accumulator(mut self, accumulator: Number) -> Self {
+        self.core.accumulator = accumulator;
+        self
+    }
+
+    pub fn with_backup(mut self, backup: Number) -> Self {
+        self.core.backup = backup;
+        self
+    }
+
+    pub fn with_last(mut self, last: Direction) -> Self {
+        self.core.last = Some(last);
+        self
+    }
+
+    /// Sets how many values the stack of the node holds.
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.core.depth = depth;
+        self
+    }
+
+    /// Sets what happens when a value is pushed onto the stack of the node while it's full.
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.core.overflow = overflow;
+        self
+    }
+
+    /// Gives the node data to read, from its `.dat` lines.
+    pub fn with_data(mut self, data: Vec<Number>) -> Self {
+        self.core.data = data;
+        self
+    }
+
+    pub fn with_pointer(mut self, pointer: usize) -> Self {
+        self.core.ptr = pointer;
+        self
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.core.instructions
+    }
+
+    /// Swaps the instructions for ones doing the same in fewer, see [`optimize`].
+    pub fn optimize(&mut self) -> Vec<Rewrite> {
+        let (instructions, ptr, rewrites) = optimize(&self.core.instructions, self.core.ptr);
+        self.core.instructions = instructions;
+        self.core.ptr = ptr;
+        rewrites
+    }
+
+    /// Has the node run instructions that don't use any port ahead of the machine, many at a time,
+    /// and only catch up one cycle at a time without running anything. Whatever happens on the
+    /// ports still happens on the same cycle, but ACC, BAK and the pointer are where they'll be
+    /// once the node has caught up.
+    pub fn run_ahead(&mut self) {
+        self.core.compiled = Some(compile(&self.core.instructions));
+    }
+
+    /// Takes over the instructions of `node`, along with its registers and stack unless `keep_state` is set,
+    /// in which case any value being written is still written too.
+    pub fn reload(&mut self, node: InstructionNode, keep_state: bool) {
+        self.core.reload(node.core, keep_state);
+    }
+
+    /// Everything but the ports, to tick on another thread.
+    pub(crate) fn core(&mut self) -> &mut Core {
+        &mut self.core
+    }
+
+    /// Finishes the tick started by [`Core::tick_alone`], which means ticking all over again with
+    /// the ports if it stopped at reading from one.
+    pub(crate) fn finish_tick(&mut self) {
+        if self.core.at_port {
+            self.tick();
+        }
+    }
+}
+
+impl Ports {
+    fn get(&mut self, direction: Direction) -> Option<&mut Rc<RefCell<dyn Node>>> {
+        match direction {
+            Direction::Up => self.up.as_mut(),
+            Direction::Down => self.down.as_mut(),
+            Direction::Left => self.left.as_mut(),
+            Direction::Right => self.right.as_mut(),
+            Direction::Above => self.above.as_mut(),
+            Direction::Below => self.below.as_mut(),
+        }
+    }
+
+    fn connected(&self) -> impl Iterator<Item = &Rc<RefCell<dyn Node>>> {
+        [
+            &self.up,
+            &self.down,
+            &self.left,
+            &self.right,
+            &self.above,
+            &self.below,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    fn read(&mut self, direction: Direction) -> Option<Number> {
+        read_from(&mut *self.get(direction)?.borrow_mut(), direction)
+    }
+
+    fn read_any(&mut self) -> Option<Number> {
+        for direction in all::<Direction>() {
+            if let Some(node) = self.get(direction) {
+                let mut node = node.borrow_mut();
+                match node.give() {
+                    DirectionGiving::None => {}
+                    DirectionGiving::Any => match node.giving_to() {
+                        None => {
+                            node.set_giving_to(direction.opposite());
+                            return None;
+                        }
+                        Some(prev_direction) => {
+                            node.set_giving_to(prev_direction.min(direction.opposite()));
+                            return None;
+                        }
+                    },
+                    DirectionGiving::Direction(giving_direction) => {
+                        if giving_direction == &direction.opposite() {
+                            node.set_giving_to(direction.opposite());
+                            return None;
+                        }
+                    }
+                    DirectionGiving::Given => {
+                        return take_value(&mut *node);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Core {
+    fn new(position: Position, instructions: Vec<Instruction>) -> Self {
+        Self {
+            position,
+
+            instructions,
+            ptr: 0,
+            compiled: None,
+            ahead: VecDeque::new(),
+
+            accumulator: Number::new(),
+            backup: Number::new(),
+            general: [Number::new(); GENERAL_REGISTERS],
+            last: None,
+
+            stack: Vec::new(),
+            depth: DEFAULT_DEPTH,
+            overflow: Overflow::Block,
+            calls: Vec::new(),
+            data: Vec::new(),
+            cursor: 0,
+
+            trap: None,
+            blocked: false,
+            at_port: false,
+            executed: None,
+
+            give: DirectionGiving::None,
+            give_value: None,
+            giving_to: None,
+            write: DirectionGiving::None,
+            handing_over: DirectionGiving::None,
+        }
+    }
+
+    fn reload(&mut self, node: Core, keep_state: bool) {
+        self.instructions = node.instructions;
+        self.ptr = node.ptr;
+        (self.depth, self.overflow) = (node.depth, node.overflow);
+        // Where calls return to only means anything in the code they were made from
+        self.calls = node.calls;
+        self.data = node.data;
+        self.ahead.clear();
+        if self.compiled.is_some() {
+            self.compiled = Some(compile(&self.instructions));
+        }
+
+        if !keep_state {
+            self.accumulator = node.accumulator;
+            self.backup = node.backup;
+            self.general = node.general;
+            self.stack = node.stack;
+            self.cursor = node.cursor;
+            self.last = node.last;
+            self.blocked = false;
+            self.give = DirectionGiving::None;
+            self.give_value = None;
+            self.giving_to = None;
+            self.write = DirectionGiving::None;
+        }
+        // The new data may not go as far as what was read of the old
+        if self.cursor >= self.data.len() {
+            self.cursor = 0;
+        }
+    }
+
+    /// Stops the node, and with it the machine, with `message`.
+    fn fail(&mut self, message: String) {
+        self.trap = Some(message);
+        self.blocked = false;
+    }
+
+    /// Ticks the node without reading from any of its ports, which can happen on any thread. If
+    /// the tick gets to reading from one, it stops right there, having done nothing, see
+    /// [`InstructionNode::finish_tick`].
+    pub(crate) fn tick_alone(&mut self) {
+        self.tick(None);
+    }
+
+    fn get_value(&mut self, register: Register, ports: Option<&mut Ports>) -> Option<Number> {
+        match register {
+            Register::Accumulator => Some(self.accumulator),
+            Register::Backup => Some(self.backup),
+            Register::General(register) => Some(self.general[register as usize]),
+            Register::Nil => Some(zero()),
+            Register::Direction(_) | Register::Any => {
+                let Some(ports) = ports else {
+                    self.at_port = true;
+                    return None;
+                };
+                match register {
+                    Register::Direction(direction) => ports.read(direction),
+                    _ => ports.read_any(),
+                }
+            }
+            Register::Last => match self.last {
+                None => Some(zero()),
+                Some(direction) => self.get_value(Register::Direction(direction), ports),
+            },
+        }
+    }
+
+    fn get_from_register_or_number(
+        &mut self,
+        register_or_number: RegisterOrNumber,
+        ports: Option<&mut Ports>,
+    ) -> Option<Number> {
+        match register_or_number {
+            RegisterOrNumber::Register(register) => self.get_value(register, ports),
+            RegisterOrNumber::Number(number) => Some(number),
+        }
+    }
+
+    fn set_value(&mut self, register: Register, value: Number) -> bool {
+        match register {
+            Register::Accumulator => {
+                self.accumulator = value;
+                false
+            }
+            Register::Backup => unreachable!("BAK can't be written to"),
+            Register::General(register) => {
+                self.general[register as usize] = value;
+                false
+            }
+            Register::Nil => false,
+            Register::Direction(direction) => {
+                self.give_value = Some(value);
+                self.write = DirectionGiving::Direction(direction);
+                true
+            }
+            Register::Any => {
+                self.give_value = Some(value);
+                self.write = DirectionGiving::Any;
+                true
+            }
+            Register::Last => match self.last {
+                Some(last) => {
+                    self.give_value = Some(value);
+                    self.write = DirectionGiving::Direction(last);
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+
+    /// Runs the next instruction, reading from `ports`. Without them, reading from a port does
+    /// nothing but leave the node where it was, as if there was nothing to read.
+    fn tick(&mut self, ports: Option<&mut Ports>) {
+        self.at_port = false;
+        if let Some(pointer) = self.ahead.pop_front() {
+            self.executed = Some(pointer);
+            return;
+        }
+        if self.instructions.is_empty() || self.trap.is_some() || self.give != DirectionGiving::None
+        {
+            return;
+        }
+
+        if self.ptr >= self.instructions.len() {
+            self.ptr = 0;
+        }
+
+        if let Some(compiled) = &self.compiled {
+            if compiled[self.ptr].is_some() {
+                let mut ptr = self.ptr;
+                while self.ahead.len() < RUN_AHEAD {
+                    if ptr >= compiled.len() {
+                        ptr = 0;
+                    }
+                    let Some(step) = &compiled[ptr] else {
+                        break;
+                    };
+                    self.ahead.push_back(ptr);
+                    ptr = step(&mut self.accumulator, &mut self.backup);
+                }
+                self.ptr = ptr;
+                self.blocked = false;
+                self.executed = self.ahead.pop_front();
+                return;
+            }
+        }
+
+        let pointer = self.ptr;
+        let instruction = self.instructions[self.ptr]
+            .conditional_move(self.accumulator)
+            .unwrap_or(Instruction::Noop);
+
+        // Any early return below means a read had nothing to read
+        self.blocked = true;
+
+        let mut skip_ptr_incr = false;
+        let mut jump = |ptr: usize| {
+            skip_ptr_incr = true;
+            self.ptr = ptr;
+        };
+
+        match instruction {
+            Instruction::Move(source, destination) => {
+                let Some(value) = self.get_from_register_or_number(source, ports) else {
+                    return;
+                };
+                skip_ptr_incr = self.set_value(destination, value);
+            }
+
+            Instruction::Swap => {
+                (self.accumulator, self.backup) = (self.backup, self.accumulator);
+            }
+            Instruction::Save => {
+                self.backup = self.accumulator;
+            }
+
+            Instruction::Add(source) => {
+                let Some(value) = self.get_from_register_or_number(source, ports) else {
+                    return;
+                };
+                self.accumulator += value;
+            }
+            Instruction::Subtract(source) => {
+                let Some(value) = self.get_from_register_or_number(source, ports) else {
+                    return;
+                };
+                self.accumulator -= value;
+            }
+            Instruction::Negate => {
+                self.accumulator = -self.accumulator;
+            }
+
+            Instruction::Jump(ptr) => jump(ptr),
+
+            Instruction::JumpEqualZero(ptr) if self.accumulator.is_zero() => jump(ptr),
+            Instruction::JumpNotZero(ptr) if !self.accumulator.is_zero() => jump(ptr),
+
+            Instruction::JumpGreaterThanZero(ptr) if self.accumulator > zero() => jump(ptr),
+            Instruction::JumpLessThanZero(ptr) if self.accumulator < zero() => jump(ptr),
+
+            Instruction::Push(source) => {
+                let full = self.stack.len() >= self.depth;
+                // Nothing can make room while the node waits, but it waits all the same, just like
+                // when writing to a full stack memory node
+                if full && self.overflow == Overflow::Block {
+                    return;
+                }
+                let Some(value) = self.get_from_register_or_number(source, ports) else {
+                    return;
+                };
+                match (full, self.overflow) {
+                    (false, _) => self.stack.push(value),
+                    (true, Overflow::Trap) => {
+                        return self.fail(overflow(value, self.depth));
+                    }
+                    (true, _) => {}
+                }
+            }
+            Instruction::Pop(destination) => {
+                // An empty stack has nothing to read, like an empty stack memory node
+                let Some(value) = self.stack.pop() else {
+                    return;
+                };
+                skip_ptr_incr = self.set_value(destination, value);
+            }
+
+            Instruction::Call(ptr) => {
+                if self.calls.len() >= CALL_DEPTH {
+                    return self.fail(format!(
+                        "Call stack overflow, instruction {} called with {} calls already made",
+                        pointer, CALL_DEPTH
+                    ));
+                }
+                self.calls.push(pointer + 1);
+                jump(ptr);
+            }
+            Instruction::Return => {
+                let Some(ptr) = self.calls.pop() else {
+                    return self.fail(format!(
+                        "Call stack underflow, instruction {} returned without a call",
+                        pointer
+                    ));
+                };
+                jump(ptr);
+            }
+
+            Instruction::ReadNext(destination) => {
+                let Some(&value) = self.data.get(self.cursor) else {
+                    return self.fail(String::from("Data read, but the node has no data"));
+                };
+                self.cursor = (self.cursor + 1) % self.data.len();
+                skip_ptr_incr = self.set_value(destination, value);
+            }
+            Instruction::ReadIndexed(destination) => {
+                let index = self.accumulator.value();
+                let Some(&value) = usize::try_from(index)
+                    .ok()
+                    .and_then(|index| self.data.get(index))
+                else {
+                    return self.fail(format!(
+                        "Data read out of range, {} isn't an index of its {} values",
+                        index,
+                        self.data.len()
+                    ));
+                };
+                skip_ptr_incr = self.set_value(destination, value);
+            }
+
+            Instruction::JumpRelative(source) => {
+                skip_ptr_incr = true;
+                self.ptr = (self.ptr as i32
+                    + match self.get_from_register_or_number(source, ports) {
+                        Some(number) => number,
+                        None => return,
+                    }
+                    .value() as i32)
+                    .max(0) as usize;
+            }
+
+            _ => {}
+        }
+
+        self.blocked = false;
+        self.executed = Some(pointer);
+        if !skip_ptr_incr {
+            self.ptr += 1;
+        }
+    }
+
+    fn handle_give(&mut self) {
+        if self.give == DirectionGiving::None && self.write != DirectionGiving::None {
+            self.give = mem::replace(&mut self.write, DirectionGiving::None);
+            self.ptr += 1;
+        }
+    }
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        let giving_to = self.giving_to?;
+        self.handing_over = mem::replace(&mut self.give, DirectionGiving::Given);
+
+        Some(self.position.in_direction(giving_to))
+    }
+
+    fn post_post_handle_give(&mut self) {
+        let handed_over = mem::replace(&mut self.handing_over, DirectionGiving::None);
+        if self.give_value.is_some() {
+            // The reader wasn't ready to take the value after all, so it's still being written
+            self.give = handed_over;
+        } else {
+            if handed_over == DirectionGiving::Any {
+                self.last = self.giving_to;
+            }
+            self.give = DirectionGiving::None;
+        }
+        self.giving_to = None;
+    }
+
+    fn is_blocked(&self) -> bool {
+        self.blocked || self.give != DirectionGiving::None
+    }
+
+    fn view(&self) -> NodeView {
+        NodeView {
+            position: self.position,
+            registers: Some(Registers {
+                accumulator: self.accumulator,
+                backup: self.backup,
+                general: self.general,
+                pointer: self.ptr,
+                last: self.last,
+            }),
+            pending: self.give_value,
+            blocked: self.is_blocked(),
+        }
+    }
+
+    fn save_state(&self) -> Vec<i32> {
+        let mut state = vec![
+            self.ptr as i32,
+            self.accumulator.value() as i32,
+            self.backup.value() as i32,
+            self.blocked as i32,
+        ];
+        save_direction(&mut state, self.last);
+        save_transfer(&mut state, &self.give, self.giving_to, self.give_value);
+        // Only saved once they're used, so checkpoints of nodes without them stay as they were
+        if self.general.iter().any(|register| !register.is_zero())
+            || !self.stack.is_empty()
+            || !self.calls.is_empty()
+            || self.cursor != 0
+        {
+            state.extend(self.general.iter().map(|register| register.value() as i32));
+            state.push(self.stack.len() as i32);
+            state.extend(self.stack.iter().map(|value| value.value() as i32));
+            state.push(self.cursor as i32);
+            state.extend(self.calls.iter().map(|&ptr| ptr as i32));
+        }
+        state
+    }
+
+    fn load_state(&mut self, state: &[i32]) -> Result<(), String> {
+        let mut state = StateReader::new(state);
+        self.ahead.clear();
+        let ptr = state.word()?;
+        self.ptr = usize::try_from(ptr)
+            .ok()
+            .filter(|&ptr| ptr <= self.instructions.len())
+            .ok_or_else(|| format!("{} is not a valid instruction pointer", ptr))?;
+        self.accumulator = state.number()?;
+        self.backup = state.number()?;
+        self.blocked = state.word()? != 0;
+        self.last = state.direction()?;
+        (self.give, self.giving_to, self.give_value) = state.transfer()?;
+        self.write = DirectionGiving::None;
+        self.general = [Number::new(); GENERAL_REGISTERS];
+        self.stack.clear();
+        self.calls.clear();
+        self.cursor = 0;
+        if state.is_empty() {
+            return Ok(());
+        }
+        for register in &mut self.general {
+            *register = state.number()?;
+        }
+        let len = state.word()?;
+        let len = usize::try_from(len)
+            .ok()
+            .filter(|&len| len <= self.depth)
+            .ok_or_else(|| format!("{} is not a valid number of values on the stack", len))?;
+        for _ in 0..len {
+            self.stack.push(state.number()?);
+        }
+        let cursor = state.word()?;
+        self.cursor = usize::try_from(cursor)
+            .ok()
+            .filter(|&cursor| cursor == 0 || cursor < self.data.len())
+            .ok_or_else(|| format!("{} is not a valid place in the data", cursor))?;
+        while !state.is_empty() {
+            let ptr = state.word()?;
+            let ptr = usize::try_from(ptr)
+                .ok()
+                .filter(|&ptr| ptr <= self.instructions.len())
+                .ok_or_else(|| format!("{} is not a valid place to return to", ptr))?;
+            self.calls.push(ptr);
+        }
+        if self.calls.len() > CALL_DEPTH {
+            return Err(format!(
+                "{} is not a valid number of calls",
+                self.calls.len()
+            ));
+        }
+        state.finish()
+    }
+}
+
+impl Node for InstructionNode {
+    fn position(&self) -> Position {
+        self.core.position
+    }
+
+    fn set_dir(&mut self, direction: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        match direction {
+            Direction::Up => self.ports.up = node,
+            Direction::Down => self.ports.down = node,
+            Direction::Left => self.ports.left = node,
+            Direction::Right => self.ports.right = node,
+            Direction::Above => self.ports.above = node,
+            Direction::Below => self.ports.below = node,
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &self.core.give
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        self.core.giving_to
+    }
+
+    fn set_giving_to(&mut self, direction: Direction) {
+        self.core.giving_to = Some(direction);
+    }
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        &mut self.core.give_value
+    }
+
+    fn tick(&mut self) {
+        self.core.tick(Some(&mut self.ports));
+    }
+
+    fn handle_give(&mut self) {
+        self.core.handle_give();
+    }
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        self.core.post_handle_give()
+    }
+
+    fn post_post_handle_give(&mut self) {
+        self.core.post_post_handle_give();
+    }
+
+    fn is_blocked(&self) -> bool {
+        self.core.is_blocked()
+    }
+
+    fn is_stuck(&self) -> bool {
+        if self.core.instructions.is_empty() {
+            return true;
+        }
+        // Writing a value nobody has asked for yet
+        if self.core.give != DirectionGiving::None {
+            return self.core.giving_to.is_none();
+        }
+        // Reading when none of the neighbors are writing
+        self.core.blocked
+            && self
+                .ports
+                .connected()
+                .all(|node| *node.borrow().give() == DirectionGiving::None)
+    }
+
+    fn is_running_ahead(&self) -> bool {
+        !self.core.ahead.is_empty()
+    }
+
+    fn trap(&self) -> Option<&str> {
+        self.core.trap.as_deref()
+    }
+
+    fn take_executed(&mut self) -> Option<usize> {
+        self.core.executed.take()
+    }
+
+    fn as_instruction_node(&mut self) -> Option<&mut InstructionNode> {
+        Some(self)
+    }
+
+    fn view(&self) -> NodeView {
+        self.core.view()
+    }
+
+    fn save_state(&self) -> Vec<i32> {
+        self.core.save_state()
+    }
+
+    fn load_state(&mut self, state: &[i32]) -> Result<(), String> {
+        self.core.load_state(state)
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    cell::{Cell, RefCell},
+    fs::{File, OpenOptions},
+    io::{BufWriter, Write},
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use enum_iterator::all;
+
+use crate::{direction::Direction, number::Number, position::Position};
+
+use super::{take_value, DirectionGiving, Node};
+
+/// Appends every value it receives to a log file, along with when it was received.
+pub struct LogOutNode {
+    position: Position,
+    log: BufWriter<File>,
+    clock: Rc<Cell<usize>>,
+
+    // Directions
+    up: Option<Rc<RefCell<dyn Node>>>,
+    down: Option<Rc<RefCell<dyn Node>>>,
+    left: Option<Rc<RefCell<dyn Node>>>,
+    right: Option<Rc<RefCell<dyn Node>>>,
+    above: Option<Rc<RefCell<dyn Node>>>,
+    below: Option<Rc<RefCell<dyn Node>>>,
+}
+
+impl LogOutNode {
+    pub fn new(position: Position, path: &str, clock: Rc<Cell<usize>>) -> Result<Self, String> {
+        let log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Couldn't open log {}: {}", path, e))?;
+
+        Ok(Self {
+            position,
+            log: BufWriter::new(log),
+            clock,
+
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            above: None,
+            below: None,
+        })
+    }
+}
+
+impl Node for LogOutNode {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        match dir {
+            Direction::Up => self.up = node,
+            Direction::Down => self.down = node,
+            Direction::Left => self.left = node,
+            Direction::Right => self.right = node,
+            Direction::Above => self.above = node,
+            Direction::Below => self.below = node,
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &DirectionGiving::None
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        None
+    }
+
+    fn set_giving_to(&mut self, _direction: Direction) {}
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        unreachable!("LogOutNode does not give values");
+    }
+
+    fn tick(&mut self) {
+        for direction in all::<Direction>() {
+            if let Some(node) = match direction {
+                Direction::Up => self.up.as_mut(),
+                Direction::Down => self.down.as_mut(),
+                Direction::Left => self.left.as_mut(),
+                Direction::Right => self.right.as_mut(),
+                Direction::Above => self.above.as_mut(),
+                Direction::Below => self.below.as_mut(),
+            } {
+                let mut node = node.borrow_mut();
+                match node.give() {
+                    DirectionGiving::None => {}
+                    DirectionGiving::Any => match node.giving_to() {
+                        None => {
+                            node.set_giving_to(direction.opposite());
+                        }
+                        Some(prev_direction) => {
+                            node.set_giving_to(prev_direction.min(direction.opposite()));
+                        }
+                    },
+                    DirectionGiving::Direction(giving_direction) => {
+                        if giving_direction == &direction.opposite() {
+                            node.set_giving_to(direction.opposite());
+                        }
+                    }
+                    DirectionGiving::Given => {
+                        if let Some(value) = take_value(&mut *node) {
+                            writeln!(
+                                self.log,
+                                "{} cycle {}: {}",
+                                format_timestamp(SystemTime::now()),
+                                self.clock.get(),
+                                value
+                            )
+                            .unwrap();
+                            // Flushed right away so the log can be followed while running
+                            self.log.flush().unwrap();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_give(&mut self) {}
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        None
+    }
+
+    fn post_post_handle_give(&mut self) {}
+}
+
+/// Formats a time as an RFC 3339 UTC timestamp with millisecond precision.
+fn format_timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs();
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+
+    // Converts days since the epoch to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let days = days as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        since_epoch.subsec_millis()
+    )
+}
@@ -0,0 +1,183 @@
+use alloc::rc::Rc;
+use core::cell::RefCell;
+
+use enum_iterator::all;
+
+use crate::{direction::Direction, number::Number, position::Position};
+
+use super::{take_value, DirectionGiving, Node};
+
+const MULTIPLY: i16 = 0;
+const DIVIDE: i16 = 1;
+const MODULO: i16 = 2;
+const MIN: i16 = 3;
+const MAX: i16 = 4;
+
+/// A node doing the arithmetic instruction nodes can't. It's written an operation, then the two
+/// operands, after which the result can be read:
+///
+/// - 0 multiplies
+/// - 1 divides, rounding towards zero
+/// - 2 gives the remainder of dividing, with the sign of the first operand
+/// - 3 gives the smaller operand
+/// - 4 gives the larger operand
+///
+/// Results are clamped like any other value, while dividing by zero and unknown operations give 0.
+pub struct MathNode {
+    position: Position,
+    operation: Option<i16>,
+    first: Option<i16>,
+    result: Option<Number>,
+
+    // Directions
+    up: Option<Rc<RefCell<dyn Node>>>,
+    down: Option<Rc<RefCell<dyn Node>>>,
+    left: Option<Rc<RefCell<dyn Node>>>,
+    right: Option<Rc<RefCell<dyn Node>>>,
+    above: Option<Rc<RefCell<dyn Node>>>,
+    below: Option<Rc<RefCell<dyn Node>>>,
+
+    // Direction transmition
+    give: DirectionGiving,
+    giving_to: Option<Direction>,
+    give_value: Option<Number>,
+}
+
+impl MathNode {
+    pub fn new(position: Position) -> Self {
+        Self {
+            position,
+            operation: None,
+            first: None,
+            result: None,
+
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            above: None,
+            below: None,
+
+            give: DirectionGiving::None,
+            giving_to: None,
+            give_value: None,
+        }
+    }
+
+    fn receive(&mut self, value: Number) {
+        let value = value.value();
+        let (Some(operation), Some(first)) = (self.operation, self.first) else {
+            if self.operation.is_none() {
+                self.operation = Some(value);
+            } else {
+                self.first = Some(value);
+            }
+            return;
+        };
+
+        let (first, second) = (first as i32, value as i32);
+        let result = match operation {
+            MULTIPLY => first * second,
+            DIVIDE if second != 0 => first / second,
+            MODULO if second != 0 => first % second,
+            MIN => first.min(second),
+            MAX => first.max(second),
+            _ => 0,
+        };
+
+        self.operation = None;
+        self.first = None;
+        self.result = Some(result.into());
+        self.give = DirectionGiving::Any;
+    }
+}
+
+impl Node for MathNode {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        match dir {
+            Direction::Up => self.up = node,
+            Direction::Down => self.down = node,
+            Direction::Left => self.left = node,
+            Direction::Right => self.right = node,
+            Direction::Above => self.above = node,
+            Direction::Below => self.below = node,
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &self.give
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        self.giving_to
+    }
+
+    fn set_giving_to(&mut self, direction: Direction) {
+        self.giving_to = Some(direction);
+    }
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        self.give_value = self.result;
+        &mut self.give_value
+    }
+
+    fn tick(&mut self) {
+        // Nothing new is started until the result has been read
+        if self.result.is_some() {
+            return;
+        }
+
+        for direction in all::<Direction>() {
+            if let Some(node) = match direction {
+                Direction::Up => self.up.clone(),
+                Direction::Down => self.down.clone(),
+                Direction::Left => self.left.clone(),
+                Direction::Right => self.right.clone(),
+                Direction::Above => self.above.clone(),
+                Direction::Below => self.below.clone(),
+            } {
+                let mut node = node.borrow_mut();
+                match node.give() {
+                    DirectionGiving::None => {}
+                    DirectionGiving::Any => match node.giving_to() {
+                        None => {
+                            node.set_giving_to(direction.opposite());
+                        }
+                        Some(prev_direction) => {
+                            node.set_giving_to(prev_direction.min(direction.opposite()));
+                        }
+                    },
+                    DirectionGiving::Direction(giving_direction) => {
+                        if giving_direction == &direction.opposite() {
+                            node.set_giving_to(direction.opposite());
+                        }
+                    }
+                    DirectionGiving::Given => {
+                        if let Some(value) = take_value(&mut *node) {
+                            self.receive(value);
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_give(&mut self) {}
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        let giving_to = self.giving_to?;
+        self.give = DirectionGiving::Given;
+        Some(self.position.in_direction(giving_to))
+    }
+
+    fn post_post_handle_give(&mut self) {
+        self.result = None;
+        self.give = DirectionGiving::None;
+        self.giving_to = None;
+    }
+}
@@ -0,0 +1,270 @@
+use std::{cell::RefCell, fs::OpenOptions, rc::Rc};
+
+use enum_iterator::all;
+use memmap2::MmapMut;
+
+use crate::{
+    checkpoint::{save_transfer, StateReader},
+    direction::Direction,
+    number::Number,
+    position::Position,
+};
+
+use super::{take_value, DirectionGiving, Node};
+
+/// The number of cells in a shared memory file, one for every non-negative value.
+const SHARED_CELLS: usize = 1000;
+
+/// Where the cells of a memory node are stored.
+enum Cells {
+    /// A memory-mapped file, so other processes can watch and modify the cells.
+    Shared(MmapMut),
+    Ram(Vec<Number>),
+}
+
+impl Cells {
+    fn len(&self) -> usize {
+        match self {
+            Self::Shared(memory) => memory.len() / 2,
+            Self::Ram(memory) => memory.len(),
+        }
+    }
+}
+
+/// A node giving programs random access memory.
+///
+/// Writing a value sets the address. After that, reading gives the value stored at the address,
+/// while writing stores the value at the address. Either way the address is then cleared.
+pub struct MemoryNode {
+    position: Position,
+    cells: Cells,
+    address: Option<usize>,
+
+    // Directions
+    up: Option<Rc<RefCell<dyn Node>>>,
+    down: Option<Rc<RefCell<dyn Node>>>,
+    left: Option<Rc<RefCell<dyn Node>>>,
+    right: Option<Rc<RefCell<dyn Node>>>,
+    above: Option<Rc<RefCell<dyn Node>>>,
+    below: Option<Rc<RefCell<dyn Node>>>,
+
+    // Direction transmition
+    give: DirectionGiving,
+    giving_to: Option<Direction>,
+    give_value: Option<Number>,
+}
+
+impl MemoryNode {
+    /// A memory backed by the file at `path`.
+    pub fn shared(position: Position, path: &str) -> Result<Self, String> {
+        let error = |e| format!("Couldn't map shared memory {}: {}", path, e);
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(error)?;
+        if file.metadata().map_err(error)?.len() < (SHARED_CELLS * 2) as u64 {
+            file.set_len((SHARED_CELLS * 2) as u64).map_err(error)?;
+        }
+        // SAFETY: the mapping is meant to be shared with other processes, a cell being changed
+        // underneath us just means another process wrote to it
+        let memory = unsafe { MmapMut::map_mut(&file) }.map_err(error)?;
+
+        Ok(Self::new(position, Cells::Shared(memory)))
+    }
+
+    /// A memory private to the program, with `size` cells.
+    pub fn ram(position: Position, size: &str) -> Result<Self, String> {
+        let size = size
+            .parse::<usize>()
+            .ok()
+            .filter(|&size| size > 0)
+            .ok_or_else(|| format!("Invalid RAM size: '{}'", size))?;
+
+        Ok(Self::new(position, Cells::Ram(vec![Number::from(0); size])))
+    }
+
+    fn new(position: Position, cells: Cells) -> Self {
+        Self {
+            position,
+            cells,
+            address: None,
+
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            above: None,
+            below: None,
+
+            give: DirectionGiving::None,
+            giving_to: None,
+            give_value: None,
+        }
+    }
+
+    fn read(&self, address: usize) -> Number {
+        match &self.cells {
+            Cells::Shared(memory) => Number::from(i16::from_le_bytes([
+                memory[address * 2],
+                memory[address * 2 + 1],
+            ])),
+            Cells::Ram(memory) => memory[address],
+        }
+    }
+
+    fn write(&mut self, address: usize, value: Number) {
+        match &mut self.cells {
+            Cells::Shared(memory) => {
+                memory[address * 2..address * 2 + 2].copy_from_slice(&value.value().to_le_bytes())
+            }
+            Cells::Ram(memory) => memory[address] = value,
+        }
+    }
+
+    fn receive(&mut self, value: Number) {
+        match self.address.take() {
+            Some(address) => {
+                self.write(address, value);
+                self.give = DirectionGiving::None;
+            }
+            None => {
+                // Negative addresses wrap around to the end of the memory
+                self.address =
+                    Some((value.value() as i32).rem_euclid(self.cells.len() as i32) as usize);
+                self.give = DirectionGiving::Any;
+            }
+        }
+    }
+}
+
+impl Node for MemoryNode {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        match dir {
+            Direction::Up => self.up = node,
+            Direction::Down => self.down = node,
+            Direction::Left => self.left = node,
+            Direction::Right => self.right = node,
+            Direction::Above => self.above = node,
+            Direction::Below => self.below = node,
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &self.give
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        self.giving_to
+    }
+
+    fn set_giving_to(&mut self, direction: Direction) {
+        self.giving_to = Some(direction);
+    }
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        // Read as late as possible so changes from other processes are seen
+        if let Some(address) = self.address {
+            self.give_value = Some(self.read(address));
+        }
+
+        &mut self.give_value
+    }
+
+    fn tick(&mut self) {
+        // A value being read can't be overwritten at the same time
+        if self.giving_to.is_some() {
+            return;
+        }
+
+        for direction in all::<Direction>() {
+            if let Some(node) = match direction {
+                Direction::Up => self.up.clone(),
+                Direction::Down => self.down.clone(),
+                Direction::Left => self.left.clone(),
+                Direction::Right => self.right.clone(),
+                Direction::Above => self.above.clone(),
+                Direction::Below => self.below.clone(),
+            } {
+                let mut node = node.borrow_mut();
+                match node.give() {
+                    DirectionGiving::None => {}
+                    DirectionGiving::Any => match node.giving_to() {
+                        None => {
+                            node.set_giving_to(direction.opposite());
+                        }
+                        Some(prev_direction) => {
+                            node.set_giving_to(prev_direction.min(direction.opposite()));
+                        }
+                    },
+                    DirectionGiving::Direction(giving_direction) => {
+                        if giving_direction == &direction.opposite() {
+                            node.set_giving_to(direction.opposite());
+                        }
+                    }
+                    DirectionGiving::Given => {
+                        if let Some(value) = take_value(&mut *node) {
+                            self.receive(value);
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_give(&mut self) {}
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        let giving_to = self.giving_to?;
+        self.give = DirectionGiving::Given;
+        Some(self.position.in_direction(giving_to))
+    }
+
+    fn post_post_handle_give(&mut self) {
+        self.address = None;
+        self.give = DirectionGiving::None;
+        self.giving_to = None;
+    }
+
+    fn save_state(&self) -> Vec<i32> {
+        let mut state = vec![self.address.map_or(-1, |address| address as i32)];
+        save_transfer(&mut state, &self.give, self.giving_to, self.give_value);
+        // Shared memory lives on in its file
+        if let Cells::Ram(memory) = &self.cells {
+            state.extend(memory.iter().map(|value| value.value() as i32));
+        }
+        state
+    }
+
+    fn load_state(&mut self, state: &[i32]) -> Result<(), String> {
+        let mut state = StateReader::new(state);
+        let address = state.word()?;
+        self.address = match address {
+            -1 => None,
+            _ => Some(
+                usize::try_from(address)
+                    .ok()
+                    .filter(|&address| address < self.cells.len())
+                    .ok_or_else(|| format!("{} is not a valid address", address))?,
+            ),
+        };
+        (self.give, self.giving_to, self.give_value) = state.transfer()?;
+
+        // Only RAM has its cells saved
+        let cells = state.numbers()?;
+        match &mut self.cells {
+            Cells::Ram(memory) if memory.len() == cells.len() => *memory = cells,
+            Cells::Shared(_) if cells.is_empty() => {}
+            _ => return Err("The saved memory is a different size".to_owned()),
+        }
+        Ok(())
+    }
+}
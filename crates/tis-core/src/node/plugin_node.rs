@@ -0,0 +1,130 @@
+use std::fs;
+
+use wasmi::{Engine, Instance, Linker, Module, Store, TypedFunc};
+
+use crate::number::Number;
+
+use super::custom_node::CustomNode;
+
+/// A node whose behavior comes from a WebAssembly module. Every export is optional:
+///
+/// - `init(ptr: i32, len: i32)` is called once with the argument given after the path, which is
+///   written to `memory` at the pointer returned by `alloc(len: i32) -> i32`.
+/// - `on_write(value: i32)` is called with every value written to the node.
+/// - `on_read() -> i32` gives the next value to be read, anything outside -999..=999 meaning
+///   there's nothing to read yet.
+/// - `tick()` is called once every cycle.
+pub struct PluginNode {
+    path: String,
+    store: Store<()>,
+    on_write: Option<TypedFunc<i32, ()>>,
+    on_read: Option<TypedFunc<(), i32>>,
+    tick: Option<TypedFunc<(), ()>>,
+    // How the plugin trapped, once it has
+    trap: Option<String>,
+}
+
+impl PluginNode {
+    pub fn load(path: &str, argument: Option<&str>) -> Result<Self, String> {
+        let error = |e: wasmi::Error| format!("Couldn't load plugin {}: {}", path, e);
+
+        let wasm = fs::read(path).map_err(|e| format!("Couldn't read plugin {}: {}", path, e))?;
+        let engine = Engine::default();
+        let module = Module::new(&engine, &wasm).map_err(error)?;
+        let mut store = Store::new(&engine, ());
+        let instance = Linker::<()>::new(&engine)
+            .instantiate(&mut store, &module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(error)?;
+
+        init(path, &instance, &mut store, argument)?;
+
+        Ok(Self {
+            path: path.to_owned(),
+            on_write: instance.get_typed_func(&store, "on_write").ok(),
+            on_read: instance.get_typed_func(&store, "on_read").ok(),
+            tick: instance.get_typed_func(&store, "tick").ok(),
+            store,
+            trap: None,
+        })
+    }
+
+    fn trapped(&mut self, e: wasmi::Error) {
+        self.trap
+            .get_or_insert_with(|| format!("Plugin {} trapped: {}", self.path, e));
+    }
+}
+
+/// Hands the argument to the plugin's `init`.
+fn init(
+    path: &str,
+    instance: &Instance,
+    store: &mut Store<()>,
+    argument: Option<&str>,
+) -> Result<(), String> {
+    let Ok(init) = instance.get_typed_func::<(i32, i32), ()>(&*store, "init") else {
+        return match argument {
+            Some(_) => Err(format!("Plugin {} doesn't take an argument", path)),
+            None => Ok(()),
+        };
+    };
+    let error = |e: wasmi::Error| format!("Couldn't initialize plugin {}: {}", path, e);
+
+    let argument = argument.unwrap_or_default().as_bytes();
+    let mut ptr = 0;
+    if !argument.is_empty() {
+        let (Ok(alloc), Some(memory)) = (
+            instance.get_typed_func::<i32, i32>(&*store, "alloc"),
+            instance.get_memory(&*store, "memory"),
+        ) else {
+            return Err(format!(
+                "Plugin {} needs to export alloc and memory to take an argument",
+                path
+            ));
+        };
+        ptr = alloc
+            .call(&mut *store, argument.len() as i32)
+            .map_err(error)?;
+        memory
+            .write(&mut *store, ptr as usize, argument)
+            .map_err(|e| format!("Couldn't initialize plugin {}: {}", path, e))?;
+    }
+    init.call(store, (ptr, argument.len() as i32))
+        .map_err(error)
+}
+
+impl CustomNode for PluginNode {
+    fn receive(&mut self, value: Number) {
+        if let Some(on_write) = self.on_write {
+            if let Err(e) = on_write.call(&mut self.store, value.value() as i32) {
+                self.trapped(e);
+            }
+        }
+    }
+
+    fn next_value(&mut self) -> Option<Number> {
+        let on_read = self.on_read?;
+        match on_read.call(&mut self.store, ()) {
+            Ok(value) => i16::try_from(value)
+                .ok()
+                .filter(|value| (-999..=999).contains(value))
+                .map(Number::from),
+            Err(e) => {
+                self.trapped(e);
+                None
+            }
+        }
+    }
+
+    fn tick(&mut self) {
+        if let Some(tick) = self.tick {
+            if let Err(e) = tick.call(&mut self.store, ()) {
+                self.trapped(e);
+            }
+        }
+    }
+
+    fn trap(&self) -> Option<&str> {
+        self.trap.as_deref()
+    }
+}
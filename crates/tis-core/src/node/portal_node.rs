@@ -0,0 +1,168 @@
+use alloc::rc::Rc;
+use core::cell::RefCell;
+
+use enum_iterator::all;
+
+use crate::{direction::Direction, number::Number, position::Position};
+
+use super::{take_value, DirectionGiving, Node};
+
+/// Holds a value on its way from one portal to its twin.
+type Slot = Rc<RefCell<Option<Number>>>;
+
+/// One side of a pair of portals, to be turned into a node.
+pub struct PortalEnd {
+    outgoing: Slot,
+    incoming: Slot,
+}
+
+impl PortalEnd {
+    pub fn pair() -> (Self, Self) {
+        let there = Slot::default();
+        let back = Slot::default();
+        (
+            Self {
+                outgoing: there.clone(),
+                incoming: back.clone(),
+            },
+            Self {
+                outgoing: back,
+                incoming: there,
+            },
+        )
+    }
+}
+
+/// A node linked to a twin somewhere else on the grid, so a value written into one can be read
+/// from the other as if they were next to each other.
+pub struct PortalNode {
+    position: Position,
+    end: PortalEnd,
+
+    // Directions
+    up: Option<Rc<RefCell<dyn Node>>>,
+    down: Option<Rc<RefCell<dyn Node>>>,
+    left: Option<Rc<RefCell<dyn Node>>>,
+    right: Option<Rc<RefCell<dyn Node>>>,
+    above: Option<Rc<RefCell<dyn Node>>>,
+    below: Option<Rc<RefCell<dyn Node>>>,
+
+    // Direction transmition
+    give: DirectionGiving,
+    giving_to: Option<Direction>,
+    give_value: Option<Number>,
+}
+
+impl PortalNode {
+    pub fn new(position: Position, end: PortalEnd) -> Self {
+        Self {
+            position,
+            end,
+
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            above: None,
+            below: None,
+
+            give: DirectionGiving::None,
+            giving_to: None,
+            give_value: None,
+        }
+    }
+}
+
+impl Node for PortalNode {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        match dir {
+            Direction::Up => self.up = node,
+            Direction::Down => self.down = node,
+            Direction::Left => self.left = node,
+            Direction::Right => self.right = node,
+            Direction::Above => self.above = node,
+            Direction::Below => self.below = node,
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &self.give
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        self.giving_to
+    }
+
+    fn set_giving_to(&mut self, direction: Direction) {
+        self.giving_to = Some(direction);
+    }
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        self.give_value = self.end.incoming.borrow_mut().take();
+        &mut self.give_value
+    }
+
+    fn tick(&mut self) {
+        // A value may have come through from the twin since the last cycle
+        if self.give == DirectionGiving::None && self.end.incoming.borrow().is_some() {
+            self.give = DirectionGiving::Any;
+        }
+
+        // The twin hasn't passed the last value on yet
+        if self.end.outgoing.borrow().is_some() {
+            return;
+        }
+
+        for direction in all::<Direction>() {
+            if let Some(node) = match direction {
+                Direction::Up => self.up.clone(),
+                Direction::Down => self.down.clone(),
+                Direction::Left => self.left.clone(),
+                Direction::Right => self.right.clone(),
+                Direction::Above => self.above.clone(),
+                Direction::Below => self.below.clone(),
+            } {
+                let mut node = node.borrow_mut();
+                match node.give() {
+                    DirectionGiving::None => {}
+                    DirectionGiving::Any => match node.giving_to() {
+                        None => {
+                            node.set_giving_to(direction.opposite());
+                        }
+                        Some(prev_direction) => {
+                            node.set_giving_to(prev_direction.min(direction.opposite()));
+                        }
+                    },
+                    DirectionGiving::Direction(giving_direction) => {
+                        if giving_direction == &direction.opposite() {
+                            node.set_giving_to(direction.opposite());
+                        }
+                    }
+                    DirectionGiving::Given => {
+                        if let Some(value) = take_value(&mut *node) {
+                            *self.end.outgoing.borrow_mut() = Some(value);
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_give(&mut self) {}
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        let giving_to = self.giving_to?;
+        self.give = DirectionGiving::Given;
+        Some(self.position.in_direction(giving_to))
+    }
+
+    fn post_post_handle_give(&mut self) {
+        self.give = DirectionGiving::None;
+        self.giving_to = None;
+    }
+}
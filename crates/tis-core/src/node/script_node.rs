@@ -0,0 +1,118 @@
+use rhai::{CallFnOptions, Dynamic, Engine, FuncArgs, Map, Scope, AST, INT};
+
+use crate::number::Number;
+
+use super::custom_node::CustomNode;
+
+/// A node whose behavior comes from a Rhai script. Every function is optional, and they all share
+/// the node's state through `this`, which starts out as an empty object map:
+///
+/// - `init(argument)` is called once with the argument given after the path.
+/// - `on_write(value)` is called with every value written to the node.
+/// - `on_read()` gives the next value to be read, `()` meaning there's nothing to read yet.
+/// - `tick()` is called once every cycle.
+pub struct ScriptNode {
+    path: String,
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    state: Dynamic,
+    // How the script failed, once it has
+    failure: Option<String>,
+}
+
+impl ScriptNode {
+    pub fn load(path: &str, argument: Option<&str>) -> Result<Self, String> {
+        let engine = Engine::new();
+        let ast = engine
+            .compile_file(path.into())
+            .map_err(|e| format!("Couldn't load script {}: {}", path, e))?;
+
+        let mut node = Self {
+            path: path.to_owned(),
+            engine,
+            ast,
+            scope: Scope::new(),
+            state: Map::new().into(),
+            failure: None,
+        };
+
+        match argument {
+            _ if node.has_function("init", 1) => {
+                let argument = argument.unwrap_or_default().to_owned();
+                if let Err(e) = node.call("init", (argument,)) {
+                    return Err(format!("Couldn't initialize script {}: {}", path, e));
+                }
+            }
+            Some(_) => return Err(format!("Script {} doesn't take an argument", path)),
+            None => {}
+        }
+
+        Ok(node)
+    }
+
+    fn has_function(&self, name: &str, params: usize) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|function| function.name == name && function.params.len() == params)
+    }
+
+    fn call(&mut self, name: &str, args: impl FuncArgs) -> Result<Dynamic, String> {
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.state);
+        self.engine
+            .call_fn_with_options(options, &mut self.scope, &self.ast, name, args)
+            .map_err(|e| e.to_string())
+    }
+
+    fn fail(&mut self, e: String) {
+        self.failure
+            .get_or_insert_with(|| format!("Script {} failed: {}", self.path, e));
+    }
+}
+
+impl CustomNode for ScriptNode {
+    fn receive(&mut self, value: Number) {
+        if self.has_function("on_write", 1) {
+            if let Err(e) = self.call("on_write", (value.value() as INT,)) {
+                self.fail(e);
+            }
+        }
+    }
+
+    fn next_value(&mut self) -> Option<Number> {
+        if !self.has_function("on_read", 0) {
+            return None;
+        }
+        let value = match self.call("on_read", ()) {
+            Ok(value) => value,
+            Err(e) => {
+                self.fail(e);
+                return None;
+            }
+        };
+        if value.is_unit() {
+            return None;
+        }
+        match value.as_int() {
+            Ok(value) => Some(Number::from(value)),
+            Err(kind) => {
+                self.fail(format!("on_read gave a {} instead of a value", kind));
+                None
+            }
+        }
+    }
+
+    fn tick(&mut self) {
+        if self.has_function("tick", 0) {
+            if let Err(e) = self.call("tick", ()) {
+                self.fail(e);
+            }
+        }
+    }
+
+    fn trap(&self) -> Option<&str> {
+        self.failure.as_deref()
+    }
+}
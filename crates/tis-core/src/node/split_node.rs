@@ -0,0 +1,164 @@
+use alloc::rc::Rc;
+use core::cell::RefCell;
+
+use enum_iterator::all;
+
+use crate::{direction::Direction, number::Number, position::Position};
+
+use super::{take_value, DirectionGiving, Node};
+
+/// A node passing every value it's given on to two outputs, either taking turns between them or
+/// sending a copy to each.
+pub struct SplitNode {
+    position: Position,
+    outputs: [Direction; 2],
+    duplicate: bool,
+    // The output the next value goes to
+    next: usize,
+    value: Option<Number>,
+
+    // Directions
+    up: Option<Rc<RefCell<dyn Node>>>,
+    down: Option<Rc<RefCell<dyn Node>>>,
+    left: Option<Rc<RefCell<dyn Node>>>,
+    right: Option<Rc<RefCell<dyn Node>>>,
+    above: Option<Rc<RefCell<dyn Node>>>,
+    below: Option<Rc<RefCell<dyn Node>>>,
+
+    // Direction transmition
+    give: DirectionGiving,
+    giving_to: Option<Direction>,
+    give_value: Option<Number>,
+}
+
+impl SplitNode {
+    pub fn new(position: Position, first: Direction, second: Direction) -> Self {
+        Self {
+            position,
+            outputs: [first, second],
+            duplicate: false,
+            next: 0,
+            value: None,
+
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            above: None,
+            below: None,
+
+            give: DirectionGiving::None,
+            giving_to: None,
+            give_value: None,
+        }
+    }
+
+    /// Sends every value to both outputs instead of taking turns.
+    pub fn with_duplicate(mut self, duplicate: bool) -> Self {
+        self.duplicate = duplicate;
+        self
+    }
+}
+
+impl Node for SplitNode {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        match dir {
+            Direction::Up => self.up = node,
+            Direction::Down => self.down = node,
+            Direction::Left => self.left = node,
+            Direction::Right => self.right = node,
+            Direction::Above => self.above = node,
+            Direction::Below => self.below = node,
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &self.give
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        self.giving_to
+    }
+
+    fn set_giving_to(&mut self, direction: Direction) {
+        self.giving_to = Some(direction);
+    }
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        self.give_value = self.value;
+        &mut self.give_value
+    }
+
+    fn tick(&mut self) {
+        // Still passing the last value on
+        if self.value.is_some() {
+            return;
+        }
+
+        for direction in all::<Direction>() {
+            // Values only come in from the sides that aren't outputs
+            if self.outputs.contains(&direction) {
+                continue;
+            }
+
+            if let Some(node) = match direction {
+                Direction::Up => self.up.clone(),
+                Direction::Down => self.down.clone(),
+                Direction::Left => self.left.clone(),
+                Direction::Right => self.right.clone(),
+                Direction::Above => self.above.clone(),
+                Direction::Below => self.below.clone(),
+            } {
+                let mut node = node.borrow_mut();
+                match node.give() {
+                    DirectionGiving::None => {}
+                    DirectionGiving::Any => match node.giving_to() {
+                        None => {
+                            node.set_giving_to(direction.opposite());
+                        }
+                        Some(prev_direction) => {
+                            node.set_giving_to(prev_direction.min(direction.opposite()));
+                        }
+                    },
+                    DirectionGiving::Direction(giving_direction) => {
+                        if giving_direction == &direction.opposite() {
+                            node.set_giving_to(direction.opposite());
+                        }
+                    }
+                    DirectionGiving::Given => {
+                        if let Some(value) = take_value(&mut *node) {
+                            self.value = Some(value);
+                            self.give = DirectionGiving::Direction(self.outputs[self.next]);
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_give(&mut self) {}
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        let giving_to = self.giving_to?;
+        self.give = DirectionGiving::Given;
+        Some(self.position.in_direction(giving_to))
+    }
+
+    fn post_post_handle_give(&mut self) {
+        self.giving_to = None;
+        self.next = (self.next + 1) % self.outputs.len();
+
+        // A copy still has to go to the second output
+        if self.duplicate && self.next != 0 {
+            self.give = DirectionGiving::Direction(self.outputs[self.next]);
+        } else {
+            self.value = None;
+            self.give = DirectionGiving::None;
+        }
+    }
+}
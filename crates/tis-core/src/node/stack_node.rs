@@ -0,0 +1,260 @@
+use alloc::{format, rc::Rc, string::String, vec::Vec};
+use core::cell::RefCell;
+
+use enum_iterator::all;
+
+use crate::{
+    checkpoint::{save_transfer, StateReader},
+    direction::Direction,
+    number::Number,
+    position::Position,
+};
+
+use super::{take_value, DirectionGiving, Node};
+
+/// How many values a stack holds unless told otherwise, just like the stack memory node.
+pub(super) const DEFAULT_DEPTH: usize = 15;
+
+/// What happens when a value is written to a full stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// The writer waits until a value is read, like the real thing.
+    Block,
+    /// The value is thrown away.
+    Drop,
+    /// The machine stops with a diagnostic.
+    Trap,
+}
+
+impl Overflow {
+    pub fn parse(overflow: &str) -> Result<Self, String> {
+        match overflow {
+            "block" => Ok(Self::Block),
+            "drop" => Ok(Self::Drop),
+            "trap" => Ok(Self::Trap),
+            _ => Err(format!(
+                "Invalid overflow policy: '{}', expected block, drop or trap",
+                overflow
+            )),
+        }
+    }
+}
+
+/// Why the machine stops when `value` is pushed onto a full stack.
+pub(super) fn overflow(value: Number, depth: usize) -> String {
+    format!(
+        "Stack overflow, {} was written with {} values already stored",
+        value, depth
+    )
+}
+
+/// A node storing the values written to it, giving them back last in first out.
+pub struct StackNode {
+    position: Position,
+    stack: Vec<Number>,
+    depth: usize,
+    overflow: Overflow,
+    // What overflowed the stack, once something has
+    trap: Option<String>,
+
+    // Directions
+    up: Option<Rc<RefCell<dyn Node>>>,
+    down: Option<Rc<RefCell<dyn Node>>>,
+    left: Option<Rc<RefCell<dyn Node>>>,
+    right: Option<Rc<RefCell<dyn Node>>>,
+    above: Option<Rc<RefCell<dyn Node>>>,
+    below: Option<Rc<RefCell<dyn Node>>>,
+
+    // Direction transmition
+    give: DirectionGiving,
+    giving_to: Option<Direction>,
+    give_value: Option<Number>,
+}
+
+impl StackNode {
+    pub fn new(position: Position) -> Self {
+        Self {
+            position,
+            stack: Vec::new(),
+            depth: DEFAULT_DEPTH,
+            overflow: Overflow::Block,
+            trap: None,
+
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            above: None,
+            below: None,
+
+            give: DirectionGiving::None,
+            giving_to: None,
+            give_value: None,
+        }
+    }
+
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Starts the stack out with `values`, the last one being on top.
+    pub fn with_values(mut self, values: Vec<Number>) -> Result<Self, String> {
+        if values.len() > self.depth {
+            return Err(format!(
+                "{} values don't fit in a stack of {}",
+                values.len(),
+                self.depth
+            ));
+        }
+        if !values.is_empty() {
+            self.give = DirectionGiving::Any;
+        }
+        self.stack = values;
+        Ok(self)
+    }
+
+    fn is_full(&self) -> bool {
+        self.stack.len() >= self.depth
+    }
+
+    fn push(&mut self, value: Number) {
+        if self.is_full() {
+            match self.overflow {
+                // Full stacks don't take values at all when blocking
+                Overflow::Block => unreachable!("Value written to a full stack"),
+                Overflow::Drop => return,
+                Overflow::Trap => {
+                    self.trap = Some(overflow(value, self.depth));
+                    return;
+                }
+            }
+        }
+
+        self.stack.push(value);
+        self.give = DirectionGiving::Any;
+    }
+}
+
+impl Node for StackNode {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        match dir {
+            Direction::Up => self.up = node,
+            Direction::Down => self.down = node,
+            Direction::Left => self.left = node,
+            Direction::Right => self.right = node,
+            Direction::Above => self.above = node,
+            Direction::Below => self.below = node,
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &self.give
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        self.giving_to
+    }
+
+    fn set_giving_to(&mut self, direction: Direction) {
+        self.giving_to = Some(direction);
+    }
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        &mut self.give_value
+    }
+
+    fn tick(&mut self) {
+        // The value on top is being read, so nothing can be pushed on top of it
+        if self.giving_to.is_some() {
+            return;
+        }
+        if self.is_full() && self.overflow == Overflow::Block {
+            return;
+        }
+
+        for direction in all::<Direction>() {
+            if let Some(node) = match direction {
+                Direction::Up => self.up.clone(),
+                Direction::Down => self.down.clone(),
+                Direction::Left => self.left.clone(),
+                Direction::Right => self.right.clone(),
+                Direction::Above => self.above.clone(),
+                Direction::Below => self.below.clone(),
+            } {
+                let mut node = node.borrow_mut();
+                match node.give() {
+                    DirectionGiving::None => {}
+                    DirectionGiving::Any => match node.giving_to() {
+                        None => {
+                            node.set_giving_to(direction.opposite());
+                        }
+                        Some(prev_direction) => {
+                            node.set_giving_to(prev_direction.min(direction.opposite()));
+                        }
+                    },
+                    DirectionGiving::Direction(giving_direction) => {
+                        if giving_direction == &direction.opposite() {
+                            node.set_giving_to(direction.opposite());
+                        }
+                    }
+                    DirectionGiving::Given => {
+                        if let Some(value) = take_value(&mut *node) {
+                            self.push(value);
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_give(&mut self) {}
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        let giving_to = self.giving_to?;
+        self.give = DirectionGiving::Given;
+        self.give_value = self.stack.pop();
+        Some(self.position.in_direction(giving_to))
+    }
+
+    fn post_post_handle_give(&mut self) {
+        // The reader wasn't ready to take the value after all, so it goes back on top
+        if let Some(value) = self.give_value.take() {
+            self.stack.push(value);
+        }
+        self.give = if self.stack.is_empty() {
+            DirectionGiving::None
+        } else {
+            DirectionGiving::Any
+        };
+        self.giving_to = None;
+    }
+
+    fn trap(&self) -> Option<&str> {
+        self.trap.as_deref()
+    }
+
+    fn save_state(&self) -> Vec<i32> {
+        let mut state = Vec::new();
+        save_transfer(&mut state, &self.give, self.giving_to, self.give_value);
+        state.extend(self.stack.iter().map(|value| value.value() as i32));
+        state
+    }
+
+    fn load_state(&mut self, state: &[i32]) -> Result<(), String> {
+        let mut state = StateReader::new(state);
+        (self.give, self.giving_to, self.give_value) = state.transfer()?;
+        self.stack = state.numbers()?;
+        Ok(())
+    }
+}
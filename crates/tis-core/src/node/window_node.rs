@@ -0,0 +1,219 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use enum_iterator::all;
+use minifb::{Scale, Window, WindowOptions};
+
+use crate::{
+    direction::Direction,
+    number::Number,
+    position::{Coords, Position},
+    topology::parse_size,
+};
+
+use super::{take_value, DirectionGiving, Node, Waiting};
+
+/// How often the window is redrawn, no matter how fast the machine runs.
+const FRAME_TIME: Duration = Duration::from_micros(16_667);
+
+/// A node drawing pixels in a native window. Values are received as x, y and then a color,
+/// written as three decimal digits for red, green and blue (so 900 is red and 999 is white).
+/// A negative value starts over from the x.
+pub struct WindowNode {
+    position: Position,
+    window: Window,
+    width: usize,
+    height: usize,
+    pixels: Vec<u32>,
+    x: Option<i16>,
+    y: Option<i16>,
+    last_frame: Instant,
+    // Whether the window was closed, which stops the machine
+    closed: bool,
+
+    // Directions
+    up: Option<Rc<RefCell<dyn Node>>>,
+    down: Option<Rc<RefCell<dyn Node>>>,
+    left: Option<Rc<RefCell<dyn Node>>>,
+    right: Option<Rc<RefCell<dyn Node>>>,
+    above: Option<Rc<RefCell<dyn Node>>>,
+    below: Option<Rc<RefCell<dyn Node>>>,
+}
+
+impl WindowNode {
+    /// Opens a window with `size` written as `WxH`, titled with the position written in `coords`.
+    pub fn new(position: Position, size: &str, coords: Coords) -> Result<Self, String> {
+        let (width, height) =
+            parse_size(size).ok_or_else(|| format!("Invalid window size: '{}'", size))?;
+
+        // Small images are blown up so they can actually be seen
+        let scale = match width.max(height) {
+            0..=64 => Scale::X8,
+            65..=128 => Scale::X4,
+            129..=256 => Scale::X2,
+            _ => Scale::X1,
+        };
+        let window = Window::new(
+            &format!("tis-cli {}", position.shown(coords)),
+            width,
+            height,
+            WindowOptions {
+                scale,
+                ..WindowOptions::default()
+            },
+        )
+        .map_err(|e| format!("Couldn't open window: {}", e))?;
+
+        Ok(Self {
+            position,
+            window,
+            width,
+            height,
+            pixels: vec![0; width * height],
+            x: None,
+            y: None,
+            last_frame: Instant::now(),
+            closed: false,
+
+            up: None,
+            down: None,
+            left: None,
+            right: None,
+            above: None,
+            below: None,
+        })
+    }
+
+    fn receive(&mut self, value: Number) {
+        let value = value.value();
+        if value < 0 {
+            self.x = None;
+            self.y = None;
+            return;
+        }
+
+        match (self.x, self.y) {
+            (None, _) => self.x = Some(value),
+            (Some(_), None) => self.y = Some(value),
+            (Some(x), Some(y)) => {
+                let (x, y) = (x as usize, y as usize);
+                if x < self.width && y < self.height {
+                    // Each digit from 0 to 9 is scaled up to a channel from 0 to 255
+                    let channel = |digit: i16| (digit as u32 % 10) * 255 / 9;
+                    self.pixels[y * self.width + x] =
+                        channel(value / 100) << 16 | channel(value / 10) << 8 | channel(value);
+                }
+                self.x = None;
+                self.y = None;
+            }
+        }
+    }
+
+    fn redraw(&mut self) {
+        if self.last_frame.elapsed() < FRAME_TIME {
+            return;
+        }
+        self.last_frame = Instant::now();
+
+        if !self.window.is_open() {
+            // Closing the window stops the machine, there'd be nothing left to look at
+            self.closed = true;
+            return;
+        }
+        if let Err(e) = self
+            .window
+            .update_with_buffer(&self.pixels, self.width, self.height)
+        {
+            eprintln!("Couldn't draw window: {}", e);
+        }
+    }
+}
+
+impl Node for WindowNode {
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn set_dir(&mut self, dir: Direction, node: Option<Rc<RefCell<dyn Node>>>) {
+        match dir {
+            Direction::Up => self.up = node,
+            Direction::Down => self.down = node,
+            Direction::Left => self.left = node,
+            Direction::Right => self.right = node,
+            Direction::Above => self.above = node,
+            Direction::Below => self.below = node,
+        }
+    }
+
+    fn give(&self) -> &DirectionGiving {
+        &DirectionGiving::None
+    }
+
+    fn giving_to(&self) -> Option<Direction> {
+        None
+    }
+
+    fn set_giving_to(&mut self, _direction: Direction) {}
+
+    fn give_value(&mut self) -> &mut Option<Number> {
+        unreachable!("WindowNode does not give values");
+    }
+
+    fn tick(&mut self) {
+        self.redraw();
+
+        for direction in all::<Direction>() {
+            if let Some(node) = match direction {
+                Direction::Up => self.up.clone(),
+                Direction::Down => self.down.clone(),
+                Direction::Left => self.left.clone(),
+                Direction::Right => self.right.clone(),
+                Direction::Above => self.above.clone(),
+                Direction::Below => self.below.clone(),
+            } {
+                let mut node = node.borrow_mut();
+                match node.give() {
+                    DirectionGiving::None => {}
+                    DirectionGiving::Any => match node.giving_to() {
+                        None => {
+                            node.set_giving_to(direction.opposite());
+                        }
+                        Some(prev_direction) => {
+                            node.set_giving_to(prev_direction.min(direction.opposite()));
+                        }
+                    },
+                    DirectionGiving::Direction(giving_direction) => {
+                        if giving_direction == &direction.opposite() {
+                            node.set_giving_to(direction.opposite());
+                        }
+                    }
+                    DirectionGiving::Given => {
+                        if let Some(value) = take_value(&mut *node) {
+                            self.receive(value);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_give(&mut self) {}
+
+    fn post_handle_give(&mut self) -> Option<Position> {
+        None
+    }
+
+    fn post_post_handle_give(&mut self) {}
+
+    fn exit_code(&self) -> Option<i32> {
+        self.closed.then_some(0)
+    }
+
+    // The window has to keep being redrawn to stay open
+    fn waiting(&self) -> Waiting {
+        Waiting::Polling
+    }
+}
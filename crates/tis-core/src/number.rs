@@ -1,4 +1,11 @@
-use std::{
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::String,
+    vec::Vec,
+};
+use core::{
+    fmt::{self, Display, Formatter},
     ops::{Add, AddAssign, Neg, Sub, SubAssign},
     str::FromStr,
 };
@@ -6,14 +13,15 @@ use std::{
 use num_traits::Zero;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-pub(crate) struct Number(i16);
+/// A value as stored by the machine, always between -999 and 999.
+pub struct Number(i16);
 
 impl Number {
     pub(crate) fn new() -> Self {
         Self(0)
     }
 
-    pub(crate) fn value(&self) -> i16 {
+    pub fn value(&self) -> i16 {
         self.0
     }
 
@@ -22,6 +30,15 @@ impl Number {
     }
 }
 
+/// Parses a list of numbers separated by commas and/or whitespace.
+pub fn parse_numbers(numbers: &str) -> Result<Vec<Number>, String> {
+    numbers
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|number| !number.is_empty())
+        .map(|number| number.parse())
+        .collect()
+}
+
 impl From<i8> for Number {
     fn from(number: i8) -> Self {
         Self(number as i16)
@@ -119,7 +136,7 @@ impl FromStr for Number {
             s.next();
         }
 
-        while let Some(c) = s.next() {
+        for c in s {
             match c {
                 '0'..='9' => {
                     value *= 10;
@@ -139,8 +156,8 @@ impl FromStr for Number {
     }
 }
 
-impl ToString for Number {
-    fn to_string(&self) -> String {
-        self.value().to_string()
+impl Display for Number {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.value().fmt(f)
     }
 }
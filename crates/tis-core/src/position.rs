@@ -0,0 +1,150 @@
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::{self, Display, Formatter};
+
+use crate::direction::Direction;
+
+/// How many columns and rows of nodes the game's grid has.
+pub const GAME_COLUMNS: i32 = 4;
+pub const GAME_ROWS: i32 = 3;
+
+/// How positions are written in programs, on the command line and in reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coords {
+    /// With y growing upwards, and the game's grid going from 0, 0 at the bottom left to 3, 2.
+    Plane,
+    /// Like the game, with y growing downwards from the top left node at 0, 0. A node of the
+    /// game's grid can also be given by its number alone, counting row by row from 0 to 11.
+    Game,
+}
+
+impl Coords {
+    pub fn parse(coords: &str) -> Result<Self, String> {
+        match coords {
+            "plane" => Ok(Self::Plane),
+            "game" => Ok(Self::Game),
+            _ => Err(format!(
+                "Invalid coordinates: '{}', expected plane or game",
+                coords
+            )),
+        }
+    }
+}
+
+/// Parses a position written as `x,y` in `coords`, or `x,y,z` on a layered grid.
+pub fn parse_position(position: &str, coords: Coords) -> Result<Position, String> {
+    let invalid = || format!("Invalid position: '{}'", position);
+    let coordinates = position
+        .split(',')
+        .map(|coordinate| coordinate.trim().parse().map_err(|_| invalid()))
+        .collect::<Result<Vec<i32>, _>>()?;
+    match coordinates[..] {
+        [x, y] => Ok(Position::written(x, y, coords)),
+        [x, y, z] => Ok(Position::written(x, y, coords).with_z(z)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Where a node is on the grid, with y growing upwards.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+    /// The layer, which stays 0 unless the program uses a layered grid
+    pub z: i32,
+}
+
+impl Position {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y, z: 0 }
+    }
+
+    pub fn with_z(mut self, z: i32) -> Self {
+        self.z = z;
+        self
+    }
+
+    /// A position as it's written in `coords`.
+    #[doc(hidden)]
+    pub fn written(x: i32, y: i32, coords: Coords) -> Self {
+        match coords {
+            Coords::Plane => Self::new(x, y),
+            Coords::Game => Self::new(x, GAME_ROWS - 1 - y),
+        }
+    }
+
+    /// The node of the game's grid with `number`, if there is one.
+    #[doc(hidden)]
+    pub fn game_node(number: i32) -> Option<Self> {
+        (0..GAME_COLUMNS * GAME_ROWS)
+            .contains(&number)
+            .then(|| Self::new(number % GAME_COLUMNS, GAME_ROWS - 1 - number / GAME_COLUMNS))
+    }
+
+    /// Where the position is in the game's coordinates, along with the number of the node if it's
+    /// on the game's grid.
+    #[doc(hidden)]
+    pub fn in_game(&self) -> (i32, i32, Option<i32>) {
+        let y = GAME_ROWS - 1 - self.y;
+        let on_grid =
+            self.z == 0 && (0..GAME_COLUMNS).contains(&self.x) && (0..GAME_ROWS).contains(&y);
+        (self.x, y, on_grid.then_some(y * GAME_COLUMNS + self.x))
+    }
+
+    pub(crate) fn in_direction(&self, direction: Direction) -> Self {
+        match direction {
+            Direction::Up => Self {
+                y: self.y + 1,
+                ..*self
+            },
+            Direction::Down => Self {
+                y: self.y - 1,
+                ..*self
+            },
+            Direction::Left => Self {
+                x: self.x - 1,
+                ..*self
+            },
+            Direction::Right => Self {
+                x: self.x + 1,
+                ..*self
+            },
+            Direction::Above => Self {
+                z: self.z + 1,
+                ..*self
+            },
+            Direction::Below => Self {
+                z: self.z - 1,
+                ..*self
+            },
+        }
+    }
+
+    /// The position as it's written in `coords`, like `1, 2`.
+    pub fn shown(self, coords: Coords) -> Shown {
+        Shown {
+            position: self,
+            coords,
+        }
+    }
+}
+
+/// A position along with how it's written, see [`Position::shown`].
+#[derive(Clone, Copy, Debug)]
+pub struct Shown {
+    position: Position,
+    coords: Coords,
+}
+
+impl Display for Shown {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Shown { position, coords } = self;
+        match coords {
+            Coords::Plane => write!(f, "{}, {}", position.x, position.y)?,
+            Coords::Game => write!(f, "{}, {}", position.x, position.in_game().1)?,
+        }
+        if position.z != 0 {
+            write!(f, ", {}", position.z)?;
+        }
+        Ok(())
+    }
+}
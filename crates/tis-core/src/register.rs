@@ -0,0 +1,28 @@
+use crate::{direction::Direction, number::Number};
+
+/// How many registers the [`Registers`](crate::extension::Extension::Registers) extension gives
+/// every node.
+pub const GENERAL_REGISTERS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    Accumulator,
+    /// Only read, with the [`ReadableBak`](crate::extension::Extension::ReadableBak) extension.
+    Backup,
+    /// `r0` and up, with the [`Registers`](crate::extension::Extension::Registers) extension.
+    General(u8),
+    Nil,
+
+    // Directions
+    Direction(Direction),
+
+    // Special
+    Any,
+    Last,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterOrNumber {
+    Register(Register),
+    Number(Number),
+}
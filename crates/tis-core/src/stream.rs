@@ -0,0 +1,802 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, VecDeque},
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Read, Write},
+    mem,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    number::Number,
+    position::{Coords, Position},
+};
+
+/// Where a console node reads from or writes to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StreamTarget {
+    Std,
+    File(String),
+    #[cfg(unix)]
+    Fd(i32),
+}
+
+impl StreamTarget {
+    /// Parses `-` (stdin/stdout), `fd:N` or a file path.
+    pub fn parse(target: &str) -> Result<Self, String> {
+        if target == "-" {
+            return Ok(Self::Std);
+        }
+        if let Some(fd) = target.strip_prefix("fd:") {
+            #[cfg(unix)]
+            return fd
+                .parse()
+                .map(Self::Fd)
+                .map_err(|_| format!("Invalid file descriptor: '{}'", fd));
+            #[cfg(not(unix))]
+            return Err(format!(
+                "File descriptors aren't supported on this platform: '{}'",
+                fd
+            ));
+        }
+        Ok(Self::File(target.to_owned()))
+    }
+
+    fn open(&self, write: bool) -> io::Result<File> {
+        match self {
+            Self::Std => unreachable!("Standard streams aren't opened as files"),
+            Self::File(path) if write => File::create(path),
+            Self::File(path) => OpenOptions::new().read(true).open(path),
+            // The descriptor is duplicated rather than taken over, so it's never closed under
+            // whoever else uses it, stdin, stdout and stderr included
+            #[cfg(unix)]
+            Self::Fd(fd) => {
+                use std::os::fd::BorrowedFd;
+
+                // SAFETY: the descriptor is only borrowed for as long as it takes to duplicate it,
+                // which fails if it isn't open
+                unsafe { BorrowedFd::borrow_raw(*fd) }
+                    .try_clone_to_owned()
+                    .map(File::from)
+            }
+        }
+    }
+}
+
+/// Creates a named pipe at `path` unless one already exists there.
+#[cfg(unix)]
+fn make_fifo(path: &str) -> Result<(), String> {
+    use std::{ffi::CString, fs, os::unix::fs::FileTypeExt};
+
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.file_type().is_fifo() => Ok(()),
+        Ok(_) => Err(format!("{} exists but isn't a named pipe", path)),
+        Err(_) => {
+            let c_path = CString::new(path).map_err(|_| format!("Invalid pipe path: {}", path))?;
+            // SAFETY: `c_path` is a valid nul-terminated string
+            if unsafe { libc::mkfifo(c_path.as_ptr(), 0o644) } == 0 {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Couldn't create named pipe {}: {}",
+                    path,
+                    io::Error::last_os_error()
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn make_fifo(path: &str) -> Result<(), String> {
+    Err(format!(
+        "Named pipes aren't supported on this platform: {}",
+        path
+    ))
+}
+
+/// Runs `f` with the terminal not echoing what's typed in, if stdin is a terminal.
+#[cfg(unix)]
+fn without_echo<T>(f: impl FnOnce() -> T) -> T {
+    // SAFETY: termios is plain data, and it's only used once tcgetattr has filled it in
+    let mut termios = unsafe { mem::zeroed::<libc::termios>() };
+    if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } != 0 {
+        // Not a terminal, so nothing gets echoed anyway
+        return f();
+    }
+
+    let mut hidden = termios;
+    hidden.c_lflag &= !libc::ECHO;
+    // Still move to the next line when enter is pressed
+    hidden.c_lflag |= libc::ECHONL;
+    unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &hidden) };
+    let result = f();
+    unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) };
+    result
+}
+
+#[cfg(not(unix))]
+fn without_echo<T>(f: impl FnOnce() -> T) -> T {
+    f()
+}
+
+/// A named pipe that's opened lazily and reopened whenever the other side goes away, so external
+/// processes can come and go while the machine keeps running.
+pub struct NamedPipe<T> {
+    path: String,
+    opened: Option<T>,
+}
+
+impl<T> NamedPipe<T> {
+    fn new(path: String) -> Result<Self, String> {
+        make_fifo(&path)?;
+        Ok(Self { path, opened: None })
+    }
+}
+
+impl InputSource for NamedPipe<BufReader<File>> {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        loop {
+            let reader = match &mut self.opened {
+                Some(reader) => reader,
+                // Blocks until a writer opens the pipe
+                None => self.opened.insert(BufReader::new(File::open(&self.path)?)),
+            };
+
+            match BufRead::read_line(reader, buf)? {
+                // The writer closed the pipe, so wait for the next one
+                0 => self.opened = None,
+                read => return Ok(read),
+            }
+        }
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        loop {
+            let reader = match &mut self.opened {
+                Some(reader) => reader,
+                // Blocks until a writer opens the pipe
+                None => self.opened.insert(BufReader::new(File::open(&self.path)?)),
+            };
+
+            match read_byte(reader)? {
+                // The writer closed the pipe, so wait for the next one
+                None => self.opened = None,
+                byte => return Ok(byte),
+            }
+        }
+    }
+
+    // Whatever writes to the pipe is another program, which has no use for a prompt
+    fn read_line_prompted(
+        &mut self,
+        _prompt: Option<&str>,
+        _echo: bool,
+        buf: &mut String,
+    ) -> io::Result<usize> {
+        self.read_line(buf)
+    }
+}
+
+impl Write for NamedPipe<File> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        loop {
+            let writer = match &mut self.opened {
+                Some(writer) => writer,
+                // Blocks until a reader opens the pipe
+                None => self
+                    .opened
+                    .insert(OpenOptions::new().write(true).open(&self.path)?),
+            };
+
+            match writer.write(buf) {
+                // The reader closed the pipe, so wait for the next one
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => self.opened = None,
+                result => return result,
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.opened {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Somewhere console input nodes read from.
+pub trait InputSource {
+    /// Reads a line into `buf` along with its newline, giving how many bytes were read, which is 0
+    /// once the input has ended. A signal coming in before anything was read may give an
+    /// [`io::ErrorKind::Interrupted`] error instead, so the run can see to it first.
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize>;
+
+    /// Reads a single byte, giving `None` once the input has ended.
+    fn read_byte(&mut self) -> io::Result<Option<u8>>;
+
+    /// Reads a line after showing `prompt` to whoever types it in, hiding what's typed in unless
+    /// `echo` is set. Only the source knows whether anyone types into it and where they'd see the
+    /// prompt, so sources nobody types into read the line as is.
+    fn read_line_prompted(
+        &mut self,
+        prompt: Option<&str>,
+        echo: bool,
+        buf: &mut String,
+    ) -> io::Result<usize>;
+}
+
+/// Somewhere console output nodes write to, which is anything that can be written to.
+pub trait OutputSink: Write {}
+
+impl<T: Write> OutputSink for T {}
+
+/// The input of a console input node.
+pub type InputStream = Box<dyn InputSource>;
+
+/// The output of a console output node, which several nodes may share.
+#[derive(Clone)]
+pub struct OutputStream {
+    sink: Rc<RefCell<dyn OutputSink>>,
+    // When the next value can be written, if values can only be written so fast
+    limit: Option<Rc<RateLimit>>,
+}
+
+/// How often values can be written to an output, see [`OutputStream::with_max_rate`].
+struct RateLimit {
+    interval: Duration,
+    next: Cell<Instant>,
+}
+
+/// Standard input, the only input that can be typed into.
+pub struct Stdin;
+
+impl InputSource for Stdin {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        let mut stdin = io::stdin().lock();
+        // Waiting for the line is where signals come in, which aren't retried, unlike the rest
+        stdin.fill_buf()?;
+        BufRead::read_line(&mut stdin, buf)
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut stdin = io::stdin().lock();
+        stdin.fill_buf()?;
+        read_byte(&mut stdin)
+    }
+
+    fn read_line_prompted(
+        &mut self,
+        prompt: Option<&str>,
+        echo: bool,
+        buf: &mut String,
+    ) -> io::Result<usize> {
+        if let Some(prompt) = prompt {
+            print!("{}", prompt);
+            io::stdout().flush()?;
+        }
+        if echo {
+            self.read_line(buf)
+        } else {
+            without_echo(|| self.read_line(buf))
+        }
+    }
+}
+
+/// Files and in-memory buffers.
+impl<T: BufRead> InputSource for T {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        BufRead::read_line(self, buf)
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        read_byte(self)
+    }
+
+    // Nobody types into a file or a buffer, so there's nobody to prompt
+    fn read_line_prompted(
+        &mut self,
+        _prompt: Option<&str>,
+        _echo: bool,
+        buf: &mut String,
+    ) -> io::Result<usize> {
+        BufRead::read_line(self, buf)
+    }
+}
+
+/// A file several input nodes read from, each taking what the others haven't yet.
+#[derive(Clone)]
+struct SharedInput(Rc<RefCell<BufReader<File>>>);
+
+impl InputSource for SharedInput {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        BufRead::read_line(&mut *self.0.borrow_mut(), buf)
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        read_byte(&mut *self.0.borrow_mut())
+    }
+
+    // Nobody types into a file either
+    fn read_line_prompted(
+        &mut self,
+        _prompt: Option<&str>,
+        _echo: bool,
+        buf: &mut String,
+    ) -> io::Result<usize> {
+        self.read_line(buf)
+    }
+}
+
+/// Everything a recorded input stream has read and its replay hasn't yet, with `None` wherever a
+/// read was interrupted.
+type Recording = Rc<RefCell<VecDeque<Option<u8>>>>;
+
+/// Keeps a copy of everything read from the inner stream, so it can be read again.
+struct Recorded {
+    stream: InputStream,
+    recording: Recording,
+}
+
+impl InputSource for Recorded {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        let start = buf.len();
+        let read = self.stream.read_line(buf);
+        let read = self.record(read)?;
+        self.recording
+            .borrow_mut()
+            .extend(buf[start..].bytes().map(Some));
+        Ok(read)
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let byte = self.stream.read_byte();
+        let byte = self.record(byte)?;
+        self.recording.borrow_mut().extend(byte.map(Some));
+        Ok(byte)
+    }
+
+    fn read_line_prompted(
+        &mut self,
+        prompt: Option<&str>,
+        echo: bool,
+        buf: &mut String,
+    ) -> io::Result<usize> {
+        let start = buf.len();
+        let read = self.stream.read_line_prompted(prompt, echo, buf);
+        let read = self.record(read)?;
+        self.recording
+            .borrow_mut()
+            .extend(buf[start..].bytes().map(Some));
+        Ok(read)
+    }
+}
+
+impl Recorded {
+    /// Notes down a read that was interrupted, which the replay is interrupted at too.
+    fn record<T>(&self, read: io::Result<T>) -> io::Result<T> {
+        if read
+            .as_ref()
+            .is_err_and(|e| e.kind() == io::ErrorKind::Interrupted)
+        {
+            self.recording.borrow_mut().push_back(None);
+        }
+        read
+    }
+}
+
+/// Gives back what a recorded stream read, or whatever else was put in ahead of time.
+struct Replay(Recording);
+
+impl InputSource for Replay {
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        let mut recording = self.0.borrow_mut();
+        if recording.front() == Some(&None) {
+            recording.pop_front();
+            return Err(io::ErrorKind::Interrupted.into());
+        }
+        let len = match recording
+            .iter()
+            .position(|&byte| byte.is_none_or(|byte| byte == b'\n'))
+        {
+            Some(end) if recording[end].is_some() => end + 1,
+            Some(end) => end,
+            None => recording.len(),
+        };
+        let line = String::from_utf8(recording.drain(..len).flatten().collect())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        buf.push_str(&line);
+        Ok(len)
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        match self.0.borrow_mut().pop_front() {
+            Some(None) => Err(io::ErrorKind::Interrupted.into()),
+            byte => Ok(byte.flatten()),
+        }
+    }
+
+    // The prompt was already shown by the stream that was recorded
+    fn read_line_prompted(
+        &mut self,
+        _prompt: Option<&str>,
+        _echo: bool,
+        buf: &mut String,
+    ) -> io::Result<usize> {
+        self.read_line(buf)
+    }
+}
+
+/// What a read gave, unless a signal came in first, in which case nothing was read and the read is
+/// tried again the next cycle, once the run has seen to the signal.
+pub(crate) fn unless_interrupted<T>(read: io::Result<T>) -> Option<T> {
+    match read {
+        Err(e) if e.kind() == io::ErrorKind::Interrupted => None,
+        read => Some(read.unwrap()),
+    }
+}
+
+fn read_byte(reader: &mut impl Read) -> io::Result<Option<u8>> {
+    let mut byte = [0];
+    loop {
+        match reader.read(&mut byte) {
+            Ok(0) => return Ok(None),
+            Ok(_) => return Ok(Some(byte[0])),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+impl OutputStream {
+    pub fn new(sink: impl OutputSink + 'static) -> Self {
+        Self {
+            sink: Rc::new(RefCell::new(sink)),
+            limit: None,
+        }
+    }
+
+    /// Only lets `rate` values a second be written, shared by every node writing to the stream.
+    pub fn with_max_rate(mut self, rate: u32) -> Self {
+        self.limit = Some(Rc::new(RateLimit {
+            interval: Duration::from_secs(1) / rate,
+            next: Cell::new(Instant::now()),
+        }));
+        self
+    }
+
+    /// Whether another value can be written without going over the rate. Output nodes don't read
+    /// anything until it can, which keeps whatever is writing to them waiting.
+    pub fn is_ready(&self) -> bool {
+        self.limit
+            .as_ref()
+            .is_none_or(|limit| Instant::now() >= limit.next.get())
+    }
+
+    /// Counts a value as written, for the rate.
+    pub fn count_value(&self) {
+        if let Some(limit) = &self.limit {
+            limit
+                .next
+                .set(limit.next.get().max(Instant::now()) + limit.interval);
+        }
+    }
+
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+
+    /// Throws everything away.
+    pub fn sink() -> Self {
+        Self::new(io::sink())
+    }
+
+    pub fn pipe(path: String) -> Result<Self, String> {
+        NamedPipe::<File>::new(path).map(Self::new)
+    }
+}
+
+impl Write for OutputStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sink.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.sink.borrow_mut().flush()
+    }
+}
+
+/// Everything an output node wrote while its output was collected.
+type Collection = Rc<RefCell<Vec<u8>>>;
+
+/// Holds on to everything written to it, see [`Streams::with_collected_output`].
+struct Collected(Collection);
+
+impl Write for Collected {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Per-node routing of console input and output, as given by `--map-in` and `--map-out`, along
+/// with where the image is saved by `--screenshot`.
+pub struct Streams {
+    inputs: HashMap<Position, StreamTarget>,
+    outputs: HashMap<Position, StreamTarget>,
+    screenshot: Option<String>,
+
+    // What every input node has read, so a second run of the same program can read it again
+    record: bool,
+    recordings: HashMap<Position, Recording>,
+    replay: bool,
+
+    // What every input node reads when running a test
+    test_input: Option<Recording>,
+
+    // Several input or output nodes may share one file, so each file is only opened once
+    opened_inputs: HashMap<StreamTarget, SharedInput>,
+    opened_outputs: HashMap<StreamTarget, OutputStream>,
+
+    // Where output nodes write instead of stdout, if anywhere
+    console_output: Option<OutputStream>,
+    // How many values a second can be written to each output, if there's a limit
+    max_output_rate: Option<u32>,
+    // What every output node that would write to stdout wrote, if it's held back until the run is
+    // over
+    collected: Option<Vec<(Position, Collection)>>,
+    // How the positions of nodes are written in errors
+    coords: Coords,
+    // Whether a console input node reads stdin
+    reads_stdin: bool,
+}
+
+impl Streams {
+    pub fn new(
+        inputs: HashMap<Position, StreamTarget>,
+        outputs: HashMap<Position, StreamTarget>,
+    ) -> Self {
+        Self {
+            inputs,
+            outputs,
+            screenshot: None,
+            record: false,
+            recordings: HashMap::new(),
+            replay: false,
+            test_input: None,
+            opened_inputs: HashMap::new(),
+            opened_outputs: HashMap::new(),
+            console_output: None,
+            max_output_rate: None,
+            collected: None,
+            coords: Coords::Plane,
+            reads_stdin: false,
+        }
+    }
+
+    /// Writes the positions of nodes in errors in `coords`.
+    pub fn with_coords(mut self, coords: Coords) -> Self {
+        self.coords = coords;
+        self
+    }
+
+    /// Only lets output nodes write `rate` values a second to each output, if there's a rate.
+    pub fn with_max_output_rate(mut self, rate: Option<u32>) -> Self {
+        self.max_output_rate = rate;
+        self
+    }
+
+    /// Keeps a copy of everything input nodes read, see [`Streams::replay`].
+    pub fn with_recording(mut self, record: bool) -> Self {
+        self.record = record;
+        self
+    }
+
+    /// Streams for a second run of the program, where input nodes read what the input nodes of
+    /// the first run read and output nodes write nowhere.
+    pub fn replay(&self) -> Self {
+        Self {
+            recordings: self.recordings.clone(),
+            replay: true,
+            coords: self.coords,
+            ..Self::new(HashMap::new(), HashMap::new())
+        }
+    }
+
+    /// Streams for running the program against a test, where input nodes read `input` one value
+    /// per line and output nodes write nowhere.
+    pub fn test(input: &[Number]) -> Self {
+        let input = input
+            .iter()
+            .map(|value| format!("{}\n", value))
+            .collect::<String>();
+        Self {
+            test_input: Some(Rc::new(RefCell::new(input.bytes().map(Some).collect()))),
+            ..Self::new(HashMap::new(), HashMap::new())
+        }
+    }
+
+    pub fn is_replay(&self) -> bool {
+        self.replay
+    }
+
+    /// Whether any console input node was given stdin to read.
+    pub fn reads_stdin(&self) -> bool {
+        self.reads_stdin
+    }
+
+    fn record(&mut self, position: Position, stream: InputStream) -> InputStream {
+        if !self.record {
+            return stream;
+        }
+        let recording = self.recordings.entry(position).or_default().clone();
+        Box::new(Recorded { stream, recording })
+    }
+
+    /// Has output nodes that aren't mapped anywhere write to `output` instead of stdout.
+    pub fn with_console_output(mut self, output: Option<OutputStream>) -> Self {
+        self.console_output = output;
+        self
+    }
+
+    /// Has output nodes that would write to stdout hold on to what they write instead, so it can be
+    /// written all at once when the run is over, see [`Streams::collected`].
+    pub fn with_collected_output(mut self, collect: bool) -> Self {
+        self.collected = collect.then(Vec::new);
+        self
+    }
+
+    /// What every output node holding on to what it writes wrote, sorted by position.
+    pub fn collected(&self) -> Vec<(Position, Vec<u8>)> {
+        let mut collected = self
+            .collected
+            .iter()
+            .flatten()
+            .map(|(position, output)| (*position, output.borrow().clone()))
+            .collect::<Vec<_>>();
+        collected.sort_by_key(|(position, _)| (position.z, position.x, position.y));
+        collected
+    }
+
+    pub fn with_screenshot(mut self, screenshot: Option<String>) -> Self {
+        self.screenshot = screenshot;
+        self
+    }
+
+    /// Where the image node saves screenshots to, if anywhere.
+    pub fn screenshot(&mut self) -> Option<String> {
+        self.screenshot.take()
+    }
+
+    /// Opens the input stream for the console input node at `position`.
+    pub fn input(&mut self, position: Position) -> Result<InputStream, String> {
+        if let Some(input) = &self.test_input {
+            return Ok(Box::new(Replay(input.clone())));
+        }
+        if self.replay {
+            return Ok(self.replayed(position));
+        }
+        let target = match self.inputs.remove(&position) {
+            None | Some(StreamTarget::Std) => {
+                self.reads_stdin = true;
+                return Ok(self.record(position, Box::new(Stdin)));
+            }
+            Some(target) => target,
+        };
+        // Every other node reading from the same place shares a reader, so what's buffered for
+        // one isn't lost to the others
+        let stream = match self.opened_inputs.get(&target) {
+            Some(stream) => stream.clone(),
+            None => {
+                let file = target.open(false).map_err(|e| {
+                    format!(
+                        "Couldn't open input for node {}: {}",
+                        position.shown(self.coords),
+                        e
+                    )
+                })?;
+                let stream = SharedInput(Rc::new(RefCell::new(BufReader::new(file))));
+                self.opened_inputs.insert(target, stream.clone());
+                stream
+            }
+        };
+        Ok(self.record(position, Box::new(stream)))
+    }
+
+    /// Opens the named pipe at `path` for the pipe input node at `position`.
+    pub fn pipe_input(&mut self, position: Position, path: String) -> Result<InputStream, String> {
+        if let Some(input) = &self.test_input {
+            return Ok(Box::new(Replay(input.clone())));
+        }
+        if self.replay {
+            return Ok(self.replayed(position));
+        }
+        let stream = Box::new(NamedPipe::new(path)?);
+        Ok(self.record(position, stream))
+    }
+
+    fn replayed(&self, position: Position) -> InputStream {
+        Box::new(Replay(
+            self.recordings.get(&position).cloned().unwrap_or_default(),
+        ))
+    }
+
+    /// Whether output nodes write nowhere.
+    fn is_silent(&self) -> bool {
+        self.replay || self.test_input.is_some()
+    }
+
+    /// Opens the output stream for the console output node at `position`.
+    pub fn output(&mut self, position: Position) -> Result<OutputStream, String> {
+        if self.is_silent() {
+            return Ok(OutputStream::sink());
+        }
+        let target = self.outputs.remove(&position).unwrap_or(StreamTarget::Std);
+        // What's collected is kept apart for every node
+        if let (StreamTarget::Std, Some(collected)) = (&target, &mut self.collected) {
+            let output = Collection::default();
+            collected.push((position, output.clone()));
+            return Ok(self.limited(OutputStream::new(Collected(output))));
+        }
+        // Every other node writing to the same place shares a stream, and with it the rate
+        if let Some(stream) = self.opened_outputs.get(&target) {
+            return Ok(stream.clone());
+        }
+        let stream = match &target {
+            StreamTarget::Std => self
+                .console_output
+                .clone()
+                .unwrap_or_else(OutputStream::stdout),
+            target => OutputStream::new(target.open(true).map_err(|e| {
+                format!(
+                    "Couldn't open output for node {}: {}",
+                    position.shown(self.coords),
+                    e
+                )
+            })?),
+        };
+        let stream = self.limited(stream);
+        self.opened_outputs.insert(target, stream.clone());
+        Ok(stream)
+    }
+
+    /// `stream`, only letting values be written as fast as the rate, if there is one.
+    fn limited(&self, stream: OutputStream) -> OutputStream {
+        match self.max_output_rate {
+            Some(rate) => stream.with_max_rate(rate),
+            None => stream,
+        }
+    }
+
+    /// Opens the named pipe at `path` for a pipe output node.
+    pub fn pipe_output(&mut self, path: String) -> Result<OutputStream, String> {
+        if self.is_silent() {
+            return Ok(OutputStream::sink());
+        }
+        Ok(self.limited(OutputStream::pipe(path)?))
+    }
+
+    /// Fails if a mapping was given for a position that has no matching console node.
+    pub fn check_all_used(&self) -> Result<(), String> {
+        if let Some(position) = self.inputs.keys().next() {
+            return Err(format!(
+                "No console input node at {}",
+                position.shown(self.coords)
+            ));
+        }
+        if let Some(position) = self.outputs.keys().next() {
+            return Err(format!(
+                "No console output node at {}",
+                position.shown(self.coords)
+            ));
+        }
+        if self.screenshot.is_some() {
+            return Err("No image node to take a screenshot of".to_owned());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1201 @@
+use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    format,
+    rc::Rc,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{
+    cell::{Cell, RefCell},
+    mem,
+};
+#[cfg(feature = "std")]
+use std::{thread, time::Duration};
+
+use enum_iterator::all;
+#[cfg(feature = "std")]
+use rayon::{
+    iter::{IntoParallelRefMutIterator, ParallelIterator},
+    ThreadPool, ThreadPoolBuilder,
+};
+
+use crate::{
+    checkpoint::{hash, Checkpoint, HASH_START},
+    collections::{Map, Set},
+    direction::Direction,
+    event::Event,
+    extension::Extensions,
+    instruction::Instruction,
+    node::{
+        custom_node::CustomNode, fused_node::FusedNode, instruction_node::InstructionNode,
+        stack_node::Overflow, taken, DirectionGiving, Node, Waiting,
+    },
+    number::Number,
+    optimize::Rewrite,
+    position::{Coords, Position},
+    register::{Register, RegisterOrNumber, GENERAL_REGISTERS},
+    topology::Topology,
+};
+
+/// How long a quiet machine sleeps when it can't block on just one thing from outside.
+#[cfg(feature = "std")]
+const WAIT_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How many instruction nodes have to tick in a cycle for every thread before the work is split
+/// between threads at all, as handing it over takes about as long as ticking that many.
+#[cfg(feature = "std")]
+const NODES_PER_THREAD: usize = 64;
+
+/// A value written by an output node.
+#[derive(Debug, Clone, Copy)]
+pub struct Output {
+    pub position: Position,
+    pub value: Number,
+    pub cycle: usize,
+}
+
+/// What happened during a single cycle.
+#[derive(Debug, Clone)]
+pub struct TickReport {
+    /// The cycle that just ran, counting from 1.
+    pub cycle: usize,
+    pub outputs: Vec<Output>,
+    /// How many values were handed from one node to another.
+    pub transfers: usize,
+    /// How many nodes were waiting on a port instead of executing.
+    pub blocked: usize,
+    pub status: TickStatus,
+}
+
+/// Whether the machine can keep running after a cycle, or why it can't.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TickStatus {
+    /// Something happened, or still might.
+    Progress,
+    /// Nothing has happened for long enough that nothing will until something outside the
+    /// machine changes, if anything can.
+    AllBlocked,
+    /// A node stopped the machine, with the code the program should exit with.
+    Halted(i32),
+    /// As many values as were expected have been written.
+    OutputComplete,
+    /// A node ran into something it can't go on from and stopped the machine, saying what.
+    Trapped(String),
+}
+
+/// The registers of a node running instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Registers {
+    pub accumulator: Number,
+    pub backup: Number,
+    /// `r0` and up, which stay 0 unless the program uses the regs extension.
+    pub general: [Number; GENERAL_REGISTERS],
+    /// The index of the next instruction to run.
+    pub pointer: usize,
+    /// The port `LAST` refers to.
+    pub last: Option<Direction>,
+}
+
+/// A look at a single node, see [`Tis::node_at`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeView {
+    pub position: Position,
+    /// Only nodes running instructions have registers.
+    pub registers: Option<Registers>,
+    /// The value the node is waiting to hand to another node.
+    pub pending: Option<Number>,
+    pub blocked: bool,
+}
+
+/// Running totals kept for every node.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NodeStats {
+    pub sent: u64,
+    pub received: u64,
+    pub blocked_cycles: u64,
+    /// How many instructions the node finished running.
+    pub executed: u64,
+}
+
+impl NodeStats {
+    /// How much of `cycles` cycles the node spent waiting on a port, like the game's IDLE.
+    #[doc(hidden)]
+    pub fn idle(&self, cycles: usize) -> f64 {
+        match cycles {
+            0 => 0.0,
+            cycles => self.blocked_cycles as f64 / cycles as f64,
+        }
+    }
+}
+
+/// Called with the cycle and the event for everything that happens, see [`Tis::observe`].
+type Observer = dyn FnMut(usize, &Event);
+
+/// Creates a custom node from the argument it was given in the program, if any.
+type NodeConstructor = dyn Fn(Option<&str>) -> Result<Box<dyn CustomNode>, String>;
+
+/// The machine, a grid of nodes all running at once.
+pub struct Tis {
+    nodes: Map<Position, Rc<RefCell<dyn Node>>>,
+    stats: Map<Position, NodeStats>,
+    topology: Topology,
+
+    // Shared so nodes that report the cycle can read it
+    cycle: Rc<Cell<usize>>,
+    outputs: Vec<Output>,
+    transfers: usize,
+    blocked: usize,
+    executed: usize,
+    // Cycles in a row that nothing happened in
+    idle_cycles: usize,
+    // How many values output nodes have written in total, and how many are expected
+    written: usize,
+    expected_outputs: Option<usize>,
+    // Values that stop the machine once an output node writes them, by the node that has to write
+    // them or `None` for any of them
+    sentinels: Vec<(Option<Position>, Number)>,
+    // The code the first node to stop the machine gave
+    exit_code: Option<i32>,
+    // What the first node to run into something it can't go on from said about it
+    trap: Option<String>,
+
+    events: Vec<Event>,
+    observers: Vec<Box<Observer>>,
+    // Nodes that have halted, which is only reported once
+    halted: Set<Position>,
+
+    node_types: Map<String, Box<NodeConstructor>>,
+    // Names given to nodes in the program, used in place of their position in reports
+    names: Map<Position, String>,
+    // Ports wired somewhere other than the neighbor in their direction, or nowhere when `None`
+    links: Map<(Position, Direction), Option<Position>>,
+    // Nodes that only run every so many cycles
+    dividers: Map<Position, usize>,
+    // Nodes standing in for a chain of nodes only passing values on, see `Tis::fuse`
+    fused: Set<Position>,
+    // Nodes that can't do anything until a neighbor does, which are skipped, by whether they're
+    // blocked
+    stuck: Map<Position, bool>,
+    // Whether a stuck node got going again during the last tick, which it only shows next tick
+    unstuck: bool,
+    // Whether a node started writing during the last tick, which nobody can read until next tick
+    started_writing: bool,
+    // What stacks do when they're full, unless their settings say otherwise
+    overflow: Overflow,
+    // How positions are written in the program and in whatever the machine reports
+    coords: Coords,
+    // Which extensions the programs loaded into the machine may use
+    extensions: Extensions,
+    // The threads instruction nodes tick on, when there's more than one, see `Tis::tick_nodes`
+    #[cfg(feature = "std")]
+    pool: Option<ThreadPool>,
+    // The region of the grid every instruction node is in, with one region for every thread
+    #[cfg(feature = "std")]
+    regions: Map<Position, usize>,
+}
+
+impl Default for Tis {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tis {
+    pub fn new() -> Self {
+        Self {
+            nodes: Map::new(),
+            stats: Map::new(),
+            topology: Topology::Plane,
+
+            cycle: Rc::new(Cell::new(0)),
+            outputs: Vec::new(),
+            transfers: 0,
+            blocked: 0,
+            executed: 0,
+            idle_cycles: 0,
+            written: 0,
+            expected_outputs: None,
+            sentinels: Vec::new(),
+            exit_code: None,
+            trap: None,
+
+            events: Vec::new(),
+            observers: Vec::new(),
+            halted: Set::new(),
+
+            node_types: Map::new(),
+            names: Map::new(),
+            links: Map::new(),
+            dividers: Map::new(),
+            fused: Set::new(),
+            stuck: Map::new(),
+            unstuck: false,
+            started_writing: false,
+            overflow: Overflow::Block,
+            coords: Coords::Plane,
+            extensions: Extensions::default(),
+            #[cfg(feature = "std")]
+            pool: None,
+            #[cfg(feature = "std")]
+            regions: Map::new(),
+        }
+    }
+
+    /// Lets programs loaded from now on use `keyword` as a special node. The node is created by
+    /// `constructor`, given the argument after the keyword if there is one, as in `@gpio: 17 0,0`.
+    pub fn with_node_type(
+        mut self,
+        keyword: &str,
+        constructor: impl Fn(Option<&str>) -> Result<Box<dyn CustomNode>, String> + 'static,
+    ) -> Self {
+        self.node_types
+            .insert(keyword.to_lowercase(), Box::new(constructor));
+        self
+    }
+
+    /// Creates a node of a type registered with [`Tis::with_node_type`], if there is one.
+    #[doc(hidden)]
+    pub fn custom_node(
+        &self,
+        keyword: &str,
+        argument: Option<&str>,
+    ) -> Option<Result<Box<dyn CustomNode>, String>> {
+        self.node_types
+            .get(keyword)
+            .map(|constructor| constructor(argument))
+    }
+
+    #[doc(hidden)]
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn with_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Writes positions in `coords`, both in programs loaded from now on and in reports.
+    pub fn with_coords(mut self, coords: Coords) -> Self {
+        self.coords = coords;
+        self
+    }
+
+    /// Lets programs loaded from now on use `extensions`.
+    pub fn with_extensions(mut self, extensions: Extensions) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Ticks instruction nodes on `threads` threads, each ticking the nodes in a region of the
+    /// grid. Ports are only ever read on the machine's thread, once every region is done, so the
+    /// machine runs exactly as it does on one.
+    #[cfg(feature = "std")]
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        // Without threads to spare, the machine just runs on its own
+        self.pool = (threads > 1)
+            .then(|| ThreadPoolBuilder::new().num_threads(threads).build().ok())
+            .flatten();
+        self.regions.clear();
+        self
+    }
+
+    /// Makes the machine report [`TickStatus::OutputComplete`] once `count` values have been
+    /// written.
+    #[doc(hidden)]
+    pub fn with_expected_outputs(mut self, count: usize) -> Self {
+        self.expected_outputs = Some(count);
+        self
+    }
+
+    /// Makes the machine halt with code 0 once the output node at `position` writes `value`, or
+    /// any output node when `position` is `None`. The value is still written like any other.
+    pub fn with_sentinel(mut self, position: Option<Position>, value: Number) -> Self {
+        self.sentinels.push((position, value));
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    pub fn coords(&self) -> Coords {
+        self.coords
+    }
+
+    pub fn extensions(&self) -> Extensions {
+        self.extensions
+    }
+
+    pub fn cycle(&self) -> usize {
+        self.cycle.get()
+    }
+
+    /// A handle to the cycle counter for nodes that need to know the current cycle.
+    #[doc(hidden)]
+    pub fn clock(&self) -> Rc<Cell<usize>> {
+        self.cycle.clone()
+    }
+
+    #[doc(hidden)]
+    pub fn stats(&self) -> impl Iterator<Item = (&Position, &NodeStats)> {
+        self.stats.iter()
+    }
+
+    /// Values written by output nodes during the last tick.
+    #[doc(hidden)]
+    pub fn outputs(&self) -> &[Output] {
+        &self.outputs
+    }
+
+    /// Whether nothing at all happened during the last tick.
+    fn is_idle(&self) -> bool {
+        self.transfers == 0
+            && self.executed == 0
+            && self.outputs.is_empty()
+            && !self.unstuck
+            && !self.started_writing
+    }
+
+    /// Whether nothing has happened for long enough that nothing will until something outside
+    /// the machine changes.
+    fn is_quiet(&self) -> bool {
+        // Slower nodes only get a chance to do something every so many cycles
+        let period = self.dividers.values().fold(1, |period, &divider| {
+            period / gcd(period, divider) * divider
+        });
+        self.idle_cycles >= period
+            && self
+                .nodes
+                .values()
+                .all(|node| node.borrow().waiting() != Waiting::Polling)
+    }
+
+    /// Whether anything outside the machine could get a quiet machine going again, see
+    /// [`Tis::wait_for_input`].
+    #[doc(hidden)]
+    pub fn waits_for_input(&self) -> bool {
+        self.nodes
+            .values()
+            .any(|node| node.borrow().waiting() == Waiting::Input)
+    }
+
+    /// Blocks until something from outside the machine arrives, once it's quiet, instead of
+    /// running cycles that do nothing.
+    #[cfg(feature = "std")]
+    #[doc(hidden)]
+    pub fn wait_for_input(&mut self) {
+        let waiting = self
+            .nodes
+            .values()
+            .filter(|node| node.borrow().waiting() == Waiting::Input)
+            .collect::<Vec<_>>();
+        match &waiting[..] {
+            [] => {}
+            [node] => node.borrow_mut().wait_for_input(),
+            // Only one of them could be blocked on, so they're all checked on every so often
+            _ => thread::sleep(WAIT_INTERVAL),
+        }
+        self.idle_cycles = 0;
+    }
+
+    #[doc(hidden)]
+    pub fn add_node<T>(&mut self, node: T)
+    where
+        T: Node + 'static,
+    {
+        let node = Rc::new(RefCell::new(node));
+        if self.nodes.contains_key(&node.borrow().position()) {
+            panic!(
+                "Node already exists at position {:?}",
+                node.borrow().position()
+            );
+        }
+        if !self.topology.contains(node.borrow().position()) {
+            panic!(
+                "Node at {} is outside the {}",
+                node.borrow().position().shown(self.coords),
+                self.topology
+            );
+        }
+
+        for dir in all::<Direction>() {
+            let dir_pos = self.topology.neighbor(node.borrow().position(), dir);
+            if let Some(dir_node) = self.nodes.get(&dir_pos) {
+                dir_node
+                    .borrow_mut()
+                    .set_dir(dir.opposite(), Some(node.clone()));
+                node.borrow_mut().set_dir(dir, Some(dir_node.clone()));
+            }
+        }
+        let pos = node.borrow().position();
+        self.nodes.insert(pos, node);
+        self.stats.insert(pos, NodeStats::default());
+        self.stuck.clear();
+        #[cfg(feature = "std")]
+        self.regions.clear();
+    }
+
+    /// Makes the node at `position` only run every `divider` cycles, starting with the first one.
+    #[doc(hidden)]
+    pub fn set_divider(&mut self, position: Position, divider: usize) {
+        self.dividers.insert(position, divider);
+        self.stuck.clear();
+    }
+
+    /// The node the `direction` port of the node at `position` is connected to, if any.
+    #[doc(hidden)]
+    pub fn connected(&self, position: Position, direction: Direction) -> Option<Position> {
+        match self.links.get(&(position, direction)) {
+            Some(&link) => link,
+            None => Some(self.topology.neighbor(position, direction))
+                .filter(|neighbor| self.nodes.contains_key(neighbor)),
+        }
+    }
+
+    /// Disconnects the `direction` port of the node at `position`, along with the port on the
+    /// other end.
+    fn unwire(&mut self, position: Position, direction: Direction) {
+        if let Some(other) = self.connected(position, direction) {
+            if self.connected(other, direction.opposite()) == Some(position) {
+                self.nodes[&other]
+                    .borrow_mut()
+                    .set_dir(direction.opposite(), None);
+                self.links.insert((other, direction.opposite()), None);
+            }
+        }
+        if let Some(node) = self.nodes.get(&position) {
+            node.borrow_mut().set_dir(direction, None);
+        }
+        self.links.insert((position, direction), None);
+        self.stuck.clear();
+    }
+
+    /// Connects the `direction` port of the node at `position` to the `port` port of the node at
+    /// `to` instead of to its neighbor, or leaves it unconnected if `to` is `None`.
+    #[doc(hidden)]
+    pub fn wire(
+        &mut self,
+        position: Position,
+        direction: Direction,
+        to: Option<(Position, Direction)>,
+    ) -> Result<(), String> {
+        let Some((to, port)) = to else {
+            self.unwire(position, direction);
+            return Ok(());
+        };
+
+        // Nodes only ever look for values coming from the opposite side
+        if port != direction.opposite() {
+            return Err(format!(
+                "The {} port of {} can only be wired to a {} port",
+                direction,
+                self.label(position),
+                direction.opposite()
+            ));
+        }
+        let (Some(node), Some(other)) = (self.nodes.get(&position), self.nodes.get(&to)) else {
+            return Err(format!(
+                "There's no node at {} to wire {} to",
+                to.shown(self.coords),
+                self.label(position)
+            ));
+        };
+        let (node, other) = (node.clone(), other.clone());
+
+        self.unwire(position, direction);
+        self.unwire(to, port);
+        node.borrow_mut().set_dir(direction, Some(other.clone()));
+        other.borrow_mut().set_dir(port, Some(node));
+        self.links.insert((position, direction), Some(to));
+        self.links.insert((to, port), Some(position));
+        self.stuck.clear();
+        Ok(())
+    }
+
+    /// Calls `observer` with the cycle and the event for everything that happens from now on.
+    pub fn observe(&mut self, observer: impl FnMut(usize, &Event) + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Runs a single cycle.
+    pub fn step(&mut self) -> TickReport {
+        let status = self.tick();
+        TickReport {
+            cycle: self.cycle.get(),
+            outputs: self.outputs.clone(),
+            transfers: self.transfers,
+            blocked: self.blocked,
+            status,
+        }
+    }
+
+    /// The name the node at `position` was given in the program, if any.
+    pub fn name(&self, position: Position) -> Option<&str> {
+        self.names.get(&position).map(String::as_str)
+    }
+
+    /// How reports refer to the node at `position`, by its name if it has one.
+    #[doc(hidden)]
+    pub fn label(&self, position: Position) -> String {
+        match self.name(position) {
+            Some(name) => name.to_owned(),
+            None => position.shown(self.coords).to_string(),
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn set_name(&mut self, position: Position, name: String) -> Result<(), String> {
+        if let Some((other, _)) = self.names.iter().find(|(_, other)| **other == name) {
+            return Err(format!(
+                "The nodes at {} and {} are both named {}",
+                other.shown(self.coords),
+                position.shown(self.coords),
+                name
+            ));
+        }
+        self.names.insert(position, name);
+        Ok(())
+    }
+
+    /// How every instruction of every node running any is written, by the node's position, for
+    /// making sense of [`Event::InstructionExecuted`] pointers while the machine runs.
+    #[doc(hidden)]
+    pub fn mnemonics(&self) -> Map<Position, Vec<&'static str>> {
+        self.nodes
+            .iter()
+            .filter_map(|(&position, node)| {
+                // A fused chain runs the `mov` every node of it did
+                if self.fused.contains(&position) {
+                    return Some((position, vec!["mov"]));
+                }
+                let mnemonics = node
+                    .borrow_mut()
+                    .as_instruction_node()?
+                    .instructions()
+                    .iter()
+                    .map(Instruction::mnemonic)
+                    .collect();
+                Some((position, mnemonics))
+            })
+            .collect()
+    }
+
+    /// The instruction at `pointer` of the node at `position`, if it runs instructions.
+    #[doc(hidden)]
+    pub fn instruction(&self, position: Position, pointer: usize) -> Option<Instruction> {
+        self.nodes
+            .get(&position)?
+            .borrow_mut()
+            .as_instruction_node()?
+            .instructions()
+            .get(pointer)
+            .copied()
+    }
+
+    /// The node at `position`, if there is one.
+    pub fn node_at(&self, position: Position) -> Option<NodeView> {
+        self.nodes.get(&position).map(|node| node.borrow().view())
+    }
+
+    /// Every node, sorted by position.
+    pub fn nodes(&self) -> impl Iterator<Item = NodeView> + '_ {
+        self.sorted_nodes()
+            .into_iter()
+            .map(|(_, node)| node.borrow().view())
+    }
+
+    #[doc(hidden)]
+    pub fn tick(&mut self) -> TickStatus {
+        self.cycle.set(self.cycle.get() + 1);
+        self.outputs.clear();
+        // Regions only change along with the nodes
+        #[cfg(feature = "std")]
+        if self.pool.is_some() && self.regions.is_empty() {
+            self.regions = self.partition();
+        }
+
+        // Slower nodes sit out most cycles, without reading, writing or running anything
+        let cycle = self.cycle.get();
+        let scheduled = |position: &Position| {
+            self.dividers
+                .get(position)
+                .is_none_or(|divider| (cycle - 1).is_multiple_of(*divider))
+        };
+        // Stuck nodes wouldn't do anything either, see `Node::is_stuck`
+        let mut running = self
+            .nodes
+            .iter()
+            .filter(|(position, _)| !self.stuck.contains_key(position) && scheduled(position))
+            .map(|(&position, node)| (position, node.clone()))
+            .collect::<Vec<_>>();
+
+        self.tick_nodes(&running);
+        // Nodes catching up on what they already ran can't read, write or wake anything
+        running.retain(|(_, node)| !node.borrow().is_running_ahead());
+
+        // Starting to write is something happening, as a node that read what it writes late in the
+        // last tick only starts writing it now, without running anything
+        self.started_writing = false;
+        for (_, node) in &running {
+            let mut node = node.borrow_mut();
+            let writing = *node.give() != DirectionGiving::None;
+            node.handle_give();
+            self.started_writing |= !writing && *node.give() != DirectionGiving::None;
+        }
+
+        // A stuck node still has to hand over what it's writing once a neighbor asks for it
+        let mut woken = Vec::new();
+        for (position, _) in &running {
+            for direction in all::<Direction>() {
+                let Some(neighbor) = self.connected(*position, direction) else {
+                    continue;
+                };
+                let node = &self.nodes[&neighbor];
+                if self.stuck.contains_key(&neighbor)
+                    && node.borrow().giving_to().is_some()
+                    && scheduled(&neighbor)
+                {
+                    self.stuck.remove(&neighbor);
+                    woken.push((neighbor, node.clone()));
+                }
+            }
+        }
+        running.extend(woken);
+
+        for (position, node) in &running {
+            let position = *position;
+            // Nodes don't know about the topology or the wiring, so positions past an edge are
+            // wrapped here, and wired ports lead to wherever they're wired to
+            let pos = node.borrow_mut().post_handle_give();
+            let link = node
+                .borrow()
+                .giving_to()
+                .and_then(|direction| self.links.get(&(position, direction)));
+            let pos = match link {
+                Some(&link) => link,
+                None => pos.map(|pos| self.topology.wrap(pos)),
+            };
+            if let Some(pos) = pos {
+                if let Some(n) = self.nodes.get(&pos) {
+                    n.borrow_mut().tick();
+                    // The receiver may not be ready to take the value after all
+                    if let Some(value) = taken() {
+                        self.events.push(Event::ValueTransferred {
+                            from: position,
+                            to: pos,
+                            value,
+                        });
+                    }
+                }
+                node.borrow_mut().post_post_handle_give();
+            }
+        }
+
+        let mut trapped = None;
+        for (&position, node) in &self.nodes {
+            if let Some(&blocked) = self.stuck.get(&position) {
+                if blocked {
+                    self.events.push(Event::NodeBlocked { position });
+                }
+                continue;
+            }
+            let mut node = node.borrow_mut();
+            if let Some(pointer) = node.take_executed() {
+                self.events
+                    .push(Event::InstructionExecuted { position, pointer });
+            }
+            if node.is_blocked() {
+                self.events.push(Event::NodeBlocked { position });
+            }
+            if node.is_halted() && self.halted.insert(position) {
+                self.events.push(Event::NodeHalted { position });
+            }
+            if let Some(code) = node.exit_code() {
+                self.exit_code.get_or_insert(code);
+            }
+            // Nodes aren't gone through in any particular order, so when several trap at once,
+            // the one reported is the first by position, the same every run
+            if let Some(message) = node.trap() {
+                if trapped.as_ref().is_none_or(|&(at, _)| position < at) {
+                    trapped = Some((position, message.to_owned()));
+                }
+            }
+            for value in node.take_outputs() {
+                let sentinel = self
+                    .sentinels
+                    .iter()
+                    .any(|&(at, sentinel)| sentinel == value && at.is_none_or(|at| at == position));
+                if sentinel {
+                    self.exit_code.get_or_insert(0);
+                }
+                self.outputs.push(Output {
+                    position,
+                    value,
+                    cycle: self.cycle.get(),
+                });
+            }
+        }
+        self.written += self.outputs.len();
+        if self.trap.is_none() {
+            self.trap = trapped.map(|(position, message)| {
+                format!("At node {}: {}", self.label(position), message)
+            });
+        }
+
+        // Only nodes that ran, and their neighbors, can have become stuck or stopped being stuck
+        self.unstuck = false;
+        for (position, _) in running {
+            let neighbors = all::<Direction>()
+                .filter_map(|direction| self.connected(position, direction))
+                .collect::<Vec<_>>();
+            for position in neighbors.into_iter().chain([position]) {
+                let node = self.nodes[&position].borrow();
+                if node.is_stuck() {
+                    self.stuck.insert(position, node.is_blocked());
+                } else if self.stuck.remove(&position).is_some() {
+                    self.unstuck = true;
+                }
+            }
+        }
+
+        self.dispatch();
+
+        if let Some(message) = &self.trap {
+            TickStatus::Trapped(message.clone())
+        } else if let Some(code) = self.exit_code {
+            TickStatus::Halted(code)
+        } else if self
+            .expected_outputs
+            .is_some_and(|expected| self.written >= expected)
+        {
+            TickStatus::OutputComplete
+        } else if self.is_quiet() {
+            TickStatus::AllBlocked
+        } else {
+            TickStatus::Progress
+        }
+    }
+
+    /// Ticks every node in `running`, as if one after the other.
+    fn tick_nodes(&self, running: &[(Position, Rc<RefCell<dyn Node>>)]) {
+        #[cfg(feature = "std")]
+        if let Some(pool) = &self.pool {
+            self.tick_regions(pool, running);
+            return;
+        }
+        for (_, node) in running {
+            node.borrow_mut().tick();
+        }
+    }
+
+    /// Ticks every node in `running` as [`Tis::tick_nodes`] does, on several threads. The instruction
+    /// nodes of every region tick on a thread of their own first, up to reading from a port, which
+    /// only the node itself is touched by until then. Ports are read once every region is done:
+    /// the nodes that got to reading from one finish their tick here, along with the other kinds of
+    /// nodes, in the same order as on a single thread. Writing only starts after every node has
+    /// ticked, so nodes reading from a port get the same answer either way.
+    #[cfg(feature = "std")]
+    fn tick_regions(&self, pool: &ThreadPool, running: &[(Position, Rc<RefCell<dyn Node>>)]) {
+        let mut nodes = running
+            .iter()
+            .map(|(_, node)| node.borrow_mut())
+            .collect::<Vec<_>>();
+        let mut regions = (0..pool.current_num_threads())
+            .map(|_| Vec::new())
+            .collect::<Vec<_>>();
+        for ((position, _), node) in running.iter().zip(&mut nodes) {
+            if let Some(node) = node.as_instruction_node() {
+                regions[self.regions[position]].push(node.core());
+            }
+        }
+        let count = regions.iter().map(Vec::len).sum::<usize>();
+        if count >= NODES_PER_THREAD * regions.len() {
+            pool.install(|| {
+                regions
+                    .par_iter_mut()
+                    .for_each(|region| region.iter_mut().for_each(|core| core.tick_alone()))
+            });
+        } else {
+            regions
+                .iter_mut()
+                .flatten()
+                .for_each(|core| core.tick_alone());
+        }
+        // Ticking reads from neighbors, which can't be borrowed until now
+        drop(regions);
+        drop(nodes);
+
+        for (_, node) in running {
+            let mut node = node.borrow_mut();
+            match node.as_instruction_node() {
+                Some(node) => node.finish_tick(),
+                None => node.tick(),
+            }
+        }
+    }
+
+    /// Splits the instruction nodes into a region for every thread, each a few columns of the
+    /// grid, by the region every node is in.
+    #[cfg(feature = "std")]
+    fn partition(&self) -> Map<Position, usize> {
+        let Some(pool) = &self.pool else {
+            return Map::new();
+        };
+        let positions = self
+            .sorted_nodes()
+            .into_iter()
+            .filter(|(_, node)| node.borrow_mut().as_instruction_node().is_some())
+            .map(|(position, _)| position)
+            .collect::<Vec<_>>();
+        let size = positions.len().div_ceil(pool.current_num_threads()).max(1);
+        positions
+            .into_iter()
+            .enumerate()
+            .map(|(index, position)| (position, index / size))
+            .collect()
+    }
+
+    /// Keeps the statistics up to date with the events of the last cycle, then hands the events
+    /// to the observers.
+    fn dispatch(&mut self) {
+        self.transfers = 0;
+        self.blocked = 0;
+        self.executed = 0;
+
+        let mut events = mem::take(&mut self.events);
+        for event in events.drain(..) {
+            match event {
+                Event::ValueTransferred { from, to, .. } => {
+                    self.transfers += 1;
+                    self.stats.get_mut(&from).unwrap().sent += 1;
+                    self.stats.get_mut(&to).unwrap().received += 1;
+                }
+                Event::NodeBlocked { position } => {
+                    self.blocked += 1;
+                    self.stats.get_mut(&position).unwrap().blocked_cycles += 1;
+                }
+                Event::InstructionExecuted { position, .. } => {
+                    self.executed += 1;
+                    self.stats.get_mut(&position).unwrap().executed += 1;
+                }
+                Event::NodeHalted { .. } => {}
+            }
+
+            for observer in &mut self.observers {
+                observer(self.cycle.get(), &event);
+            }
+        }
+        self.events = events;
+
+        if self.is_idle() {
+            self.idle_cycles += 1;
+        } else {
+            self.idle_cycles = 0;
+        }
+    }
+
+    /// Swaps the code of running instruction nodes for the code of `nodes`, as long as every one of
+    /// them is in the place of an instruction node. Nothing is changed otherwise.
+    #[doc(hidden)]
+    pub fn reload(&mut self, nodes: Vec<InstructionNode>, keep_state: bool) -> Result<(), String> {
+        for node in &nodes {
+            let position = node.position();
+            match self.nodes.get(&position) {
+                Some(old) if old.borrow_mut().as_instruction_node().is_some() => {}
+                _ => {
+                    return Err(format!(
+                        "There's no instruction node at {} to reload",
+                        position.shown(self.coords)
+                    ))
+                }
+            }
+        }
+
+        for node in nodes {
+            if let Some(old) = self.nodes[&node.position()]
+                .borrow_mut()
+                .as_instruction_node()
+            {
+                old.reload(node, keep_state);
+            }
+        }
+        self.stuck.clear();
+        self.idle_cycles = 0;
+        Ok(())
+    }
+
+    /// Optimizes the code of every instruction node, giving back what was changed in which node.
+    /// This is done before running the program, see [`optimize`](crate::optimize::optimize).
+    #[doc(hidden)]
+    pub fn optimize(&mut self) -> Vec<(Position, Rewrite)> {
+        let mut rewrites = Vec::new();
+        for (position, node) in self.sorted_nodes() {
+            if let Some(node) = node.borrow_mut().as_instruction_node() {
+                rewrites.extend(
+                    node.optimize()
+                        .into_iter()
+                        .map(|rewrite| (position, rewrite)),
+                );
+            }
+        }
+        rewrites
+    }
+
+    /// Has every instruction node run the loops in its code that don't use any port ahead of the
+    /// machine, see [`InstructionNode::run_ahead`].
+    #[doc(hidden)]
+    pub fn run_ahead(&mut self) {
+        for node in self.nodes.values() {
+            if let Some(node) = node.borrow_mut().as_instruction_node() {
+                node.run_ahead();
+            }
+        }
+    }
+
+    /// Runs every chain of nodes that only pass values on, running `mov <port> <port>` and nothing
+    /// else, as a single node taking the place of the last one, see [`FusedNode`]. Values take as
+    /// many cycles to get through as before, but the nodes in between are gone, so they're reported
+    /// as the last node of their chain. Gives back every chain, from the first node to the last.
+    /// This is done before running the program.
+    #[doc(hidden)]
+    pub fn fuse(&mut self) -> Vec<Vec<Position>> {
+        let chains = self.forwarding_chains();
+        for chain in &chains {
+            let (head, tail) = (chain[0], chain[chain.len() - 1]);
+            let (input, _) = self.forwarding(head).unwrap();
+            let (_, output) = self.forwarding(tail).unwrap();
+            let source = self.connected(head, input);
+
+            for &position in &chain[..chain.len() - 1] {
+                for direction in all::<Direction>() {
+                    self.unwire(position, direction);
+                }
+                self.nodes.remove(&position);
+                self.stats.remove(&position);
+                self.links.retain(|(from, _), _| *from != position);
+            }
+
+            // The chain takes over every port of the last node, then the input of the first one
+            let node: Rc<RefCell<dyn Node>> = Rc::new(RefCell::new(FusedNode::new(
+                tail,
+                chain.len(),
+                input,
+                output,
+                self.clock(),
+            )));
+            for direction in all::<Direction>() {
+                let Some(other) = self.connected(tail, direction) else {
+                    continue;
+                };
+                if self.connected(other, direction.opposite()) == Some(tail) {
+                    self.nodes[&other]
+                        .borrow_mut()
+                        .set_dir(direction.opposite(), Some(node.clone()));
+                }
+                node.borrow_mut()
+                    .set_dir(direction, Some(self.nodes[&other].clone()));
+            }
+            self.nodes.insert(tail, node);
+            if let Some(source) = source {
+                self.wire(source, input.opposite(), Some((tail, input)))
+                    .expect("The first node of a chain is connected to what it reads from");
+            }
+            self.fused.insert(tail);
+        }
+        self.stuck.clear();
+        #[cfg(feature = "std")]
+        self.regions.clear();
+        chains
+    }
+
+    /// The ports the node at `position` reads from and writes to, if all it does is pass values
+    /// on from one to the other.
+    fn forwarding(&self, position: Position) -> Option<(Direction, Direction)> {
+        if self.dividers.contains_key(&position) || self.names.contains_key(&position) {
+            return None;
+        }
+        let mut node = self.nodes.get(&position)?.borrow_mut();
+        match node.as_instruction_node()?.instructions() {
+            &[Instruction::Move(
+                RegisterOrNumber::Register(Register::Direction(input)),
+                Register::Direction(output),
+            )] if input != output => Some((input, output)),
+            _ => None,
+        }
+    }
+
+    /// Every chain of at least two nodes passing values on from one to the next that can run as a
+    /// single node, from the first node to the last.
+    fn forwarding_chains(&self) -> Vec<Vec<Position>> {
+        // The node the node at `position` passes its values on to, if that one only passes them on
+        // too
+        let next = |position: Position| {
+            let (_, output) = self.forwarding(position)?;
+            let next = self.connected(position, output)?;
+            let (input, _) = self.forwarding(next)?;
+            (input == output.opposite() && self.connected(next, input) == Some(position))
+                .then_some(next)
+        };
+
+        let forwarders = self
+            .sorted_nodes()
+            .into_iter()
+            .map(|(position, _)| position)
+            .filter(|&position| self.forwarding(position).is_some())
+            .collect::<Vec<_>>();
+        let fed = forwarders
+            .iter()
+            .filter_map(|&position| next(position))
+            .collect::<Set<_>>();
+        let mut chains = Vec::new();
+        for &head in forwarders.iter().filter(|head| !fed.contains(head)) {
+            let mut chain = vec![head];
+            while let Some(next) = next(chain[chain.len() - 1]) {
+                chain.push(next);
+            }
+            if chain.len() >= 2 {
+                chains.push(chain);
+            }
+        }
+
+        // Every node but the last of a chain goes away, so the chain has to lead from one node
+        // that stays around to another
+        let removed = chains
+            .iter()
+            .flat_map(|chain| &chain[..chain.len() - 1])
+            .copied()
+            .collect::<Set<_>>();
+        chains.retain(|chain| {
+            let (head, tail) = (chain[0], chain[chain.len() - 1]);
+            let (input, _) = self.forwarding(head).unwrap();
+            let (_, output) = self.forwarding(tail).unwrap();
+            [(head, input), (tail, output)]
+                .into_iter()
+                .all(|(member, port)| {
+                    self.connected(member, port).is_none_or(|end| {
+                        !chain.contains(&end)
+                            && !removed.contains(&end)
+                            && self.connected(end, port.opposite()) == Some(member)
+                    })
+                })
+        });
+        chains
+    }
+
+    /// The nodes sorted by position, so they come in the same order every time.
+    fn sorted_nodes(&self) -> Vec<(Position, &Rc<RefCell<dyn Node>>)> {
+        let mut nodes = self
+            .nodes
+            .iter()
+            .map(|(&position, node)| (position, node))
+            .collect::<Vec<_>>();
+        nodes.sort_by_key(|(position, _)| (position.z, position.x, position.y));
+        nodes
+    }
+
+    /// A hash of where the nodes are and the code they run, which changes whenever the program
+    /// does in a way that could make a checkpoint meaningless.
+    #[doc(hidden)]
+    pub fn program_hash(&self) -> u64 {
+        let acc = self
+            .sorted_nodes()
+            .into_iter()
+            .fold(HASH_START, |acc, (position, node)| {
+                let acc = hash(format!("{:?}", position).as_bytes(), acc);
+                match node.borrow_mut().as_instruction_node() {
+                    Some(node) => hash(format!("{:?}", node.instructions()).as_bytes(), acc),
+                    None => hash(b"special", acc),
+                }
+            });
+
+        // How the nodes are wired and how fast they run is part of the layout too
+        let mut layout = self
+            .links
+            .iter()
+            .map(|link| format!("{:?}", link))
+            .chain(self.dividers.iter().map(|divider| format!("{:?}", divider)))
+            .collect::<Vec<_>>();
+        layout.sort();
+        layout
+            .iter()
+            .fold(acc, |acc, entry| hash(entry.as_bytes(), acc))
+    }
+
+    /// Saves the state of every node.
+    #[doc(hidden)]
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            program: self.program_hash(),
+            cycle: self.cycle.get(),
+            nodes: self
+                .sorted_nodes()
+                .into_iter()
+                .map(|(position, node)| (position, node.borrow().save_state()))
+                .collect(),
+        }
+    }
+
+    /// A hash of the state of every node, which two runs of the same program only share as long as
+    /// they've done exactly the same thing.
+    #[doc(hidden)]
+    pub fn state_hash(&self) -> u64 {
+        self.sorted_nodes()
+            .into_iter()
+            .fold(HASH_START, |acc, (position, node)| {
+                let acc = hash(format!("{:?}", position).as_bytes(), acc);
+                node.borrow()
+                    .save_state()
+                    .iter()
+                    .fold(acc, |acc, word| hash(&word.to_le_bytes(), acc))
+            })
+    }
+
+    /// Puts every node back in the state it was in when `checkpoint` was made.
+    #[doc(hidden)]
+    pub fn restore(&mut self, checkpoint: &Checkpoint) -> Result<(), String> {
+        if checkpoint.program != self.program_hash() {
+            return Err(
+                "The checkpoint was made from a different program, the code or the layout of the \
+                 nodes has changed since"
+                    .to_owned(),
+            );
+        }
+
+        for (position, state) in &checkpoint.nodes {
+            let node = self.nodes.get(position).ok_or_else(|| {
+                format!(
+                    "The checkpoint has a node at {} that doesn't exist",
+                    position.shown(self.coords)
+                )
+            })?;
+            node.borrow_mut()
+                .load_state(state)
+                .map_err(|e| format!("Couldn't restore node {}: {}", self.label(*position), e))?;
+        }
+        self.cycle.set(checkpoint.cycle);
+        self.stuck.clear();
+        Ok(())
+    }
+
+    /// Lets every node wrap up once the machine has stopped running.
+    #[doc(hidden)]
+    pub fn finish(&mut self) -> Result<(), String> {
+        for node in self.nodes.values() {
+            node.borrow_mut().finish()?;
+        }
+        Ok(())
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    match b {
+        0 => a,
+        b => gcd(b, a % b),
+    }
+}
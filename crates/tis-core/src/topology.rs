@@ -0,0 +1,71 @@
+#[cfg(feature = "std")]
+use alloc::{format, string::String};
+use core::fmt::{self, Display, Formatter};
+
+use crate::{direction::Direction, position::Position};
+
+/// How the positions on the grid connect to each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    /// A grid going on forever in every direction.
+    Plane,
+    /// A grid of a fixed size where going off one edge comes back in at the opposite one.
+    Torus { width: i32, height: i32 },
+}
+
+impl Topology {
+    /// A torus with `size` written as `WxH`.
+    #[cfg(feature = "std")]
+    pub fn torus(size: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid grid size: '{}'", size);
+        let (width, height) = parse_size(size).ok_or_else(invalid)?;
+        Ok(Self::Torus {
+            width: width.try_into().map_err(|_| invalid())?,
+            height: height.try_into().map_err(|_| invalid())?,
+        })
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        match *self {
+            Self::Plane => true,
+            Self::Torus { width, height } => {
+                (0..width).contains(&position.x) && (0..height).contains(&position.y)
+            }
+        }
+    }
+
+    /// Brings a position that went off the grid back onto it.
+    pub(crate) fn wrap(&self, position: Position) -> Position {
+        match *self {
+            Self::Plane => position,
+            Self::Torus { width, height } => {
+                // Layers go on forever, only the plane wraps around
+                Position {
+                    x: position.x.rem_euclid(width),
+                    y: position.y.rem_euclid(height),
+                    ..position
+                }
+            }
+        }
+    }
+
+    pub(crate) fn neighbor(&self, position: Position, direction: Direction) -> Position {
+        self.wrap(position.in_direction(direction))
+    }
+}
+
+impl Display for Topology {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Plane => write!(f, "endless grid"),
+            Self::Torus { width, height } => write!(f, "{}x{} grid", width, height),
+        }
+    }
+}
+
+/// Parses a size written as `WxH`, where neither side can be zero.
+pub fn parse_size(size: &str) -> Option<(usize, usize)> {
+    let (width, height) = size.split_once('x')?;
+    let (width, height) = (width.trim().parse().ok()?, height.trim().parse().ok()?);
+    (width > 0 && height > 0).then_some((width, height))
+}
@@ -0,0 +1,406 @@
+use enum_iterator::{all, Sequence};
+
+use crate::warning::Warning;
+
+/// Every kind of error a program can have. Each has a code that never changes once it's given
+/// out, so new errors only ever get new codes.
+#[derive(Debug, Clone, Copy, Sequence, PartialEq, Eq)]
+pub(crate) enum ErrorCode {
+    InvalidSyntax,
+    ExpectedRegister,
+    ExpectedRegisterOrNumber,
+    ExpressionAsInstruction,
+    ExpectedNewline,
+    LabelAtEnd,
+    DuplicateLabel,
+    LabelNotFound,
+    ControlCharacter,
+    ExpectedArgument,
+    ExpectedWire,
+    ExpectedDirections,
+    WiredNonPort,
+    DuplicatePosition,
+    MissingPosition,
+    ExtensionNeeded,
+    ReadOnlyRegister,
+    InvalidString,
+    ExpectedNumber,
+    NoData,
+    DuplicateSetting,
+    MisplacedSetting,
+    InvalidArgument,
+}
+
+impl ErrorCode {
+    fn number(self) -> u32 {
+        match self {
+            ErrorCode::InvalidSyntax => 1,
+            ErrorCode::ExpectedRegister => 2,
+            ErrorCode::ExpectedRegisterOrNumber => 3,
+            ErrorCode::ExpressionAsInstruction => 4,
+            ErrorCode::ExpectedNewline => 5,
+            ErrorCode::LabelAtEnd => 6,
+            ErrorCode::DuplicateLabel => 7,
+            ErrorCode::LabelNotFound => 8,
+            ErrorCode::ControlCharacter => 9,
+            ErrorCode::ExpectedArgument => 10,
+            ErrorCode::ExpectedWire => 11,
+            ErrorCode::ExpectedDirections => 12,
+            ErrorCode::WiredNonPort => 13,
+            ErrorCode::DuplicatePosition => 14,
+            ErrorCode::MissingPosition => 15,
+            ErrorCode::ExtensionNeeded => 16,
+            ErrorCode::ReadOnlyRegister => 17,
+            ErrorCode::InvalidString => 18,
+            ErrorCode::ExpectedNumber => 19,
+            ErrorCode::NoData => 20,
+            ErrorCode::DuplicateSetting => 21,
+            ErrorCode::MisplacedSetting => 22,
+            ErrorCode::InvalidArgument => 23,
+        }
+    }
+
+    /// The code shown with the error, like `E0004`.
+    pub(crate) fn code(self) -> String {
+        format!("E{:04}", self.number())
+    }
+
+    fn explanation(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidSyntax => {
+                "Something in the program isn't part of the language at all.
+
+Erroneous code example:
+
+    @0,0
+    // Writes 1
+    mov 1 down
+
+Only instructions, registers, numbers, labels and # comments can be in a node's code, and
+only settings and a position can be on the line starting with @. Comments start with #:
+
+    @0,0
+    # Writes 1
+    mov 1 down"
+            }
+            ErrorCode::ExpectedRegister => {
+                "An instruction needs a register or a port to write to, but got something else.
+
+Erroneous code example:
+
+    @0,0
+    mov 1 2
+
+A value can only be moved to acc, nil, last, any or a port. Give it somewhere to go:
+
+    @0,0
+    mov 1 down"
+            }
+            ErrorCode::ExpectedRegisterOrNumber => {
+                "An instruction needs a value to read, but got something else or nothing.
+
+Erroneous code example:
+
+    @0,0
+    add
+
+add, sub, jro and the first argument of mov read from a register, a port or a number.
+Give the instruction what to read:
+
+    @0,0
+    add 1"
+            }
+            ErrorCode::ExpressionAsInstruction => {
+                "A register, port or number was found where an instruction should be.
+
+Erroneous code example:
+
+    @0,0
+    acc
+
+Registers, ports and numbers are only arguments of instructions, they don't do anything
+on their own. Use them in an instruction:
+
+    @0,0
+    add acc"
+            }
+            ErrorCode::ExpectedNewline => {
+                "An instruction is followed by more than it takes on the same line.
+
+Erroneous code example:
+
+    @0,0
+    mov 1 down up
+
+Every instruction goes on its own line, with only as many arguments as it takes. Remove
+the extra argument or move it to an instruction of its own:
+
+    @0,0
+    mov 1 down
+    mov 1 up"
+            }
+            ErrorCode::LabelAtEnd => {
+                "A label is the last thing in a node, so there's no instruction for it to point at.
+
+Erroneous code example:
+
+    @0,0
+    mov 1 down
+    end:
+
+A label names the instruction after it. Remove the label, or put an instruction after it:
+
+    @0,0
+    mov 1 down
+    end: nop"
+            }
+            ErrorCode::DuplicateLabel => {
+                "Two labels in the same node have the same name.
+
+Erroneous code example:
+
+    @0,0
+    loop: mov 1 down
+    loop: mov 2 down
+    jmp loop
+
+A jump has to know which instruction it goes to, so labels in a node have to be
+different. Rename one of them:
+
+    @0,0
+    first: mov 1 down
+    second: mov 2 down
+    jmp first"
+            }
+            ErrorCode::LabelNotFound => {
+                "A jump goes to a label that isn't in the node.
+
+Erroneous code example:
+
+    @0,0
+    mov 1 down
+    jmp start
+
+Jumps only go to labels in the same node. Define the label:
+
+    @0,0
+    start: mov 1 down
+    jmp start"
+            }
+            ErrorCode::ControlCharacter => {
+                "The program has a control character in it, which is usually left over from
+copying it from somewhere else.
+
+Tabs and any kind of line ending are fine, but other control characters aren't part of
+the language. Delete the character the error points at, which might not show up in an
+editor."
+            }
+            ErrorCode::ExpectedArgument => {
+                "A special node or a setting needs an argument after a colon, but doesn't have one.
+
+Erroneous code example:
+
+    @pipe_in 0,0
+
+Special nodes like pipe_in and settings like separator are followed by a colon and their
+argument:
+
+    @pipe_in: numbers 0,0"
+            }
+            ErrorCode::ExpectedWire => {
+                "A port is wired to something that isn't a port of another node.
+
+Erroneous code example:
+
+    @0,0 right -> 5,5
+    mov 1 right
+
+After the arrow comes the position of a node and the port it reads from, or none to
+leave the port unconnected:
+
+    @0,0 right -> 5,5 left
+    mov 1 right"
+            }
+            ErrorCode::ExpectedDirections => {
+                "A split node needs the two directions it writes to.
+
+Erroneous code example:
+
+    @split: left 0,0
+
+Give two different directions after the colon:
+
+    @split: left right 0,0"
+            }
+            ErrorCode::WiredNonPort => {
+                "Something other than a port is wired with an arrow.
+
+Erroneous code example:
+
+    @0,0 out -> 5,5 left
+
+Only up, down, left, right, above and below can be wired to other nodes:
+
+    @0,0 right -> 5,5 left"
+            }
+            ErrorCode::DuplicatePosition => {
+                "A node is given two positions.
+
+Erroneous code example:
+
+    @0,0 1,0
+    mov 1 down
+
+Every node is in exactly one place. Remove one of the positions:
+
+    @1,0
+    mov 1 down"
+            }
+            ErrorCode::MissingPosition => {
+                "A node isn't given a position.
+
+Erroneous code example:
+
+    @stack
+
+Every node needs a position at the end of the line starting with @:
+
+    @stack 0,0"
+            }
+            ErrorCode::ExtensionNeeded => {
+                "Something that isn't part of the game's language is used without turning it on.
+
+Erroneous code example:
+
+    @0,0
+    mov bak down
+
+Additions to the language only work once they're turned on, so programs written for the game
+stay the same everywhere. Turn on the extension the error names:
+
+    tis-cli --extension bak program.tis"
+            }
+            ErrorCode::ReadOnlyRegister => {
+                "A value is moved to a register that can only be read.
+
+Erroneous code example:
+
+    @0,0
+    mov 1 bak
+
+Even with the bak extension, BAK only changes through sav and swp. Move the value to ACC and
+save it instead:
+
+    @0,0
+    mov 1 acc
+    sav"
+            }
+            ErrorCode::InvalidString => {
+                "A string moved with mov can't be turned into values.
+
+Erroneous code example:
+
+    @0,0
+    mov \"\\q\" down
+
+Every character of a string is moved as its code, which can't be above 999, and only \\n, \\t,
+\\r, \\0, \\\\ and \\\" can follow a backslash. A string also needs at least one character:
+
+    @0,0
+    mov \"Hi!\\n\" down"
+            }
+            ErrorCode::ExpectedNumber => {
+                "Something other than a number follows .dat.
+
+Erroneous code example:
+
+    @0,0
+    .dat 1 acc 3
+
+Data is made of values only, written one after the other:
+
+    @0,0
+    .dat 1 2 3"
+            }
+            ErrorCode::NoData => {
+                "Data is read by a node that doesn't have any.
+
+Erroneous code example:
+
+    @0,0
+    rdn down
+
+rdn and rdx read the values given with .dat, so the node needs at least one:
+
+    @0,0
+    .dat 4 8 15
+    rdn down"
+            }
+            ErrorCode::DuplicateSetting => {
+                "A node is given the same setting twice.
+
+Erroneous code example:
+
+    @csv_in: numbers 0,0 separator: 59 separator: 44
+
+Every setting can only be given once. Remove one of them:
+
+    @csv_in: numbers 0,0 separator: 44"
+            }
+            ErrorCode::MisplacedSetting => {
+                "A node is given a setting only other kinds of nodes have.
+
+Erroneous code example:
+
+    @pipe_in: numbers 0,0 separator: 59
+
+Some settings only mean something to certain kinds of nodes:
+
+    separator       csv_in
+    prompt, echo    number_console_in, console_in
+    quorum          bus
+    mode            split
+    last, ptr       instruction nodes
+    values          stack
+    depth, overflow stack, instruction nodes
+
+Remove the setting, or give it to a node that has it:
+
+    @csv_in: numbers 0,0 separator: 59"
+            }
+            ErrorCode::InvalidArgument => {
+                "A setting is given an argument it doesn't take.
+
+Erroneous code example:
+
+    @console_in 0,0 echo: maybe
+
+Some settings only take certain words:
+
+    echo    on, off
+    last    up, down, left, right, above, below
+
+Give the setting one of them:
+
+    @console_in 0,0 echo: off"
+            }
+        }
+    }
+}
+
+/// Prints the long description of an error or warning code, like `tis-cli explain E0004`.
+pub fn explain(code: Option<String>) -> Result<(), String> {
+    let code = code.ok_or("Expected a code to explain, like E0004".to_owned())?;
+    let code = code.to_uppercase();
+    let explanation = all::<ErrorCode>()
+        .find(|error| error.code() == code)
+        .map(ErrorCode::explanation)
+        .or_else(|| {
+            all::<Warning>()
+                .find(|warning| warning.code() == code)
+                .map(Warning::explanation)
+        })
+        .ok_or_else(|| format!("Unknown code: '{}'", code))?;
+    println!("{}", explanation);
+    Ok(())
+}
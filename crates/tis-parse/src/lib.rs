@@ -0,0 +1,43 @@
+//! Loading `.tis` programs into a [`Tis`], reporting whatever is wrong with them on the terminal.
+
+use std::collections::HashMap;
+
+use tis_core::{direction, extension, instruction, node, number, position, register, stream, tis};
+use tis_core::{stream::Streams, Tis};
+
+// Only public so tis-cli can use them
+#[doc(hidden)]
+pub mod diagnostic;
+#[doc(hidden)]
+pub mod message;
+#[doc(hidden)]
+pub mod parse_tis;
+#[doc(hidden)]
+pub mod utils;
+#[doc(hidden)]
+pub mod warning;
+
+/// Loading programs into a machine.
+pub trait Load: Sized {
+    /// Loads the program at `path` into a new machine, see [`Load::with_program`].
+    fn load(path: &str) -> Result<Self, Option<String>>;
+
+    /// Loads the program at `path`, with console nodes reading from stdin and writing to stdout.
+    /// Errors in the program are printed to stderr as they're found, leaving the error `None`.
+    fn with_program(self, path: &str) -> Result<Self, Option<String>>;
+}
+
+impl Load for Tis {
+    fn load(path: &str) -> Result<Self, Option<String>> {
+        Self::new().with_program(path)
+    }
+
+    fn with_program(mut self, path: &str) -> Result<Self, Option<String>> {
+        parse_tis::parse(
+            &mut self,
+            path.to_owned(),
+            &mut Streams::new(HashMap::new(), HashMap::new()),
+        )?;
+        Ok(self)
+    }
+}
@@ -0,0 +1,234 @@
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+// Whether diagnostics are shown in German instead of English
+static GERMAN: AtomicBool = AtomicBool::new(false);
+
+/// The language diagnostics are shown in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    En,
+    De,
+}
+
+impl Lang {
+    pub fn parse(lang: &str) -> Result<Self, String> {
+        match lang {
+            "en" => Ok(Self::En),
+            "de" => Ok(Self::De),
+            _ => Err(format!("Unknown language: '{}', expected en or de", lang)),
+        }
+    }
+}
+
+pub fn set_lang(lang: Lang) {
+    GERMAN.store(lang == Lang::De, Ordering::Relaxed);
+}
+
+fn lang() -> Lang {
+    match GERMAN.load(Ordering::Relaxed) {
+        true => Lang::De,
+        false => Lang::En,
+    }
+}
+
+/// Every text shown in a diagnostic about a program, in every language. A `{}` is filled in with
+/// an argument, see [`Message::with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Message {
+    // Errors and warnings
+    ControlCharacter,
+    InvalidSyntax,
+    ExpectedRegister,
+    ExpectedRegisterOrNumber,
+    ExpressionAsInstruction,
+    ExpectedNewline,
+    LabelAtEnd,
+    DuplicateLabel,
+    LabelNotFound,
+    ExpectedArgument,
+    ExpectedWire,
+    ExpectedDirections,
+    WiredNonPort,
+    NoGameNode,
+    DuplicatePosition,
+    MissingPosition,
+    UnusedLabel,
+    PortUnconnected,
+    LiteralOutOfRange,
+    ExtensionNeeded,
+    ReadOnlyRegister,
+    InvalidString,
+    ExpectedNumber,
+    NoData,
+    ExpectedNumberArgument,
+    DuplicateSetting,
+    MisplacedSetting,
+    ExpectedOnOrOff,
+    ExpectedPort,
+    DidYouMean,
+    TurnOnExtension,
+    UseSave,
+    AddData,
+
+    // What the marked parts of the program are
+    Here,
+    AfterThis,
+    NotAPort,
+    AlreadySetPosition,
+    NewPositionStart,
+    FromInstructionHere,
+    AlreadyDefinedLabel,
+    NewLabel,
+    TheInstruction,
+    TheLabel,
+    LabelUsage,
+    UsesItHere,
+    NotInRange,
+    UnknownEscape,
+    CodeAbove999,
+    NothingInString,
+    AlreadySet,
+    SetAgain,
+}
+
+impl Message {
+    /// The message in the language chosen with `--lang`.
+    pub(crate) fn text(self) -> &'static str {
+        match lang() {
+            Lang::En => self.english(),
+            Lang::De => self.german(),
+        }
+    }
+
+    /// The message with each `{}` replaced by the next of `args`.
+    pub(crate) fn with(self, args: &[&dyn Display]) -> String {
+        let mut parts = self.text().split("{}");
+        let mut message = parts.next().unwrap_or_default().to_owned();
+        for (part, arg) in parts.zip(args) {
+            message += &arg.to_string();
+            message += part;
+        }
+        message
+    }
+
+    fn english(self) -> &'static str {
+        match self {
+            Message::ControlCharacter => "Unexpected control character {}",
+            Message::InvalidSyntax => "Invalid Syntax",
+            Message::ExpectedRegister => "Expected direction or register",
+            Message::ExpectedRegisterOrNumber => "Expected direction, register or number",
+            Message::ExpressionAsInstruction => "{} can only be used as an expression",
+            Message::ExpectedNewline => "Expected newline after instruction",
+            Message::LabelAtEnd => "Expected anything after label",
+            Message::DuplicateLabel => "Label already defined",
+            Message::LabelNotFound => "Label not found",
+            Message::ExpectedArgument => "Expected a colon followed by an argument",
+            Message::ExpectedWire => "Expected a position followed by a port, or none",
+            Message::ExpectedDirections => "Expected a colon followed by two different directions",
+            Message::WiredNonPort => "Only ports can be wired",
+            Message::NoGameNode => "There's no node {} in the game, they're numbered 0 to {}",
+            Message::DuplicatePosition => "Position already set",
+            Message::MissingPosition => "No position provided",
+            Message::UnusedLabel => "Label is never jumped to",
+            Message::PortUnconnected => "The {} port of {} isn't connected to anything",
+            Message::LiteralOutOfRange => "{} is out of range, so it's {} instead",
+            Message::ExtensionNeeded => "This needs the {} extension",
+            Message::ReadOnlyRegister => "BAK can only be read",
+            Message::InvalidString => "Invalid string",
+            Message::ExpectedNumber => "Expected number",
+            Message::NoData => "The node has no data to read",
+            Message::ExpectedNumberArgument => "Expected a colon followed by a number",
+            Message::DuplicateSetting => "The {} setting is already set",
+            Message::MisplacedSetting => "This node doesn't have a {} setting",
+            Message::ExpectedOnOrOff => "Expected on or off",
+            Message::ExpectedPort => "Expected up, down, left, right, above or below",
+            Message::DidYouMean => "did you mean `{}`?",
+            Message::TurnOnExtension => "turn it on with `--extension {}`",
+            Message::UseSave => "move the value to acc and use `sav` instead",
+            Message::AddData => "give it some with `.dat`",
+
+            Message::Here => "Here",
+            Message::AfterThis => "After this",
+            Message::NotAPort => "Not a port",
+            Message::AlreadySetPosition => "Already set position",
+            Message::NewPositionStart => "New position start",
+            Message::FromInstructionHere => "From instruction here",
+            Message::AlreadyDefinedLabel => "Already defined label",
+            Message::NewLabel => "New label",
+            Message::TheInstruction => "The instruction",
+            Message::TheLabel => "The label",
+            Message::LabelUsage => "Label usage",
+            Message::UsesItHere => "Uses it here",
+            Message::NotInRange => "Not between -999 and 999",
+            Message::UnknownEscape => "Unknown escape",
+            Message::CodeAbove999 => "Its code is above 999",
+            Message::NothingInString => "Nothing to move",
+            Message::AlreadySet => "Already set",
+            Message::SetAgain => "Set again",
+        }
+    }
+
+    fn german(self) -> &'static str {
+        match self {
+            Message::ControlCharacter => "Unerwartetes Steuerzeichen {}",
+            Message::InvalidSyntax => "Ungültige Syntax",
+            Message::ExpectedRegister => "Richtung oder Register erwartet",
+            Message::ExpectedRegisterOrNumber => "Richtung, Register oder Zahl erwartet",
+            Message::ExpressionAsInstruction => "{} kann nur als Ausdruck verwendet werden",
+            Message::ExpectedNewline => "Zeilenumbruch nach der Anweisung erwartet",
+            Message::LabelAtEnd => "Nach dem Label muss etwas folgen",
+            Message::DuplicateLabel => "Label ist bereits definiert",
+            Message::LabelNotFound => "Label nicht gefunden",
+            Message::ExpectedArgument => "Doppelpunkt gefolgt von einem Argument erwartet",
+            Message::ExpectedWire => "Position gefolgt von einem Port oder none erwartet",
+            Message::ExpectedDirections => {
+                "Doppelpunkt gefolgt von zwei verschiedenen Richtungen erwartet"
+            }
+            Message::WiredNonPort => "Nur Ports können verdrahtet werden",
+            Message::NoGameNode => {
+                "Im Spiel gibt es keinen Node {}, sie sind von 0 bis {} nummeriert"
+            }
+            Message::DuplicatePosition => "Position ist bereits gesetzt",
+            Message::MissingPosition => "Keine Position angegeben",
+            Message::UnusedLabel => "Zu diesem Label wird nie gesprungen",
+            Message::PortUnconnected => "Der Port {} von {} ist mit nichts verbunden",
+            Message::LiteralOutOfRange => "{} liegt außerhalb des Wertebereichs und wird zu {}",
+            Message::ExtensionNeeded => "Dafür wird die Erweiterung {} benötigt",
+            Message::ReadOnlyRegister => "BAK kann nur gelesen werden",
+            Message::InvalidString => "Ungültiger String",
+            Message::ExpectedNumber => "Zahl erwartet",
+            Message::NoData => "Der Node hat keine Daten zum Lesen",
+            Message::ExpectedNumberArgument => "Doppelpunkt gefolgt von einer Zahl erwartet",
+            Message::DuplicateSetting => "Die Einstellung {} ist bereits gesetzt",
+            Message::MisplacedSetting => "Dieser Node hat keine Einstellung {}",
+            Message::ExpectedOnOrOff => "on oder off erwartet",
+            Message::ExpectedPort => "up, down, left, right, above oder below erwartet",
+            Message::DidYouMean => "meinten Sie `{}`?",
+            Message::TurnOnExtension => "schalten Sie sie mit `--extension {}` ein",
+            Message::UseSave => "verschieben Sie den Wert nach acc und verwenden Sie `sav`",
+            Message::AddData => "geben Sie ihm welche mit `.dat`",
+
+            Message::Here => "Hier",
+            Message::AfterThis => "Danach",
+            Message::NotAPort => "Kein Port",
+            Message::AlreadySetPosition => "Bereits gesetzte Position",
+            Message::NewPositionStart => "Beginn der neuen Position",
+            Message::FromInstructionHere => "Von der Anweisung hier",
+            Message::AlreadyDefinedLabel => "Bereits definiertes Label",
+            Message::NewLabel => "Neues Label",
+            Message::TheInstruction => "Die Anweisung",
+            Message::TheLabel => "Das Label",
+            Message::LabelUsage => "Verwendung des Labels",
+            Message::UsesItHere => "Hier verwendet",
+            Message::NotInRange => "Nicht zwischen -999 und 999",
+            Message::UnknownEscape => "Unbekannte Escape-Sequenz",
+            Message::CodeAbove999 => "Sein Code ist größer als 999",
+            Message::NothingInString => "Nichts zu verschieben",
+            Message::AlreadySet => "Bereits gesetzt",
+            Message::SetAgain => "Erneut gesetzt",
+        }
+    }
+}
@@ -0,0 +1,797 @@
+pub mod normalize;
+mod parse_code;
+mod parse_settings;
+mod template;
+
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+use ariadne::Color;
+
+#[cfg(feature = "audio")]
+use crate::node::audio_node::AudioOutNode;
+#[cfg(feature = "plugins")]
+use crate::node::plugin_node::PluginNode;
+#[cfg(feature = "scripting")]
+use crate::node::script_node::ScriptNode;
+#[cfg(feature = "window")]
+use crate::node::window_node::WindowNode;
+use crate::{
+    diagnostic::ErrorCode,
+    direction::Direction,
+    extension::Extensions,
+    instruction::Instruction,
+    message::Message,
+    node::{
+        bus_node::BusNode,
+        console_node::{ConsoleInNode, ConsoleOutNode},
+        csv_node::CsvInNode,
+        custom_node::CustomNodeAdapter,
+        http_node::HttpNode,
+        image_node::ImageNode,
+        instruction_node::InstructionNode,
+        log_node::LogOutNode,
+        math_node::MathNode,
+        memory_node::MemoryNode,
+        number_console_node::{NumberConsoleInNode, NumberConsoleOutNode},
+        portal_node::{PortalEnd, PortalNode},
+        split_node::SplitNode,
+        stack_node::{Overflow, StackNode},
+    },
+    number::Number,
+    parse_tis::{
+        normalize::{find_control, lowercase, read_program},
+        parse_code::{parse_code, Code},
+        parse_settings::{parse_settings, Settings, SpecialNode},
+        template::{parse_instantiation, Template},
+    },
+    position::{Coords, Position},
+    stream::Streams,
+    tis::Tis,
+    utils::report,
+    warning::{warn, Warning},
+};
+
+/// The settings of a node, its code unless it's a special node, the text the node was parsed from
+/// and where that text starts in the file.
+type ParsedNode = (Settings, Option<Code>, String, usize);
+
+/// Parses the nodes in a file with positions written in `coords` and code that may use
+/// `extensions`, without creating any of them.
+fn parse_nodes(
+    path: &str,
+    coords: Coords,
+    extensions: Extensions,
+) -> Result<Vec<ParsedNode>, Option<String>> {
+    let Ok(code) = read_program(path) else {
+        return Err(Some("Couldn't read file".to_owned()));
+    };
+    if let Some((offset, c)) = find_control(&code) {
+        report(
+            path,
+            offset,
+            ErrorCode::ControlCharacter,
+            &Message::ControlCharacter.with(&[&format!("{:?}", c)]),
+            &[(
+                offset..offset + c.len_utf8(),
+                Message::Here.text(),
+                Color::Red,
+            )],
+        );
+        return Err(None);
+    }
+
+    let mut nodes = Vec::new();
+    let mut templates = HashMap::new();
+    if let Some(mut start) = code.find("@") {
+        for node_code in (code.clone() + "\n").split("@").skip(1) {
+            let offset = start;
+            let (settings, code) = node_code
+                .split_once("\n")
+                .ok_or("There has to be a newline separator between nodes".to_owned())?;
+            let code = lowercase(code);
+
+            start += 1;
+            let settings_len = settings.len();
+            let header = settings.trim().to_lowercase();
+            if let Some(signature) = header.strip_prefix("template ") {
+                let (name, template) = Template::parse(
+                    signature,
+                    code.clone(),
+                    start + settings_len + 1,
+                    format!("@{}", node_code),
+                    offset,
+                )?;
+                if templates.contains_key(&name) {
+                    return Err(Some(format!(
+                        "There's more than one template called {}",
+                        name
+                    )));
+                }
+                templates.insert(name, template);
+                start += settings_len + 1 + code.len();
+                continue;
+            }
+            if let Some(instantiation) = header.strip_prefix("instantiate ") {
+                let (name, arguments, positions) = parse_instantiation(instantiation, coords)?;
+                let template = templates.get(name).ok_or_else(|| {
+                    format!(
+                        "Template {} has to be defined before it's instantiated",
+                        name
+                    )
+                })?;
+                if code
+                    .lines()
+                    .any(|line| !line.trim().is_empty() && !line.trim().starts_with('#'))
+                {
+                    return Err(Some(format!(
+                        "Instances of template {} take their code from it, so they can't have \
+                         any of their own",
+                        name
+                    )));
+                }
+                // Parsed once for every instantiation, so problems with the template aren't
+                // pointed out for every position
+                let parameters = template.bind(name, &arguments)?;
+                let instructions = parse_code(
+                    template.start,
+                    path.to_owned(),
+                    &template.code,
+                    parameters,
+                    extensions,
+                )
+                .ok_or(None)?;
+                for position in positions {
+                    nodes.push((
+                        Settings::at(position),
+                        Some(instructions.clone()),
+                        template.text.clone(),
+                        template.offset,
+                    ));
+                }
+                start += settings_len + 1 + code.len();
+                continue;
+            }
+            let settings = parse_settings(start, path.to_owned(), settings, coords).ok_or(None)?;
+
+            start += settings_len + 1;
+            let instructions = match settings.special_node {
+                Some(_) => None,
+                None => Some(
+                    parse_code(start, path.to_owned(), &code, HashMap::new(), extensions)
+                        .ok_or(None)?,
+                ),
+            };
+            start += code.len();
+
+            nodes.push((settings, instructions, format!("@{}", node_code), offset));
+        }
+    }
+
+    Ok(nodes)
+}
+
+fn instruction_node(
+    pos: Position,
+    instructions: Vec<Instruction>,
+    accumulator: Option<i32>,
+    backup: Option<i32>,
+    last: Option<Direction>,
+    pointer: Option<i32>,
+    coords: Coords,
+) -> Result<InstructionNode, String> {
+    let len = instructions.len();
+    let mut node = InstructionNode::new(pos, instructions);
+    if let Some(accumulator) = accumulator {
+        node = node.with_accumulator(accumulator.into());
+    }
+    if let Some(backup) = backup {
+        node = node.with_backup(backup.into());
+    }
+    if let Some(last) = last {
+        node = node.with_last(last);
+    }
+    if let Some(pointer) = pointer {
+        let pointer = usize::try_from(pointer)
+            .ok()
+            .filter(|&pointer| pointer < len)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid pointer for the node at {}: {}, it has {} instructions",
+                    pos.shown(coords),
+                    pointer,
+                    len
+                )
+            })?;
+        node = node.with_pointer(pointer);
+    }
+    Ok(node)
+}
+
+/// Sets how deep the stack of an instruction node is and what happens when it overflows, as stack
+/// nodes have it.
+fn with_stack(
+    mut node: InstructionNode,
+    depth: Option<i32>,
+    overflow: Option<String>,
+    default: Overflow,
+) -> Result<InstructionNode, String> {
+    if let Some(depth) = depth {
+        node = node.with_depth(parse_depth(depth)?);
+    }
+    Ok(node.with_overflow(match overflow {
+        Some(overflow) => Overflow::parse(&overflow)?,
+        None => default,
+    }))
+}
+
+fn parse_depth(depth: i32) -> Result<usize, String> {
+    usize::try_from(depth).map_err(|_| format!("Invalid stack depth: {}", depth))
+}
+
+/// Parses a speed like `1/4`, giving how many cycles it takes the node to run once.
+fn parse_speed(speed: &str) -> Result<usize, String> {
+    let divider = match speed.split_once('/') {
+        Some(("1", divider)) => divider.trim().parse().ok(),
+        Some(_) => None,
+        None => (speed.trim() == "1").then_some(1),
+    };
+    divider
+        .filter(|&divider| divider > 0)
+        .ok_or_else(|| format!("Invalid speed: '{}', expected something like 1/4", speed))
+}
+
+pub fn parse(tis: &mut Tis, path: String, streams: &mut Streams) -> Result<(), Option<String>> {
+    parse_fragments(tis, &[Fragment::new(path)], streams)
+}
+
+/// A program file loaded into a machine along with others, and where its nodes go.
+#[derive(Debug, Clone)]
+pub struct Fragment {
+    pub path: String,
+    /// How far every node is moved from where it's written, after it's been turned.
+    pub offset: Position,
+    /// How many quarter turns clockwise the nodes are turned around the origin of the file.
+    pub turns: u8,
+    /// Whether the nodes are mirrored left to right before they're turned.
+    pub mirror_x: bool,
+}
+
+impl Fragment {
+    /// The file at `path` with its nodes where they're written.
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            offset: Position::new(0, 0),
+            turns: 0,
+            mirror_x: false,
+        }
+    }
+
+    /// Where a node written at `position` goes.
+    pub fn place(&self, position: Position) -> Position {
+        let (mut x, mut y) = (position.x, position.y);
+        if self.mirror_x {
+            x = -x;
+        }
+        for _ in 0..self.turns % 4 {
+            (x, y) = (y, -x);
+        }
+        Position {
+            x: x + self.offset.x,
+            y: y + self.offset.y,
+            z: position.z + self.offset.z,
+        }
+    }
+
+    /// Which way a port written as `direction` faces once the nodes are turned.
+    pub fn turn(&self, direction: Direction) -> Direction {
+        let mut direction = if self.mirror_x {
+            direction.mirrored()
+        } else {
+            direction
+        };
+        for _ in 0..self.turns % 4 {
+            direction = direction.turned();
+        }
+        direction
+    }
+}
+
+/// Loads several files into one machine, as if they were one program. Ports can be wired and
+/// portals paired across files, but no two nodes can end up at the same position.
+pub fn parse_fragments(
+    tis: &mut Tis,
+    fragments: &[Fragment],
+    streams: &mut Streams,
+) -> Result<(), Option<String>> {
+    // Portals waiting for their twin, and the ones that already have one
+    let mut portals = HashMap::new();
+    let mut paired_portals = HashSet::new();
+    // Ports are only wired once every node is in place
+    let mut wires = Vec::new();
+    // The ports instructions use, which should be connected to something once they're wired
+    let mut ports = Vec::new();
+    // The file every node came from, for pointing out nodes landing on each other
+    let mut placed = HashMap::new();
+
+    let nodes = fragments
+        .iter()
+        .map(|fragment| {
+            Ok(parse_nodes(&fragment.path, tis.coords(), tis.extensions())?
+                .into_iter()
+                .map(move |node| (fragment, node)))
+        })
+        .collect::<Result<Vec<_>, Option<String>>>()?;
+    for (fragment, (settings, instructions, _, _)) in nodes.into_iter().flatten() {
+        let Settings {
+            position,
+            accumulator,
+            backup,
+            last,
+            pointer,
+            name,
+            wires: node_wires,
+            speed,
+            locked: _,
+            values,
+            special_node,
+            separator,
+            prompt,
+            echo,
+            depth,
+            overflow,
+            quorum,
+            mode,
+        } = settings;
+
+        let pos = fragment.place(position);
+        if let Some(other) = placed.insert(pos, &fragment.path) {
+            return Err(Some(if *other == fragment.path {
+                format!(
+                    "There's more than one node at {} in {}",
+                    position.shown(tis.coords()),
+                    other
+                )
+            } else {
+                format!(
+                    "The node at {} in {} lands on one from {}",
+                    position.shown(tis.coords()),
+                    fragment.path,
+                    other
+                )
+            }));
+        }
+        if let Some(name) = name {
+            tis.set_name(pos, name)?;
+        }
+        wires.extend(node_wires.into_iter().map(|(port, to)| {
+            let to = to.map(|(to, to_port)| (fragment.place(to), fragment.turn(to_port)));
+            (pos, fragment.turn(port), to)
+        }));
+        if let Some(speed) = speed {
+            tis.set_divider(pos, parse_speed(&speed)?);
+        }
+
+        if let Some(special_node) = special_node {
+            if accumulator.is_some() {
+                panic!("Special nodes don't have accumulators");
+            }
+            if backup.is_some() {
+                panic!("Special nodes don't have backups");
+            }
+            if streams.is_replay()
+                && matches!(
+                    special_node,
+                    SpecialNode::SharedMemory(_)
+                        | SpecialNode::Http(_)
+                        | SpecialNode::LogOut(_)
+                        | SpecialNode::Window(_)
+                        | SpecialNode::AudioOut(_)
+                )
+            {
+                return Err(Some(format!(
+                    "The node at {} talks to the outside world, so it can't be run twice to verify \
+                     determinism",
+                    pos.shown(tis.coords())
+                )));
+            }
+
+            match special_node {
+                SpecialNode::NumberConsoleOut => {
+                    tis.add_node(NumberConsoleOutNode::new(pos).with_output(streams.output(pos)?))
+                }
+                SpecialNode::NumberConsoleIn => {
+                    let mut node = NumberConsoleInNode::new(pos).with_input(streams.input(pos)?);
+                    if let Some(prompt) = prompt {
+                        node = node.with_prompt(prompt);
+                    }
+                    if let Some(echo) = echo {
+                        node = node.with_echo(echo);
+                    }
+                    tis.add_node(node)
+                }
+                SpecialNode::ConsoleOut => {
+                    tis.add_node(ConsoleOutNode::new(pos).with_output(streams.output(pos)?))
+                }
+                SpecialNode::ConsoleIn => {
+                    let mut node = ConsoleInNode::new(pos).with_input(streams.input(pos)?);
+                    if let Some(prompt) = prompt {
+                        node = node.with_prompt(prompt);
+                    }
+                    if let Some(echo) = echo {
+                        node = node.with_echo(echo);
+                    }
+                    tis.add_node(node)
+                }
+                SpecialNode::ByteOut => tis.add_node(
+                    ConsoleOutNode::new(pos)
+                        .with_output(streams.output(pos)?)
+                        .with_binary(true),
+                ),
+                SpecialNode::LineOut => tis.add_node(
+                    ConsoleOutNode::new(pos)
+                        .with_output(streams.output(pos)?)
+                        .with_line_buffer(true),
+                ),
+                SpecialNode::ByteIn => tis.add_node(
+                    ConsoleInNode::new(pos)
+                        .with_input(streams.input(pos)?)
+                        .with_binary(true),
+                ),
+                SpecialNode::Image => {
+                    let mut node = ImageNode::new(pos);
+                    if let Some(screenshot) = streams.screenshot() {
+                        node = node.with_screenshot(screenshot);
+                    }
+                    tis.add_node(node)
+                }
+                SpecialNode::Stack => {
+                    let mut node = StackNode::new(pos).with_overflow(tis.overflow());
+                    if let Some(depth) = depth {
+                        node = node.with_depth(parse_depth(depth)?);
+                    }
+                    if let Some(overflow) = overflow {
+                        node = node.with_overflow(Overflow::parse(&overflow)?);
+                    }
+                    if let Some(values) = values {
+                        node = node
+                            .with_values(values.into_iter().map(Number::from).collect())
+                            .map_err(|e| format!("At node {}: {}", tis.label(pos), e))?;
+                    }
+                    tis.add_node(node)
+                }
+                SpecialNode::Bus => {
+                    let mut node = BusNode::new(pos);
+                    if let Some(quorum) = quorum {
+                        let quorum = usize::try_from(quorum)
+                            .map_err(|_| format!("Invalid bus quorum: {}", quorum))?;
+                        node = node.with_quorum(quorum);
+                    }
+                    tis.add_node(node)
+                }
+                SpecialNode::Split(first, second) => {
+                    let duplicate = match mode.as_deref() {
+                        None | Some("alternate") => false,
+                        Some("duplicate") => true,
+                        Some(mode) => {
+                            return Err(Some(format!(
+                                "Invalid split mode: '{}', expected alternate or duplicate",
+                                mode
+                            )))
+                        }
+                    };
+                    let (first, second) = (fragment.turn(first), fragment.turn(second));
+                    tis.add_node(SplitNode::new(pos, first, second).with_duplicate(duplicate))
+                }
+                SpecialNode::Math => tis.add_node(MathNode::new(pos)),
+                SpecialNode::Portal(name) => {
+                    let end = match portals.remove(&name) {
+                        Some(end) => {
+                            paired_portals.insert(name);
+                            end
+                        }
+                        None if paired_portals.contains(&name) => {
+                            return Err(Some(format!("Portal {} is used more than twice", name)))
+                        }
+                        None => {
+                            let (end, twin) = PortalEnd::pair();
+                            portals.insert(name, twin);
+                            end
+                        }
+                    };
+                    tis.add_node(PortalNode::new(pos, end))
+                }
+                SpecialNode::PipeIn(pipe) => tis.add_node(
+                    NumberConsoleInNode::new(pos).with_input(streams.pipe_input(pos, pipe)?),
+                ),
+                SpecialNode::PipeOut(pipe) => tis.add_node(
+                    NumberConsoleOutNode::new(pos).with_output(streams.pipe_output(pipe)?),
+                ),
+                SpecialNode::SharedMemory(memory) => {
+                    tis.add_node(MemoryNode::shared(pos, &memory)?)
+                }
+                SpecialNode::Ram(size) => tis.add_node(MemoryNode::ram(pos, &size)?),
+                SpecialNode::Http(address) => tis.add_node(HttpNode::new(pos, &address)?),
+                SpecialNode::LogOut(log) => {
+                    let clock = tis.clock();
+                    tis.add_node(LogOutNode::new(pos, &log, clock)?)
+                }
+                SpecialNode::CsvIn(csv) => {
+                    let mut node = CsvInNode::new(pos, &csv)?;
+                    if let Some(separator) = separator {
+                        node = node.with_separator(separator.into());
+                    }
+                    tis.add_node(node)
+                }
+                #[cfg(feature = "window")]
+                SpecialNode::Window(size) => {
+                    tis.add_node(WindowNode::new(pos, &size, tis.coords())?)
+                }
+                #[cfg(not(feature = "window"))]
+                SpecialNode::Window(size) => {
+                    return Err(Some(format!(
+                        "Couldn't open a {} window, the window feature is disabled",
+                        size
+                    )))
+                }
+                #[cfg(feature = "audio")]
+                SpecialNode::AudioOut(rate) => tis.add_node(AudioOutNode::new(pos, &rate)?),
+                #[cfg(not(feature = "audio"))]
+                SpecialNode::AudioOut(rate) => {
+                    return Err(Some(format!(
+                        "Couldn't play audio at {}, the audio feature is disabled",
+                        rate
+                    )))
+                }
+                #[cfg(feature = "plugins")]
+                SpecialNode::Plugin(plugin, argument) => tis.add_node(CustomNodeAdapter::new(
+                    pos,
+                    Box::new(PluginNode::load(&plugin, argument.as_deref())?),
+                )),
+                #[cfg(not(feature = "plugins"))]
+                SpecialNode::Plugin(plugin, _) => {
+                    return Err(Some(format!(
+                        "Couldn't load plugin {}, the plugins feature is disabled",
+                        plugin
+                    )))
+                }
+                #[cfg(feature = "scripting")]
+                SpecialNode::Script(script, argument) => tis.add_node(CustomNodeAdapter::new(
+                    pos,
+                    Box::new(ScriptNode::load(&script, argument.as_deref())?),
+                )),
+                #[cfg(not(feature = "scripting"))]
+                SpecialNode::Script(script, _) => {
+                    return Err(Some(format!(
+                        "Couldn't load script {}, the scripting feature is disabled",
+                        script
+                    )))
+                }
+                SpecialNode::Custom(keyword, argument) => {
+                    let node = tis
+                        .custom_node(&keyword, argument.as_deref())
+                        .ok_or_else(|| format!("Unknown special node: {}", keyword))?
+                        .map_err(|e| {
+                            format!(
+                                "Couldn't create the {} node at {}: {}",
+                                keyword,
+                                pos.shown(tis.coords()),
+                                e
+                            )
+                        })?;
+                    tis.add_node(CustomNodeAdapter::new(pos, node))
+                }
+            }
+
+            continue;
+        }
+
+        let (instructions, spans, _, data) =
+            instructions.expect("Instruction nodes always have code");
+        let instructions = instructions
+            .into_iter()
+            .map(|instruction| instruction.map_ports(|direction| fragment.turn(direction)))
+            .collect::<Vec<_>>();
+        for (instruction, span) in instructions.iter().zip(spans) {
+            for direction in instruction.ports() {
+                ports.push((pos, direction, span.clone(), &fragment.path));
+            }
+        }
+        let node = instruction_node(
+            pos,
+            instructions,
+            accumulator,
+            backup,
+            last.map(|last| fragment.turn(last)),
+            pointer,
+            tis.coords(),
+        )?;
+        tis.add_node(with_stack(node, depth, overflow, tis.overflow())?.with_data(data));
+    }
+
+    if let Some(name) = portals.keys().next() {
+        return Err(Some(format!("Portal {} has no twin", name)));
+    }
+
+    for (position, port, to) in wires {
+        tis.wire(position, port, to)?;
+    }
+
+    // Only the first use of each port is pointed out
+    let mut denied = false;
+    let mut unconnected = HashSet::new();
+    for (position, direction, span, path) in ports {
+        if tis.connected(position, direction).is_none() && unconnected.insert((position, direction))
+        {
+            denied |= warn(
+                Warning::PortUnconnected,
+                path,
+                span.start,
+                &Message::PortUnconnected.with(&[&direction, &tis.label(position)]),
+                &[(span, Message::UsesItHere.text(), Color::Yellow)],
+            );
+        }
+    }
+    if denied {
+        return Err(None);
+    }
+
+    Ok(())
+}
+
+/// Swaps the instructions of every instruction node for the ones now in the file of `fragment`,
+/// keeping their registers and any value they're writing if `keep_state` is set.
+pub fn reload(tis: &mut Tis, fragment: &Fragment, keep_state: bool) -> Result<(), Option<String>> {
+    let nodes = parse_nodes(&fragment.path, tis.coords(), tis.extensions())?
+        .into_iter()
+        .filter_map(|(settings, code, _, _)| {
+            let (instructions, _, _, data) = code?;
+            let instructions = instructions
+                .into_iter()
+                .map(|instruction| instruction.map_ports(|direction| fragment.turn(direction)))
+                .collect();
+            let node = instruction_node(
+                fragment.place(settings.position),
+                instructions,
+                settings.accumulator,
+                settings.backup,
+                settings.last.map(|last| fragment.turn(last)),
+                settings.pointer,
+                tis.coords(),
+            );
+            Some(node.and_then(|node| {
+                with_stack(node, settings.depth, settings.overflow, tis.overflow())
+                    .map(|node| node.with_data(data))
+            }))
+        })
+        .collect::<Result<_, _>>()?;
+    tis.reload(nodes, keep_state).map_err(Some)
+}
+
+/// The text of every node in a file with positions written in `coords` and code that may use
+/// `extensions`, without the blank lines after it, by position, along with whether the node is
+/// locked.
+pub fn node_texts(
+    path: &str,
+    coords: Coords,
+    extensions: Extensions,
+) -> Result<HashMap<Position, (bool, String)>, Option<String>> {
+    Ok(parse_nodes(path, coords, extensions)?
+        .into_iter()
+        .map(|(settings, _, text, _)| {
+            (
+                settings.position,
+                (settings.locked, text.trim_end().to_owned()),
+            )
+        })
+        .collect())
+}
+
+/// The code of every node in a file with positions written in `coords`, by position, as long as
+/// the program only uses what the TIS-100 itself has: instruction nodes on a single layer, without
+/// any settings besides their position, reading and writing the four ports of the grid.
+pub fn game_nodes(path: &str, coords: Coords) -> Result<Vec<(Position, String)>, Option<String>> {
+    // The game has none of the extensions
+    parse_nodes(path, coords, Extensions::default())?
+        .into_iter()
+        .map(|(settings, code, text, _)| {
+            let position = settings.position;
+            let unsupported = |what| {
+                Err(Some(format!(
+                    "The node at {} {}",
+                    position.shown(coords),
+                    what
+                )))
+            };
+
+            let Some((instructions, _, _, data)) = code else {
+                return unsupported("is a special node, which the game doesn't have");
+            };
+            if position.z != 0 {
+                return unsupported("is on another layer, which the game doesn't have");
+            }
+            if settings.accumulator.is_some()
+                || settings.backup.is_some()
+                || settings.last.is_some()
+                || settings.pointer.is_some()
+                || settings.name.is_some()
+                || !settings.wires.is_empty()
+                || settings.speed.is_some()
+            {
+                return unsupported("has settings, which the game doesn't have");
+            }
+            if instructions
+                .iter()
+                .flat_map(Instruction::ports)
+                .any(|port| matches!(port, Direction::Above | Direction::Below))
+            {
+                return unsupported("uses above or below, which the game doesn't have");
+            }
+            if !data.is_empty() {
+                return unsupported("has data, which the game doesn't have");
+            }
+
+            // Everything after the line with the position and settings
+            let code = text.split_once('\n').map_or("", |(_, code)| code);
+            if code.contains('$') {
+                return unsupported(
+                    "fills in parameters of a template, which the game doesn't have",
+                );
+            }
+            Ok((position, code.trim_end().to_owned()))
+        })
+        .collect()
+}
+
+/// A node as it's written in a file, for tools looking at a program rather than running it.
+pub struct NodeSource {
+    pub position: Position,
+    pub name: Option<String>,
+    pub locked: bool,
+    /// The keyword of the special node, or `None` for a node running code.
+    pub kind: Option<String>,
+    /// The text of the node without the blank lines after it, and where it starts in the file.
+    pub text: String,
+    pub offset: usize,
+    /// Where every instruction is in the file.
+    pub instructions: Vec<Range<usize>>,
+    /// Every label with the instruction it's at and where it's defined in the file, in the order
+    /// they're defined.
+    pub labels: Vec<(String, usize, Range<usize>)>,
+}
+
+/// Every node in a file as it's written with positions in `coords` and code that may use
+/// `extensions`, in the order of the file.
+pub fn node_sources(
+    path: &str,
+    coords: Coords,
+    extensions: Extensions,
+) -> Result<Vec<NodeSource>, Option<String>> {
+    Ok(parse_nodes(path, coords, extensions)?
+        .into_iter()
+        .map(|(settings, code, text, offset)| {
+            let kind = settings.special_node.as_ref().map(|_| {
+                text[1..]
+                    .split(|c: char| c.is_whitespace() || c == ':')
+                    .next()
+                    .unwrap_or_default()
+                    .to_lowercase()
+            });
+            let (instructions, labels) =
+                code.map_or_else(Default::default, |(_, spans, labels, _)| (spans, labels));
+            NodeSource {
+                position: settings.position,
+                name: settings.name,
+                locked: settings.locked,
+                kind,
+                text: text.trim_end().to_owned(),
+                offset,
+                instructions,
+                labels,
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,102 @@
+use std::{
+    fs::read_to_string,
+    io::{self, Read},
+    sync::OnceLock,
+};
+
+/// How many columns a tab moves to, the same as in error reports.
+const TAB_WIDTH: usize = 4;
+
+/// What a program read from stdin is called, in place of its path.
+pub const STDIN: &str = "<stdin>";
+
+/// The program read from stdin, once it has been.
+static STDIN_PROGRAM: OnceLock<String> = OnceLock::new();
+
+/// Reads the whole of stdin as the program called [`STDIN`]. Nothing else can read from stdin
+/// after that, so it's empty for console nodes.
+pub fn read_stdin() -> io::Result<()> {
+    let mut code = String::new();
+    io::stdin().read_to_string(&mut code)?;
+    // Reading it twice would only give an empty program
+    let _ = STDIN_PROGRAM.set(code);
+    Ok(())
+}
+
+/// Reads a program as it's written, from stdin if `path` is [`STDIN`].
+pub fn read_source(path: &str) -> io::Result<String> {
+    match path {
+        STDIN => STDIN_PROGRAM
+            .get()
+            .cloned()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "stdin wasn't read")),
+        _ => read_to_string(path),
+    }
+}
+
+/// Reads a program as the parser sees it, see [`normalize`].
+pub fn read_program(path: &str) -> io::Result<String> {
+    read_source(path).map(|code| normalize(&code))
+}
+
+/// Makes a program parse the same wherever it was written: a byte order mark is removed, line
+/// endings become `\n` and tabs are expanded to spaces, so spans line up with what's shown.
+pub(crate) fn normalize(code: &str) -> String {
+    let code = code.strip_prefix('\u{feff}').unwrap_or(code);
+
+    let mut normalized = String::with_capacity(code.len());
+    let mut column = 0;
+    let mut chars = code.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => {
+                chars.next_if_eq(&'\n');
+                normalized.push('\n');
+                column = 0;
+            }
+            '\n' => {
+                normalized.push('\n');
+                column = 0;
+            }
+            '\t' => {
+                let spaces = TAB_WIDTH - column % TAB_WIDTH;
+                normalized.extend(std::iter::repeat_n(' ', spaces));
+                column += spaces;
+            }
+            _ => {
+                normalized.push(c);
+                column += 1;
+            }
+        }
+    }
+    normalized
+}
+
+/// Finds the first control character left in a normalized program, which can't be part of one.
+pub(super) fn find_control(code: &str) -> Option<(usize, char)> {
+    code.char_indices()
+        .find(|&(_, c)| c.is_control() && c != '\n')
+}
+
+/// Lowercases the code of a node, so instructions and registers can be written in any case, except
+/// for strings between double quotes, which are moved as they're written.
+pub(super) fn lowercase(code: &str) -> String {
+    let mut lowercased = String::with_capacity(code.len());
+    let (mut in_string, mut escaped, mut in_comment) = (false, false, false);
+    for c in code.chars() {
+        if in_string {
+            lowercased.push(c);
+            in_string = c != '\n' && (escaped || c != '"');
+            escaped = !escaped && c == '\\';
+            continue;
+        }
+        match c {
+            '\n' => in_comment = false,
+            '#' => in_comment = true,
+            '"' => in_string = !in_comment,
+            _ => {}
+        }
+        lowercased.extend(c.to_lowercase());
+    }
+    lowercased
+}
@@ -0,0 +1,893 @@
+use std::{collections::HashMap, ops::Range};
+
+use ariadne::Color;
+use logos::{Lexer, Logos};
+
+use crate::{
+    diagnostic::ErrorCode,
+    direction::Direction,
+    extension::{Extension, Extensions},
+    instruction::{Instruction, MNEMONICS},
+    message::Message,
+    number::Number,
+    register::{Register, RegisterOrNumber, GENERAL_REGISTERS},
+    utils::{did_you_mean, offset_range, report, report_with_help},
+    warning::{warn, Warning},
+};
+
+/// The label a jump or call goes to, which is whatever follows the instruction.
+fn get_label<'a>(lex: &mut Lexer<'a, CodeToken<'a>>) -> &'a str {
+    lex.slice()
+        .trim_start_matches(|c: char| c.is_ascii_lowercase())
+        .trim_start_matches([' ', '\t', '\r', '\x0c'])
+}
+
+fn get_label_definition<'a>(lex: &mut Lexer<'a, CodeToken<'a>>) -> &'a str {
+    lex.slice().trim_end_matches(':')
+}
+
+/// The value of a parameter like `$step`, which fails to lex if the template doesn't have it.
+fn get_parameter<'a>(lex: &mut Lexer<'a, CodeToken<'a>>) -> Option<Number> {
+    lex.extras.parameters.get(&lex.slice()[1..]).copied()
+}
+
+/// The number of a register like `r2`, which fails to lex if there's no such register.
+fn get_general<'a>(lex: &mut Lexer<'a, CodeToken<'a>>) -> Option<u8> {
+    lex.slice()[1..]
+        .parse()
+        .ok()
+        .filter(|&register: &u8| usize::from(register) < GENERAL_REGISTERS)
+}
+
+#[derive(Logos, Debug, PartialEq)]
+#[logos(skip r"[ \t\r\f]+|#[^\n]*")]
+#[logos(extras = Extras)]
+enum CodeToken<'a> {
+    #[token("nop")]
+    Noop,
+
+    #[token("mov")]
+    Move,
+
+    #[token("mez")]
+    MoveEqualZero,
+
+    #[token("mnz")]
+    MoveNotZero,
+
+    #[token("swp")]
+    Swap,
+
+    #[token("sav")]
+    Save,
+
+    #[token("add")]
+    Add,
+
+    #[token("sub")]
+    Subtract,
+
+    #[token("neg")]
+    Negate,
+
+    #[token("push")]
+    Push,
+
+    #[token("pop")]
+    Pop,
+
+    #[regex(r"call[ \t\r\f]+[^ \t#\n\r\f:]+", get_label)]
+    Call(&'a str),
+
+    #[token("ret")]
+    Return,
+
+    #[token(".dat")]
+    Data,
+
+    #[token("rdn")]
+    ReadNext,
+
+    #[token("rdx")]
+    ReadIndexed,
+
+    #[regex(r"jmp[ \t\r\f]+[^ \t#\n\r\f:]+", get_label)]
+    Jump(&'a str),
+
+    #[regex(r"jez[ \t\r\f]+[^ \t#\n\r\f:]+", get_label)]
+    JumpEqualZero(&'a str),
+
+    #[regex(r"jnz[ \t\r\f]+[^ \t#\n\r\f:]+", get_label)]
+    JumpNotZero(&'a str),
+
+    #[regex(r"jgz[ \t\r\f]+[^ \t#\n\r\f:]+", get_label)]
+    JumpGreaterThanZero(&'a str),
+
+    #[regex(r"jlz[ \t\r\f]+[^ \t#\n\r\f:]+", get_label)]
+    JumpLessThanZero(&'a str),
+
+    #[token("jro")]
+    JumpRelative,
+
+    #[regex(r"[^ \t#\n\r\f:]+:", get_label_definition)]
+    Label(&'a str),
+
+    #[token("\n")]
+    Newline,
+
+    #[regex(r"-?\d+", |lex| lex.slice().parse().ok())]
+    #[regex(r"\$[a-z_][a-z0-9_]*", get_parameter)]
+    Number(Number),
+
+    #[token("up")]
+    Up,
+
+    #[token("down")]
+    Down,
+
+    #[token("left")]
+    Left,
+
+    #[token("right")]
+    Right,
+
+    #[token("above")]
+    Above,
+
+    #[token("below")]
+    Below,
+
+    #[token("any")]
+    Any,
+
+    #[token("last")]
+    Last,
+
+    #[token("acc")]
+    Accumulator,
+
+    #[token("bak")]
+    Backup,
+
+    #[token("nil")]
+    Nil,
+
+    #[regex(r"r[0-9]+", get_general)]
+    General(u8),
+
+    #[regex(r#""([^"\\\n]|\\[^\n])*""#)]
+    String,
+}
+
+/// Reports what was just read as needing `extension` unless it's turned on, giving back whether it
+/// can be used. `start` is where the code starts in the file.
+fn check_extension<'a>(
+    code: &Lexer<'a, CodeToken<'a>>,
+    start: usize,
+    extension: Extension,
+    path: &str,
+) -> bool {
+    if code.extras.extensions.contains(extension) {
+        return true;
+    }
+    let span = offset_range(code.span(), start);
+    report_with_help(
+        path,
+        span.start,
+        ErrorCode::ExtensionNeeded,
+        &Message::ExtensionNeeded.with(&[&extension.name()]),
+        &[(span, Message::Here.text(), Color::Red)],
+        &Message::TurnOnExtension.with(&[&extension.name()]),
+    );
+    false
+}
+
+/// Gets where an instruction writes to. `start` is where the code starts in the file, since `span`
+/// is where the instruction is in the file.
+fn get_register<'a>(
+    code: &mut Lexer<'a, CodeToken<'a>>,
+    span: Range<usize>,
+    start: usize,
+    path: &str,
+) -> Option<Register> {
+    match code.next() {
+        Some(Ok(CodeToken::Up)) => Some(Register::Direction(Direction::Up)),
+        Some(Ok(CodeToken::Down)) => Some(Register::Direction(Direction::Down)),
+        Some(Ok(CodeToken::Left)) => Some(Register::Direction(Direction::Left)),
+        Some(Ok(CodeToken::Right)) => Some(Register::Direction(Direction::Right)),
+        Some(Ok(CodeToken::Above)) => Some(Register::Direction(Direction::Above)),
+        Some(Ok(CodeToken::Below)) => Some(Register::Direction(Direction::Below)),
+        Some(Ok(CodeToken::Any)) => Some(Register::Any),
+        Some(Ok(CodeToken::Last)) => Some(Register::Last),
+        Some(Ok(CodeToken::Accumulator)) => Some(Register::Accumulator),
+        Some(Ok(CodeToken::Nil)) => Some(Register::Nil),
+        Some(Ok(CodeToken::General(register))) => {
+            check_extension(code, start, Extension::Registers, path)
+                .then_some(Register::General(register))
+        }
+        Some(Ok(CodeToken::Backup)) => {
+            let backup_span = offset_range(code.span(), start);
+            report_with_help(
+                path,
+                backup_span.start,
+                ErrorCode::ReadOnlyRegister,
+                Message::ReadOnlyRegister.text(),
+                &[
+                    (span, Message::FromInstructionHere.text(), Color::Blue),
+                    (backup_span, Message::Here.text(), Color::Red),
+                ],
+                Message::UseSave.text(),
+            );
+            None
+        }
+        _ => {
+            report(
+                path,
+                span.start,
+                ErrorCode::ExpectedRegister,
+                Message::ExpectedRegister.text(),
+                &[(span, Message::FromInstructionHere.text(), Color::Blue)],
+            );
+            None
+        }
+    }
+}
+
+/// Whether a string follows, which is only ever moved.
+fn string_follows<'a>(code: &Lexer<'a, CodeToken<'a>>) -> bool {
+    code.remainder()
+        .trim_start_matches([' ', '\t', '\r', '\x0c'])
+        .starts_with('"')
+}
+
+/// Reads a string like `"Hi!\n"` into the codes of its characters, which are moved one by one.
+/// `start` is where the code starts in the file.
+fn get_string<'a>(
+    code: &mut Lexer<'a, CodeToken<'a>>,
+    start: usize,
+    path: &str,
+) -> Option<Vec<Number>> {
+    // Where the opening quote is in the file
+    let rest = code.remainder();
+    let quote = start + code.span().end + rest.len()
+        - rest.trim_start_matches([' ', '\t', '\r', '\x0c']).len();
+    let token = code.next();
+    let span = offset_range(code.span(), start);
+    let invalid = |span: Range<usize>, label: Message| {
+        report(
+            path,
+            span.start,
+            ErrorCode::InvalidString,
+            Message::InvalidString.text(),
+            &[(span, label.text(), Color::Red)],
+        );
+    };
+    let Some(Ok(CodeToken::String)) = token else {
+        // Only a string that never ends can't be read
+        report(
+            path,
+            quote,
+            ErrorCode::InvalidSyntax,
+            Message::InvalidSyntax.text(),
+            &[(quote..quote + 1, Message::Here.text(), Color::Red)],
+        );
+        return None;
+    };
+    if !check_extension(code, start, Extension::Strings, path) {
+        return None;
+    }
+
+    let literal = code.slice();
+    let mut values = Vec::new();
+    // Where each character is in the file, after the opening quote
+    let mut chars = literal[1..literal.len() - 1]
+        .char_indices()
+        .map(|(i, c)| (span.start + 1 + i, c));
+    while let Some((at, c)) = chars.next() {
+        let (c, len) = match c {
+            '\\' => {
+                let (_, escaped) = chars
+                    .next()
+                    .expect("A backslash is always followed by something");
+                let c = match escaped {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    '0' => '\0',
+                    '\\' | '"' => escaped,
+                    _ => {
+                        invalid(at..at + 1 + escaped.len_utf8(), Message::UnknownEscape);
+                        return None;
+                    }
+                };
+                (c, 1 + escaped.len_utf8())
+            }
+            c => (c, c.len_utf8()),
+        };
+        if u32::from(c) > 999 {
+            invalid(at..at + len, Message::CodeAbove999);
+            return None;
+        }
+        values.push(Number::from(u32::from(c) as i32));
+    }
+    if values.is_empty() {
+        invalid(span, Message::NothingInString);
+        return None;
+    }
+    Some(values)
+}
+
+/// Gives back `x`, the number just read, after warning about it if it's written out of range, unless
+/// the warning is denied. `start` is where the code starts in the file.
+fn get_literal<'a>(
+    code: &Lexer<'a, CodeToken<'a>>,
+    x: Number,
+    start: usize,
+    path: &str,
+) -> Option<Number> {
+    let literal = code.slice();
+    // Arguments of templates are checked when they're given
+    if !literal.starts_with('$')
+        && literal
+            .trim_start_matches('-')
+            .trim_start_matches('0')
+            .len()
+            > 3
+    {
+        let literal_span = offset_range(code.span(), start);
+        let denied = warn(
+            Warning::LiteralOutOfRange,
+            path,
+            literal_span.start,
+            &Message::LiteralOutOfRange.with(&[&literal, &x]),
+            &[(literal_span, Message::NotInRange.text(), Color::Yellow)],
+        );
+        if denied {
+            return None;
+        }
+    }
+    Some(x)
+}
+
+/// Reads the values following `.dat` up to the end of the line into `data`, giving back whether
+/// they were all values. `start` is where the code starts in the file.
+fn get_data<'a>(
+    code: &mut Lexer<'a, CodeToken<'a>>,
+    start: usize,
+    path: &str,
+    data: &mut Vec<Number>,
+) -> bool {
+    loop {
+        match code.next() {
+            Some(Ok(CodeToken::Number(x))) => match get_literal(code, x, start, path) {
+                Some(x) => data.push(x),
+                None => return false,
+            },
+            Some(Ok(CodeToken::Newline)) | None => return true,
+            _ => {
+                let span = offset_range(code.span(), start);
+                report(
+                    path,
+                    span.start,
+                    ErrorCode::ExpectedNumber,
+                    Message::ExpectedNumber.text(),
+                    &[(span, Message::Here.text(), Color::Red)],
+                );
+                return false;
+            }
+        }
+    }
+}
+
+fn get_register_or_number<'a>(
+    code: &mut Lexer<'a, CodeToken<'a>>,
+    span: Range<usize>,
+    path: &str,
+) -> Option<RegisterOrNumber> {
+    // Where the code starts in the file, since `span` is where the instruction is in the file
+    let start = span.start - code.span().start;
+    match code.next() {
+        Some(Ok(CodeToken::Number(x))) => {
+            get_literal(code, x, start, path).map(RegisterOrNumber::Number)
+        }
+        Some(Ok(CodeToken::Up)) => Some(RegisterOrNumber::Register(Register::Direction(
+            Direction::Up,
+        ))),
+        Some(Ok(CodeToken::Down)) => Some(RegisterOrNumber::Register(Register::Direction(
+            Direction::Down,
+        ))),
+        Some(Ok(CodeToken::Left)) => Some(RegisterOrNumber::Register(Register::Direction(
+            Direction::Left,
+        ))),
+        Some(Ok(CodeToken::Right)) => Some(RegisterOrNumber::Register(Register::Direction(
+            Direction::Right,
+        ))),
+        Some(Ok(CodeToken::Above)) => Some(RegisterOrNumber::Register(Register::Direction(
+            Direction::Above,
+        ))),
+        Some(Ok(CodeToken::Below)) => Some(RegisterOrNumber::Register(Register::Direction(
+            Direction::Below,
+        ))),
+        Some(Ok(CodeToken::Any)) => Some(RegisterOrNumber::Register(Register::Any)),
+        Some(Ok(CodeToken::Last)) => Some(RegisterOrNumber::Register(Register::Last)),
+        Some(Ok(CodeToken::Accumulator)) => Some(RegisterOrNumber::Register(Register::Accumulator)),
+        Some(Ok(CodeToken::Nil)) => Some(RegisterOrNumber::Register(Register::Nil)),
+        Some(Ok(CodeToken::Backup)) => check_extension(code, start, Extension::ReadableBak, path)
+            .then_some(RegisterOrNumber::Register(Register::Backup)),
+        Some(Ok(CodeToken::General(register))) => {
+            check_extension(code, start, Extension::Registers, path)
+                .then_some(RegisterOrNumber::Register(Register::General(register)))
+        }
+        _ => {
+            report(
+                path,
+                span.start,
+                ErrorCode::ExpectedRegisterOrNumber,
+                Message::ExpectedRegisterOrNumber.text(),
+                &[(span, Message::FromInstructionHere.text(), Color::Blue)],
+            );
+            None
+        }
+    }
+}
+
+/// Skips the rest of the line after an error, unless the error was at the end of the line.
+fn skip_line<'a>(code: &mut Lexer<'a, CodeToken<'a>>) {
+    if code.slice() == "\n" {
+        return;
+    }
+    for token in code.by_ref() {
+        if let Ok(CodeToken::Newline) = token {
+            break;
+        }
+    }
+}
+
+/// A label, which is only looked up by its name once, and by its index after that.
+struct Symbol<'a> {
+    name: &'a str,
+    /// The instruction the label is at and where it's defined, once it is.
+    definition: Option<(usize, Range<usize>)>,
+    used: bool,
+}
+
+/// Every label in a node, whether it's been defined yet or only jumped to.
+#[derive(Default)]
+struct Symbols<'a> {
+    symbols: Vec<Symbol<'a>>,
+    indices: HashMap<&'a str, usize>,
+}
+
+impl<'a> Symbols<'a> {
+    /// The index of the label called `name`, adding it if it hasn't been seen yet.
+    fn intern(&mut self, name: &'a str) -> usize {
+        *self.indices.entry(name).or_insert_with(|| {
+            self.symbols.push(Symbol {
+                name,
+                definition: None,
+                used: false,
+            });
+            self.symbols.len() - 1
+        })
+    }
+
+    /// The index of the label called `name`, which something jumps to.
+    fn jump_to(&mut self, name: &'a str) -> usize {
+        let symbol = self.intern(name);
+        self.symbols[symbol].used = true;
+        symbol
+    }
+}
+
+/// The instructions of a node, along with where each of them is, every label with the instruction
+/// it's at and where it's defined, and the data given with `.dat`.
+pub(super) type Code = (
+    Vec<Instruction>,
+    Vec<Range<usize>>,
+    Vec<(String, usize, Range<usize>)>,
+    Vec<Number>,
+);
+
+/// The values of the parameters of a template by name, which are put in for `$name` in its code.
+pub(super) type Parameters = HashMap<String, Number>;
+
+/// What the code is read with besides its text.
+struct Extras {
+    parameters: Parameters,
+    /// The extensions the code may use, see [`check_extension`].
+    extensions: Extensions,
+}
+
+pub(super) fn parse_code(
+    start: usize,
+    path: String,
+    code: &str,
+    parameters: Parameters,
+    extensions: Extensions,
+) -> Option<Code> {
+    let mut code = CodeToken::lexer_with_extras(
+        code,
+        Extras {
+            parameters,
+            extensions,
+        },
+    );
+
+    let mut symbols = Symbols::default();
+    let mut post_processing_instructions = Vec::new();
+    let mut spans = Vec::new();
+    let mut data = Vec::new();
+
+    enum PostProcessing {
+        Instruction(Instruction),
+
+        // To be replaced with an instruction once every label is known
+        Jump(usize, Range<usize>),
+
+        JumpEqualZero(usize, Range<usize>),
+        JumpNotZero(usize, Range<usize>),
+
+        JumpGreaterThanZero(usize, Range<usize>),
+        JumpLessThanZero(usize, Range<usize>),
+
+        Call(usize, Range<usize>),
+    }
+
+    impl From<Instruction> for PostProcessing {
+        fn from(instruction: Instruction) -> Self {
+            PostProcessing::Instruction(instruction)
+        }
+    }
+
+    // Whether any line had an error, in which case the rest are still checked for errors
+    let mut failed = false;
+    let mut prev_was_label = None;
+    while let Some(token) = code.next() {
+        prev_was_label = None;
+        if token.is_err() {
+            let span = offset_range(code.span(), start);
+            let labels = [(span.clone(), Message::Here.text(), Color::Red)];
+
+            // Something that isn't anything at the start of a line is likely a misspelled instruction
+            let (before, after) = code.source().split_at(code.span().start);
+            let word = after
+                .split(|c: char| c.is_whitespace() || c == '#')
+                .next()
+                .unwrap_or_default();
+            match did_you_mean(word, MNEMONICS) {
+                Some(mnemonic)
+                    if before.trim_end_matches([' ', '\t']).ends_with(['\n', ':'])
+                        || before.trim().is_empty() =>
+                {
+                    report_with_help(
+                        &path,
+                        span.start,
+                        ErrorCode::InvalidSyntax,
+                        Message::InvalidSyntax.text(),
+                        &labels,
+                        &Message::DidYouMean.with(&[&mnemonic]),
+                    );
+                }
+                _ => report(
+                    &path,
+                    span.start,
+                    ErrorCode::InvalidSyntax,
+                    Message::InvalidSyntax.text(),
+                    &labels,
+                ),
+            }
+            failed = true;
+            skip_line(&mut code);
+            continue;
+        }
+        let span = offset_range(code.span(), start);
+        let instruction = match token.unwrap() {
+            CodeToken::Newline => continue,
+            CodeToken::Label(name) => {
+                let symbol = symbols.intern(name);
+                match &mut symbols.symbols[symbol].definition {
+                    Some((_, defined)) => {
+                        report(
+                            &path,
+                            span.start,
+                            ErrorCode::DuplicateLabel,
+                            Message::DuplicateLabel.text(),
+                            &[
+                                (
+                                    defined.clone(),
+                                    Message::AlreadyDefinedLabel.text(),
+                                    Color::Blue,
+                                ),
+                                (span.clone(), Message::NewLabel.text(), Color::Green),
+                            ],
+                        );
+                        failed = true;
+                    }
+                    definition => {
+                        *definition = Some((post_processing_instructions.len(), span.clone()));
+                    }
+                };
+                prev_was_label = Some(span.clone());
+                continue; // A label doesn't require a newline after it
+            }
+
+            CodeToken::Data => {
+                // Data isn't an instruction, so it's only ever followed by values
+                failed |= !check_extension(&code, start, Extension::Data, &path)
+                    || !get_data(&mut code, start, &path, &mut data);
+                skip_line(&mut code);
+                continue;
+            }
+
+            CodeToken::Noop => Some(Instruction::Noop.into()),
+
+            CodeToken::Move if string_follows(&code) => get_string(&mut code, start, &path)
+                .and_then(|values| {
+                    let destination = get_register(&mut code, span.clone(), start, &path)?;
+                    // Every character but the last is moved by an instruction of its own here
+                    let (&last, values) = values.split_last()?;
+                    for &value in values {
+                        post_processing_instructions.push(
+                            Instruction::Move(RegisterOrNumber::Number(value), destination).into(),
+                        );
+                        spans.push(span.clone());
+                    }
+                    Some(Instruction::Move(RegisterOrNumber::Number(last), destination).into())
+                }),
+            CodeToken::Move => {
+                get_register_or_number(&mut code, span.clone(), &path).and_then(|source| {
+                    Some(
+                        Instruction::Move(
+                            source,
+                            get_register(&mut code, span.clone(), start, &path)?,
+                        )
+                        .into(),
+                    )
+                })
+            }
+            conditional @ (CodeToken::MoveEqualZero | CodeToken::MoveNotZero) => {
+                check_extension(&code, start, Extension::ConditionalMove, &path)
+                    .then(|| get_register_or_number(&mut code, span.clone(), &path))
+                    .flatten()
+                    .and_then(|source| {
+                        let destination = get_register(&mut code, span.clone(), start, &path)?;
+                        Some(match conditional {
+                            CodeToken::MoveEqualZero => {
+                                Instruction::MoveEqualZero(source, destination).into()
+                            }
+                            _ => Instruction::MoveNotZero(source, destination).into(),
+                        })
+                    })
+            }
+
+            CodeToken::Swap => Some(Instruction::Swap.into()),
+            CodeToken::Save => Some(Instruction::Save.into()),
+
+            CodeToken::Add => get_register_or_number(&mut code, span.clone(), &path)
+                .map(|source| Instruction::Add(source).into()),
+            CodeToken::Subtract => get_register_or_number(&mut code, span.clone(), &path)
+                .map(|source| Instruction::Subtract(source).into()),
+            CodeToken::Negate => Some(Instruction::Negate.into()),
+
+            CodeToken::Push => check_extension(&code, start, Extension::Stack, &path)
+                .then(|| get_register_or_number(&mut code, span.clone(), &path))
+                .flatten()
+                .map(|source| Instruction::Push(source).into()),
+            CodeToken::Pop => check_extension(&code, start, Extension::Stack, &path)
+                .then(|| get_register(&mut code, span.clone(), start, &path))
+                .flatten()
+                .map(|destination| Instruction::Pop(destination).into()),
+
+            read @ (CodeToken::ReadNext | CodeToken::ReadIndexed) => {
+                check_extension(&code, start, Extension::Data, &path)
+                    .then(|| get_register(&mut code, span.clone(), start, &path))
+                    .flatten()
+                    .map(|destination| match read {
+                        CodeToken::ReadNext => Instruction::ReadNext(destination).into(),
+                        _ => Instruction::ReadIndexed(destination).into(),
+                    })
+            }
+
+            CodeToken::Call(label) => check_extension(&code, start, Extension::Call, &path)
+                .then(|| PostProcessing::Call(symbols.jump_to(label), span.clone())),
+            CodeToken::Return => check_extension(&code, start, Extension::Call, &path)
+                .then_some(Instruction::Return.into()),
+
+            CodeToken::Jump(label) => {
+                Some(PostProcessing::Jump(symbols.jump_to(label), span.clone()))
+            }
+
+            CodeToken::JumpEqualZero(label) => Some(PostProcessing::JumpEqualZero(
+                symbols.jump_to(label),
+                span.clone(),
+            )),
+            CodeToken::JumpNotZero(label) => Some(PostProcessing::JumpNotZero(
+                symbols.jump_to(label),
+                span.clone(),
+            )),
+
+            CodeToken::JumpGreaterThanZero(label) => Some(PostProcessing::JumpGreaterThanZero(
+                symbols.jump_to(label),
+                span.clone(),
+            )),
+            CodeToken::JumpLessThanZero(label) => Some(PostProcessing::JumpLessThanZero(
+                symbols.jump_to(label),
+                span.clone(),
+            )),
+
+            CodeToken::JumpRelative => get_register_or_number(&mut code, span.clone(), &path)
+                .map(|source| Instruction::JumpRelative(source).into()),
+
+            token => {
+                let name = match token {
+                    CodeToken::Accumulator => "Acc",
+                    CodeToken::Backup => "Bak",
+                    CodeToken::General(_) => "Register",
+                    CodeToken::Any => "Any",
+                    CodeToken::Last => "Last",
+                    CodeToken::Nil => "Nil",
+                    CodeToken::Up => "Up",
+                    CodeToken::Down => "Down",
+                    CodeToken::Left => "Left",
+                    CodeToken::Right => "Right",
+                    CodeToken::Above => "Above",
+                    CodeToken::Below => "Below",
+                    CodeToken::Number(_) => "Number",
+                    CodeToken::String => "String",
+                    _ => unreachable!(),
+                };
+                report(
+                    &path,
+                    span.start,
+                    ErrorCode::ExpressionAsInstruction,
+                    &Message::ExpressionAsInstruction.with(&[&name]),
+                    &[(span.clone(), Message::Here.text(), Color::Red)],
+                );
+                None
+            }
+        };
+        let Some(instruction) = instruction else {
+            failed = true;
+            skip_line(&mut code);
+            continue;
+        };
+        post_processing_instructions.push(instruction);
+        spans.push(span.clone());
+
+        match code.next() {
+            Some(Ok(CodeToken::Newline)) => {}
+            _ => {
+                report(
+                    &path,
+                    span.start,
+                    ErrorCode::ExpectedNewline,
+                    Message::ExpectedNewline.text(),
+                    &[(span, Message::TheInstruction.text(), Color::Blue)],
+                );
+                failed = true;
+                skip_line(&mut code);
+            }
+        }
+    }
+
+    // Reading data is only pointed out once all of it is known, since it can be given anywhere
+    if data.is_empty() {
+        for (instruction, span) in post_processing_instructions.iter().zip(&spans) {
+            if let PostProcessing::Instruction(
+                Instruction::ReadNext(_) | Instruction::ReadIndexed(_),
+            ) = instruction
+            {
+                report_with_help(
+                    &path,
+                    span.start,
+                    ErrorCode::NoData,
+                    Message::NoData.text(),
+                    &[(span.clone(), Message::TheInstruction.text(), Color::Blue)],
+                    Message::AddData.text(),
+                );
+                failed = true;
+            }
+        }
+    }
+
+    if let Some(span) = prev_was_label {
+        report(
+            &path,
+            span.start,
+            ErrorCode::LabelAtEnd,
+            Message::LabelAtEnd.text(),
+            &[(span, Message::TheLabel.text(), Color::Blue)],
+        );
+        failed = true;
+    }
+
+    let mut unused_labels = symbols
+        .symbols
+        .iter()
+        .filter(|symbol| !symbol.used)
+        .filter_map(|symbol| Some(symbol.definition.as_ref()?.1.clone()))
+        .collect::<Vec<_>>();
+    unused_labels.sort_by_key(|span| span.start);
+    for span in unused_labels {
+        failed |= warn(
+            Warning::UnusedLabel,
+            &path,
+            span.start,
+            Message::UnusedLabel.text(),
+            &[(span, Message::TheLabel.text(), Color::Yellow)],
+        );
+    }
+
+    let eval_label = |symbol: usize, span: Range<usize>| {
+        let res = symbols.symbols[symbol]
+            .definition
+            .as_ref()
+            .map(|&(index, _)| index);
+        if res.is_none() {
+            let labels_used = [(span.clone(), Message::LabelUsage.text(), Color::Blue)];
+            let defined = symbols
+                .symbols
+                .iter()
+                .filter(|symbol| symbol.definition.is_some())
+                .map(|symbol| symbol.name);
+            match did_you_mean(symbols.symbols[symbol].name, defined) {
+                Some(suggestion) => report_with_help(
+                    &path,
+                    span.start,
+                    ErrorCode::LabelNotFound,
+                    Message::LabelNotFound.text(),
+                    &labels_used,
+                    &Message::DidYouMean.with(&[&suggestion]),
+                ),
+                None => report(
+                    &path,
+                    span.start,
+                    ErrorCode::LabelNotFound,
+                    Message::LabelNotFound.text(),
+                    &labels_used,
+                ),
+            }
+        }
+        res
+    };
+
+    let instructions = post_processing_instructions
+        .into_iter()
+        .map(|instruction| {
+            Some(match instruction {
+                PostProcessing::Instruction(instruction) => instruction,
+
+                PostProcessing::Jump(label, span) => Instruction::Jump(eval_label(label, span)?),
+
+                PostProcessing::JumpEqualZero(label, span) => {
+                    Instruction::JumpEqualZero(eval_label(label, span)?)
+                }
+                PostProcessing::JumpNotZero(label, span) => {
+                    Instruction::JumpNotZero(eval_label(label, span)?)
+                }
+
+                PostProcessing::JumpGreaterThanZero(label, span) => {
+                    Instruction::JumpGreaterThanZero(eval_label(label, span)?)
+                }
+                PostProcessing::JumpLessThanZero(label, span) => {
+                    Instruction::JumpLessThanZero(eval_label(label, span)?)
+                }
+
+                PostProcessing::Call(label, span) => Instruction::Call(eval_label(label, span)?),
+            })
+        })
+        .collect::<Option<_>>();
+    if failed {
+        return None;
+    }
+    let mut labels = symbols
+        .symbols
+        .iter()
+        .filter_map(|symbol| {
+            let (index, span) = symbol.definition.clone()?;
+            Some((symbol.name.to_owned(), index, span))
+        })
+        .collect::<Vec<_>>();
+    labels.sort_by_key(|(_, _, span)| span.start);
+    Some((instructions?, spans, labels, data))
+}
@@ -0,0 +1,770 @@
+use std::ops::Range;
+
+use ariadne::Color;
+use logos::{Lexer, Logos};
+
+use crate::{
+    diagnostic::ErrorCode,
+    direction::Direction,
+    message::Message,
+    position::{Coords, Position, GAME_COLUMNS, GAME_ROWS},
+    utils::{offset_range, report},
+};
+
+#[derive(Logos, Debug, Clone, PartialEq)]
+#[logos(skip r"[ \t\r\f]+")]
+enum SettingsToken {
+    #[regex(r"[a-z_]+", |lex| lex.slice().to_string())]
+    SpecialNode(String),
+
+    #[regex(r"-?\d+", |lex| lex.slice().parse().ok())]
+    Number(i32),
+
+    #[token(",")]
+    Comma,
+
+    #[token(":")]
+    Colon,
+
+    #[token("->")]
+    Arrow,
+
+    #[token("acc")]
+    Accumulator,
+
+    #[token("bak")]
+    Backup,
+
+    #[token("separator")]
+    Separator,
+
+    #[token("prompt")]
+    Prompt,
+
+    #[token("depth")]
+    Depth,
+
+    #[token("overflow")]
+    Overflow,
+
+    #[token("quorum")]
+    Quorum,
+
+    #[token("mode")]
+    Mode,
+
+    #[token("echo")]
+    Echo,
+
+    #[token("last")]
+    Last,
+
+    #[token("ptr")]
+    Pointer,
+
+    #[token("name")]
+    Name,
+
+    #[token("speed")]
+    Speed,
+
+    #[token("locked")]
+    Locked,
+
+    #[token("values")]
+    Values,
+
+    #[regex(r#""[^"\n]*""#)]
+    #[regex(r"\d+x\d+")]
+    #[regex(r#"[^ \t\r\f,:"]*[/.\\~][^ \t\r\f,"]*"#)]
+    Text,
+}
+
+pub(super) enum SpecialNode {
+    NumberConsoleOut,
+    NumberConsoleIn,
+    ConsoleOut,
+    ConsoleIn,
+    ByteOut,
+    ByteIn,
+    LineOut,
+    Image,
+    Stack,
+    Bus,
+    Math,
+    PipeIn(String),
+    PipeOut(String),
+    SharedMemory(String),
+    Ram(String),
+    Portal(String),
+    Split(Direction, Direction),
+    Http(String),
+    LogOut(String),
+    CsvIn(String),
+    Window(String),
+    AudioOut(String),
+    /// The path of a WebAssembly module, along with its argument if it has one.
+    Plugin(
+        String,
+        // Only read when the plugins feature is enabled
+        #[cfg_attr(not(feature = "plugins"), allow(dead_code))] Option<String>,
+    ),
+    /// The path of a Rhai script, along with its argument if it has one.
+    Script(
+        String,
+        // Only read when the scripting feature is enabled
+        #[cfg_attr(not(feature = "scripting"), allow(dead_code))] Option<String>,
+    ),
+    /// A node type registered by a library user, along with its argument if it has one.
+    Custom(String, Option<String>),
+}
+
+impl From<String> for SpecialNode {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "number_console_out" => SpecialNode::NumberConsoleOut,
+            "number_console_in" => SpecialNode::NumberConsoleIn,
+            "console_out" => SpecialNode::ConsoleOut,
+            "console_in" => SpecialNode::ConsoleIn,
+            "byte_out" => SpecialNode::ByteOut,
+            "byte_in" => SpecialNode::ByteIn,
+            "line_out" => SpecialNode::LineOut,
+            "image" => SpecialNode::Image,
+            "stack" => SpecialNode::Stack,
+            "bus" => SpecialNode::Bus,
+            "math" => SpecialNode::Math,
+            _ => SpecialNode::Custom(value, None),
+        }
+    }
+}
+
+/// A port, along with the node and port it's wired to, if it's wired to anything.
+pub(super) type Wire = (Direction, Option<(Position, Direction)>);
+
+pub(super) struct Settings {
+    pub(super) position: Position,
+    pub(super) accumulator: Option<i32>,
+    pub(super) backup: Option<i32>,
+    pub(super) last: Option<Direction>,
+    pub(super) pointer: Option<i32>,
+    pub(super) name: Option<String>,
+    pub(super) wires: Vec<Wire>,
+    pub(super) speed: Option<String>,
+    /// Whether the node is part of a puzzle and can't be changed by the solution.
+    pub(super) locked: bool,
+    pub(super) values: Option<Vec<i32>>,
+    pub(super) special_node: Option<SpecialNode>,
+    pub(super) separator: Option<i32>,
+    pub(super) prompt: Option<String>,
+    pub(super) echo: Option<bool>,
+    pub(super) depth: Option<i32>,
+    pub(super) overflow: Option<String>,
+    pub(super) quorum: Option<i32>,
+    pub(super) mode: Option<String>,
+}
+
+impl Settings {
+    /// A node at `position` without any other settings, like an instance of a template.
+    pub(super) fn at(position: Position) -> Self {
+        Self {
+            position,
+            accumulator: None,
+            backup: None,
+            last: None,
+            pointer: None,
+            name: None,
+            wires: Vec::new(),
+            speed: None,
+            locked: false,
+            values: None,
+            special_node: None,
+            separator: None,
+            prompt: None,
+            echo: None,
+            depth: None,
+            overflow: None,
+            quorum: None,
+            mode: None,
+        }
+    }
+}
+
+/// Gets the argument after a colon, such as the path in `pipe_in: /tmp/tis_in`. The argument is
+/// taken from the original text so that its case is preserved.
+fn get_argument(
+    settings: &mut Lexer<SettingsToken>,
+    original: &str,
+    start: usize,
+    path: &str,
+) -> Option<String> {
+    let span = offset_range(settings.span(), start);
+    if let Some(Ok(SettingsToken::Colon)) = settings.next() {
+        if let Some(Ok(
+            SettingsToken::Text | SettingsToken::SpecialNode(_) | SettingsToken::Number(_),
+        )) = settings.next()
+        {
+            return Some(original[settings.span()].trim_matches('"').to_owned());
+        }
+    }
+
+    report(
+        path,
+        span.start,
+        ErrorCode::ExpectedArgument,
+        Message::ExpectedArgument.text(),
+        &[(span, Message::AfterThis.text(), Color::Red)],
+    );
+    None
+}
+
+/// Gets the number after a colon, such as the `59` in `separator: 59`.
+fn get_number(settings: &mut Lexer<SettingsToken>, start: usize, path: &str) -> Option<i32> {
+    let span = offset_range(settings.span(), start);
+    if let (Some(Ok(SettingsToken::Colon)), Some(Ok(SettingsToken::Number(number)))) =
+        (settings.next(), settings.next())
+    {
+        return Some(number);
+    }
+
+    report(
+        path,
+        span.start,
+        ErrorCode::ExpectedArgument,
+        Message::ExpectedNumberArgument.text(),
+        &[(span, Message::AfterThis.text(), Color::Red)],
+    );
+    None
+}
+
+/// Reports the last of the `given` settings, called `name`, being given a second time.
+fn report_duplicate(path: &str, given: &[(SettingsToken, Range<usize>)], name: &str) {
+    let (setting, span) = given.last().unwrap();
+    let (_, first) = given.iter().find(|(token, _)| token == setting).unwrap();
+    report(
+        path,
+        span.start,
+        ErrorCode::DuplicateSetting,
+        &Message::DuplicateSetting.with(&[&name]),
+        &[
+            (first.clone(), Message::AlreadySet.text(), Color::Blue),
+            (span.clone(), Message::SetAgain.text(), Color::Red),
+        ],
+    );
+}
+
+/// Whether a node of the kind given by `special_node`, or an instruction node without one, has
+/// `setting`.
+fn has_setting(special_node: Option<&SpecialNode>, setting: &SettingsToken) -> bool {
+    match setting {
+        SettingsToken::Separator => matches!(special_node, Some(SpecialNode::CsvIn(_))),
+        SettingsToken::Prompt | SettingsToken::Echo => matches!(
+            special_node,
+            Some(SpecialNode::NumberConsoleIn | SpecialNode::ConsoleIn)
+        ),
+        SettingsToken::Quorum => matches!(special_node, Some(SpecialNode::Bus)),
+        SettingsToken::Mode => matches!(special_node, Some(SpecialNode::Split(..))),
+        SettingsToken::Last | SettingsToken::Pointer => special_node.is_none(),
+        SettingsToken::Values => matches!(special_node, Some(SpecialNode::Stack)),
+        SettingsToken::Depth | SettingsToken::Overflow => {
+            matches!(special_node, None | Some(SpecialNode::Stack))
+        }
+        _ => true,
+    }
+}
+
+/// Gets a second argument right after the first one, like the `17` in `plugin: gpio.wasm 17`.
+fn get_extra_argument(settings: &mut Lexer<SettingsToken>, original: &str) -> Option<String> {
+    let mut lookahead = settings.clone();
+    match lookahead.next() {
+        Some(Ok(SettingsToken::Text | SettingsToken::SpecialNode(_))) => {}
+        // A number followed by a comma is the position instead
+        Some(Ok(SettingsToken::Number(_)))
+            if !matches!(lookahead.clone().next(), Some(Ok(SettingsToken::Comma))) => {}
+        _ => return None,
+    }
+    *settings = lookahead;
+    Some(original[settings.span()].trim_matches('"').to_owned())
+}
+
+/// Reads the name of a port, like `up`.
+fn direction(word: &str) -> Option<Direction> {
+    match word {
+        "up" => Some(Direction::Up),
+        "down" => Some(Direction::Down),
+        "left" => Some(Direction::Left),
+        "right" => Some(Direction::Right),
+        "above" => Some(Direction::Above),
+        "below" => Some(Direction::Below),
+        _ => None,
+    }
+}
+
+/// Gets what a port is wired to after its arrow, either a position and a port like the
+/// `5,5 left` in `right -> 5,5 left`, or `none` to leave the port unconnected.
+fn get_wire(
+    settings: &mut Lexer<SettingsToken>,
+    start: usize,
+    path: &str,
+    coords: Coords,
+) -> Option<Option<(Position, Direction)>> {
+    let span = offset_range(settings.span(), start);
+    if let Some(wire) = read_wire(settings, coords) {
+        return Some(wire);
+    }
+
+    report(
+        path,
+        span.start,
+        ErrorCode::ExpectedWire,
+        Message::ExpectedWire.text(),
+        &[(span, Message::AfterThis.text(), Color::Red)],
+    );
+    None
+}
+
+fn read_wire(
+    settings: &mut Lexer<SettingsToken>,
+    coords: Coords,
+) -> Option<Option<(Position, Direction)>> {
+    match settings.next()?.ok()? {
+        SettingsToken::SpecialNode(none) if none == "none" => Some(None),
+        SettingsToken::Number(x) => {
+            let (Some(Ok(SettingsToken::Comma)), Some(Ok(SettingsToken::Number(y)))) =
+                (settings.next(), settings.next())
+            else {
+                return None;
+            };
+            let mut position = Position::written(x, y, coords);
+
+            let mut lookahead = settings.clone();
+            if let (Some(Ok(SettingsToken::Comma)), Some(Ok(SettingsToken::Number(z)))) =
+                (lookahead.next(), lookahead.next())
+            {
+                *settings = lookahead;
+                position = position.with_z(z);
+            }
+
+            match settings.next()?.ok()? {
+                SettingsToken::SpecialNode(port) => Some(Some((position, direction(&port)?))),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Gets the two directions after a colon, like in `split: left right`.
+fn get_directions(
+    settings: &mut Lexer<SettingsToken>,
+    start: usize,
+    path: &str,
+) -> Option<(Direction, Direction)> {
+    let span = offset_range(settings.span(), start);
+    if let Some(Ok(SettingsToken::Colon)) = settings.next() {
+        let mut direction = || match settings.next() {
+            Some(Ok(SettingsToken::SpecialNode(direction))) => match direction.as_str() {
+                "up" => Some(Direction::Up),
+                "down" => Some(Direction::Down),
+                "left" => Some(Direction::Left),
+                "right" => Some(Direction::Right),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let (Some(first), Some(second)) = (direction(), direction()) {
+            if first != second {
+                return Some((first, second));
+            }
+        }
+    }
+
+    report(
+        path,
+        span.start,
+        ErrorCode::ExpectedDirections,
+        Message::ExpectedDirections.text(),
+        &[(span, Message::AfterThis.text(), Color::Red)],
+    );
+    None
+}
+
+pub(super) fn parse_settings(
+    start: usize,
+    path: String,
+    settings: &str,
+    coords: Coords,
+) -> Option<Settings> {
+    let original = settings;
+    let settings = settings.to_lowercase();
+    let mut settings = SettingsToken::lexer(&settings);
+
+    let mut pos = None;
+    let mut accumulator = None;
+    let mut backup = None;
+    let mut last = None;
+    let mut pointer = None;
+    let mut name = None;
+    let mut wires = Vec::new();
+    let mut speed = None;
+    let mut locked = false;
+    let mut values = None;
+    let mut special_node = None;
+    let mut separator = None;
+    let mut prompt = None;
+    let mut echo = None;
+    let mut depth = None;
+    let mut overflow = None;
+    let mut quorum = None;
+    let mut mode = None;
+    // Every setting along with where it is, for pointing at it once the kind of node is known
+    let mut given = Vec::new();
+
+    while let Some(token) = settings.next() {
+        if token.is_err() {
+            let span = offset_range(settings.span(), start);
+            report(
+                &path,
+                span.start,
+                ErrorCode::InvalidSyntax,
+                Message::InvalidSyntax.text(),
+                &[(span, Message::Here.text(), Color::Red)],
+            );
+            return None;
+        }
+        let span = offset_range(settings.span(), start);
+        let token = token.unwrap();
+        given.push((token.clone(), span.clone()));
+        match token {
+            SettingsToken::SpecialNode(port)
+                if matches!(settings.clone().next(), Some(Ok(SettingsToken::Arrow))) =>
+            {
+                let Some(port) = direction(&port) else {
+                    report(
+                        &path,
+                        span.start,
+                        ErrorCode::WiredNonPort,
+                        Message::WiredNonPort.text(),
+                        &[(span, Message::NotAPort.text(), Color::Red)],
+                    );
+                    return None;
+                };
+                settings.next();
+                wires.push((port, get_wire(&mut settings, start, &path, coords)?));
+            }
+            SettingsToken::SpecialNode(name) if special_node.is_none() => {
+                let mut argument = || get_argument(&mut settings, original, start, &path);
+                special_node = Some(match name.as_str() {
+                    "pipe_in" => SpecialNode::PipeIn(argument()?),
+                    "pipe_out" => SpecialNode::PipeOut(argument()?),
+                    "shared_memory" => SpecialNode::SharedMemory(argument()?),
+                    "ram" => SpecialNode::Ram(argument()?),
+                    "portal" => SpecialNode::Portal(argument()?),
+                    "split" => {
+                        let (first, second) = get_directions(&mut settings, start, &path)?;
+                        SpecialNode::Split(first, second)
+                    }
+                    "http" => SpecialNode::Http(argument()?),
+                    "log_out" => SpecialNode::LogOut(argument()?),
+                    "csv_in" | "numbers_in" => SpecialNode::CsvIn(argument()?),
+                    "window" => SpecialNode::Window(argument()?),
+                    "audio_out" => SpecialNode::AudioOut(argument()?),
+                    "plugin" => {
+                        let path = argument()?;
+                        SpecialNode::Plugin(path, get_extra_argument(&mut settings, original))
+                    }
+                    "script" => {
+                        let path = argument()?;
+                        SpecialNode::Script(path, get_extra_argument(&mut settings, original))
+                    }
+                    _ => match SpecialNode::from(name) {
+                        // Only custom nodes know whether they take an argument
+                        SpecialNode::Custom(name, _)
+                            if matches!(
+                                settings.clone().next(),
+                                Some(Ok(SettingsToken::Colon))
+                            ) =>
+                        {
+                            SpecialNode::Custom(
+                                name,
+                                Some(get_argument(&mut settings, original, start, &path)?),
+                            )
+                        }
+                        special_node => special_node,
+                    },
+                })
+            }
+            // The game's nodes can be given by their number alone
+            SettingsToken::Number(number)
+                if pos.is_none()
+                    && coords == Coords::Game
+                    && settings.clone().next() != Some(Ok(SettingsToken::Comma)) =>
+            {
+                match Position::game_node(number) {
+                    Some(position) => pos = Some((position, span)),
+                    None => report(
+                        &path,
+                        span.start,
+                        ErrorCode::InvalidSyntax,
+                        &Message::NoGameNode.with(&[&number, &(GAME_COLUMNS * GAME_ROWS - 1)]),
+                        &[(span, Message::Here.text(), Color::Red)],
+                    ),
+                }
+            }
+            SettingsToken::Number(x) if pos.is_none() => {
+                if let Some(Ok(SettingsToken::Comma)) = settings.next() {
+                    let comma_span = offset_range(settings.span(), start);
+                    if let Some(Ok(SettingsToken::Number(y))) = settings.next() {
+                        let mut position = Position::written(x, y, coords);
+
+                        // Layered grids add a z after the y
+                        let mut lookahead = settings.clone();
+                        if let (
+                            Some(Ok(SettingsToken::Comma)),
+                            Some(Ok(SettingsToken::Number(z))),
+                        ) = (lookahead.next(), lookahead.next())
+                        {
+                            settings = lookahead;
+                            position = position.with_z(z);
+                        }
+
+                        pos = Some((position, span.start..start + settings.span().end));
+                    } else {
+                        report(
+                            &path,
+                            comma_span.start,
+                            ErrorCode::InvalidSyntax,
+                            Message::InvalidSyntax.text(),
+                            &[(comma_span, Message::Here.text(), Color::Red)],
+                        );
+                    }
+                } else {
+                    report(
+                        &path,
+                        span.start,
+                        ErrorCode::InvalidSyntax,
+                        Message::InvalidSyntax.text(),
+                        &[(span, Message::Here.text(), Color::Red)],
+                    );
+                }
+            }
+            SettingsToken::Accumulator if accumulator.is_none() => {
+                if let Some(Ok(SettingsToken::Colon)) = settings.next() {
+                    if let Some(Ok(SettingsToken::Number(x))) = settings.next() {
+                        accumulator = Some(x);
+                    } else {
+                        panic!("Expected number after colon");
+                    }
+                } else {
+                    panic!("Expected colon after accumulator");
+                }
+            }
+            SettingsToken::Backup if backup.is_none() => {
+                if let Some(Ok(SettingsToken::Colon)) = settings.next() {
+                    if let Some(Ok(SettingsToken::Number(x))) = settings.next() {
+                        backup = Some(x);
+                    } else {
+                        panic!("Expected number after colon");
+                    }
+                } else {
+                    panic!("Expected colon after backup");
+                }
+            }
+            SettingsToken::Last if last.is_none() => {
+                let argument = get_argument(&mut settings, original, start, &path)?;
+                let Some(direction) = direction(&argument.to_lowercase()) else {
+                    let span = offset_range(settings.span(), start);
+                    report(
+                        &path,
+                        span.start,
+                        ErrorCode::InvalidArgument,
+                        Message::ExpectedPort.text(),
+                        &[(span, Message::Here.text(), Color::Red)],
+                    );
+                    return None;
+                };
+                last = Some(direction);
+            }
+            SettingsToken::Pointer if pointer.is_none() => {
+                pointer = Some(get_number(&mut settings, start, &path)?);
+            }
+            SettingsToken::Separator if separator.is_none() => {
+                separator = Some(get_number(&mut settings, start, &path)?);
+            }
+            SettingsToken::Depth if depth.is_none() => {
+                depth = Some(get_number(&mut settings, start, &path)?);
+            }
+            SettingsToken::Quorum if quorum.is_none() => {
+                quorum = Some(get_number(&mut settings, start, &path)?);
+            }
+            SettingsToken::Mode if mode.is_none() => {
+                mode = Some(get_argument(&mut settings, original, start, &path)?.to_lowercase());
+            }
+            SettingsToken::Overflow if overflow.is_none() => {
+                overflow =
+                    Some(get_argument(&mut settings, original, start, &path)?.to_lowercase());
+            }
+            SettingsToken::Name if name.is_none() => {
+                name = Some(get_argument(&mut settings, original, start, &path)?);
+            }
+            SettingsToken::Speed if speed.is_none() => {
+                speed = Some(get_argument(&mut settings, original, start, &path)?);
+            }
+            SettingsToken::Locked if !locked => locked = true,
+            SettingsToken::Values if values.is_none() => {
+                if let Some(Ok(SettingsToken::Colon)) = settings.next() {
+                    let mut numbers = Vec::new();
+                    loop {
+                        // A number followed by a comma is the position instead
+                        let mut lookahead = settings.clone();
+                        match (lookahead.next(), lookahead.clone().next()) {
+                            (Some(Ok(SettingsToken::Number(x))), next)
+                                if next != Some(Ok(SettingsToken::Comma)) =>
+                            {
+                                numbers.push(x);
+                                settings = lookahead;
+                            }
+                            _ => break,
+                        }
+                    }
+                    values = Some(numbers);
+                } else {
+                    report(
+                        &path,
+                        span.start,
+                        ErrorCode::ExpectedArgument,
+                        Message::ExpectedArgument.text(),
+                        &[(span, Message::AfterThis.text(), Color::Red)],
+                    );
+                    return None;
+                }
+            }
+            SettingsToken::Prompt if prompt.is_none() => {
+                prompt = Some(get_argument(&mut settings, original, start, &path)?);
+            }
+            SettingsToken::Echo if echo.is_none() => {
+                echo = Some(
+                    match get_argument(&mut settings, original, start, &path)?
+                        .to_lowercase()
+                        .as_str()
+                    {
+                        "on" | "1" => true,
+                        "off" | "0" => false,
+                        _ => {
+                            let span = offset_range(settings.span(), start);
+                            report(
+                                &path,
+                                span.start,
+                                ErrorCode::InvalidArgument,
+                                Message::ExpectedOnOrOff.text(),
+                                &[(span, Message::Here.text(), Color::Red)],
+                            );
+                            return None;
+                        }
+                    },
+                );
+            }
+
+            SettingsToken::SpecialNode(_) => {
+                panic!("Special node already set");
+            }
+            SettingsToken::Accumulator => {
+                panic!("Accumulator already set");
+            }
+            SettingsToken::Backup => {
+                panic!("Backup already set");
+            }
+            SettingsToken::Separator
+            | SettingsToken::Prompt
+            | SettingsToken::Echo
+            | SettingsToken::Depth
+            | SettingsToken::Overflow
+            | SettingsToken::Quorum
+            | SettingsToken::Mode
+            | SettingsToken::Last
+            | SettingsToken::Pointer
+            | SettingsToken::Name
+            | SettingsToken::Speed
+            | SettingsToken::Locked
+            | SettingsToken::Values => {
+                report_duplicate(&path, &given, settings.slice());
+                return None;
+            }
+            SettingsToken::Number(_) => {
+                report(
+                    &path,
+                    span.start,
+                    ErrorCode::DuplicatePosition,
+                    Message::DuplicatePosition.text(),
+                    &[
+                        (
+                            pos.unwrap().1,
+                            Message::AlreadySetPosition.text(),
+                            Color::Blue,
+                        ),
+                        (span, Message::NewPositionStart.text(), Color::Red),
+                    ],
+                );
+                return None;
+            }
+            SettingsToken::Comma => {
+                panic!("Unexpected comma");
+            }
+            SettingsToken::Colon => {
+                panic!("Unexpected colon");
+            }
+            SettingsToken::Arrow => {
+                panic!("Unexpected arrow");
+            }
+            SettingsToken::Text => {
+                panic!("Unexpected text");
+            }
+        }
+    }
+
+    let Some((position, _)) = pos else {
+        report(
+            &path,
+            start - 1,
+            ErrorCode::MissingPosition,
+            Message::MissingPosition.text(),
+            &[(start - 1..start, Message::Here.text(), Color::Red)],
+        );
+        return None;
+    };
+
+    // Which settings a node has depends on its kind, which may only come after them
+    for (setting, span) in &given {
+        if !has_setting(special_node.as_ref(), setting) {
+            let name = &original[span.start - start..span.end - start];
+            report(
+                &path,
+                span.start,
+                ErrorCode::MisplacedSetting,
+                &Message::MisplacedSetting.with(&[&name]),
+                &[(span.clone(), Message::Here.text(), Color::Red)],
+            );
+            return None;
+        }
+    }
+
+    Some(Settings {
+        position,
+        accumulator,
+        backup,
+        last,
+        pointer,
+        name,
+        wires,
+        speed,
+        locked,
+        values,
+        special_node,
+        separator,
+        prompt,
+        echo,
+        depth,
+        overflow,
+        quorum,
+        mode,
+    })
+}
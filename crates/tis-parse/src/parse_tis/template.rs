@@ -0,0 +1,130 @@
+use crate::{
+    number::Number,
+    parse_tis::parse_code::Parameters,
+    position::{parse_position, Coords, Position},
+};
+
+/// The code of a node written once with `@template name(parameters)`, to be put at several
+/// positions with `@instantiate name(arguments) at x,y; x,y`.
+pub(super) struct Template {
+    parameters: Vec<String>,
+    /// The code of the template and where it starts in the file.
+    pub(super) code: String,
+    pub(super) start: usize,
+    /// The text of the template and where it starts in the file, which every instance shares.
+    pub(super) text: String,
+    pub(super) offset: usize,
+}
+
+impl Template {
+    /// Reads a template's name and parameters from what follows `@template`, like `adder(step)`.
+    pub(super) fn parse(
+        signature: &str,
+        code: String,
+        start: usize,
+        text: String,
+        offset: usize,
+    ) -> Result<(String, Self), String> {
+        let (name, parameters) = parse_call(signature)?;
+        for (i, parameter) in parameters.iter().enumerate() {
+            let valid = parameter.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+                && parameter
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+            if !valid {
+                return Err(format!(
+                    "Invalid parameter of template {}: '{}'",
+                    name, parameter
+                ));
+            }
+            if parameters[..i].contains(parameter) {
+                return Err(format!(
+                    "Template {} has more than one parameter called {}",
+                    name, parameter
+                ));
+            }
+        }
+        Ok((
+            name.to_owned(),
+            Self {
+                parameters: parameters.into_iter().map(str::to_owned).collect(),
+                code,
+                start,
+                text,
+                offset,
+            },
+        ))
+    }
+
+    /// The values of the parameters for `arguments`, which are given in the same order.
+    pub(super) fn bind(&self, name: &str, arguments: &[&str]) -> Result<Parameters, String> {
+        if arguments.len() != self.parameters.len() {
+            return Err(format!(
+                "Template {} takes {} arguments, but was given {}",
+                name,
+                self.parameters.len(),
+                arguments.len()
+            ));
+        }
+        self.parameters
+            .iter()
+            .zip(arguments)
+            .map(|(parameter, argument)| {
+                let value = argument
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|value| (-999..=999).contains(value))
+                    .ok_or_else(|| {
+                        format!(
+                            "Invalid argument for {} of template {}: '{}', expected a value from \
+                             -999 to 999",
+                            parameter, name, argument
+                        )
+                    })?;
+                Ok((parameter.clone(), Number::from(value)))
+            })
+            .collect()
+    }
+}
+
+/// Reads what follows `@instantiate`, like `adder(1) at 1,0; 2,0`, giving the name of the template,
+/// its arguments and every position it's put at, written in `coords`.
+pub(super) fn parse_instantiation(
+    instantiation: &str,
+    coords: Coords,
+) -> Result<(&str, Vec<&str>, Vec<Position>), String> {
+    let (call, positions) = instantiation.split_once(" at ").ok_or_else(|| {
+        format!(
+            "Expected `@instantiate name at x,y; x,y`, got '@instantiate {}'",
+            instantiation
+        )
+    })?;
+    let (name, arguments) = parse_call(call)?;
+    let positions = positions
+        .split(';')
+        .map(|position| parse_position(position, coords))
+        .collect::<Result<_, _>>()?;
+    Ok((name, arguments, positions))
+}
+
+/// Splits something like `adder(1, 2)` into its name and what's between the parentheses, which
+/// can be left out along with them when there's nothing.
+fn parse_call(call: &str) -> Result<(&str, Vec<&str>), String> {
+    let call = call.trim();
+    let (name, list) = match call.split_once('(') {
+        Some((name, list)) => (
+            name.trim_end(),
+            list.strip_suffix(')')
+                .ok_or_else(|| format!("Expected a closing parenthesis in '{}'", call))?,
+        ),
+        None => (call, ""),
+    };
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(format!("Invalid template name: '{}'", name));
+    }
+    let list = match list.trim() {
+        "" => Vec::new(),
+        list => list.split(',').map(str::trim).collect(),
+    };
+    Ok((name, list))
+}
@@ -0,0 +1,241 @@
+use std::{
+    env,
+    io::{stderr, stdout, IsTerminal},
+    ops::{Add, Range},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use ariadne::{Color, Config, Label, Report, ReportKind, Source};
+
+use crate::{diagnostic::ErrorCode, parse_tis::normalize::read_program};
+
+// Whether diagnostics are printed with colors
+static COLOR: AtomicBool = AtomicBool::new(true);
+// Whether errors in a program are printed on a single line each
+static PLAIN_ERRORS: AtomicBool = AtomicBool::new(false);
+// Whether output is plain sentences a screen reader can read out, from --accessible
+static ACCESSIBLE: AtomicBool = AtomicBool::new(false);
+
+/// When to print with colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Only when printing to a terminal and `NO_COLOR` isn't set.
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn parse(choice: &str) -> Result<Self, String> {
+        match choice {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err(format!(
+                "Invalid color choice: '{}', expected auto, always or never",
+                choice
+            )),
+        }
+    }
+}
+
+pub fn set_color(choice: ColorChoice) {
+    let color = match choice {
+        ColorChoice::Auto => {
+            env::var_os("NO_COLOR").is_none_or(|no_color| no_color.is_empty())
+                && stdout().is_terminal()
+                && stderr().is_terminal()
+        }
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+    };
+    COLOR.store(color, Ordering::Relaxed);
+}
+
+pub fn color() -> bool {
+    COLOR.load(Ordering::Relaxed)
+}
+
+pub fn set_plain_errors(plain: bool) {
+    PLAIN_ERRORS.store(plain, Ordering::Relaxed);
+}
+
+/// Has reports written as sentences instead of drawings and tables, and turns off colors.
+pub fn set_accessible(accessible: bool) {
+    ACCESSIBLE.store(accessible, Ordering::Relaxed);
+    if accessible {
+        COLOR.store(false, Ordering::Relaxed);
+    }
+}
+
+pub fn accessible() -> bool {
+    ACCESSIBLE.load(Ordering::Relaxed)
+}
+
+/// Reports an error in the program at `path` on stderr, titled with the line and column of `offset`
+/// and marking each of `labels`. With `--plain-errors` only `file:line:column: error[code]:
+/// message` is printed.
+pub(crate) fn report(
+    path: &str,
+    offset: usize,
+    code: ErrorCode,
+    message: &str,
+    labels: &[(Range<usize>, &str, Color)],
+) {
+    emit(
+        ReportKind::Error,
+        path,
+        offset,
+        &code.code(),
+        message,
+        labels,
+        None,
+    );
+}
+
+/// Reports an error like [`report`], with a note on how it might be fixed.
+pub(crate) fn report_with_help(
+    path: &str,
+    offset: usize,
+    code: ErrorCode,
+    message: &str,
+    labels: &[(Range<usize>, &str, Color)],
+    help: &str,
+) {
+    emit(
+        ReportKind::Error,
+        path,
+        offset,
+        &code.code(),
+        message,
+        labels,
+        Some(help),
+    );
+}
+
+/// Prints any kind of report, see [`report`].
+pub(crate) fn emit(
+    kind: ReportKind,
+    path: &str,
+    offset: usize,
+    code: &str,
+    message: &str,
+    labels: &[(Range<usize>, &str, Color)],
+    help: Option<&str>,
+) {
+    let source = read_program(path).unwrap();
+
+    let (line, column) = line_column(&source, offset);
+    if accessible() {
+        let kind = match kind {
+            ReportKind::Warning => "Warning",
+            _ => "Error",
+        };
+        eprintln!(
+            "{} {} in {} at line {}, column {}: {}.",
+            kind, code, path, line, column, message
+        );
+        for (span, message, _) in labels {
+            let (line, column) = line_column(&source, span.start);
+            eprintln!("Marked at line {}, column {}: {}.", line, column, message);
+        }
+        if let Some(help) = help {
+            eprintln!("Help: {}", help);
+        }
+        return;
+    }
+    if PLAIN_ERRORS.load(Ordering::Relaxed) {
+        let kind = match kind {
+            ReportKind::Warning => "warning",
+            _ => "error",
+        };
+        eprintln!(
+            "{}:{}:{}: {}[{}]: {}",
+            path, line, column, kind, code, message
+        );
+        if let Some(help) = help {
+            eprintln!("{}:{}:{}: help: {}", path, line, column, help);
+        }
+        return;
+    }
+
+    let mut report = Report::build(kind, path.to_owned(), offset)
+        .with_config(Config::default().with_color(color()))
+        .with_code(code)
+        .with_message(format!("{}:{}: {}", line, column, message));
+    for (span, message, color) in labels {
+        report = report.with_label(
+            Label::new((path.to_owned(), span.clone()))
+                .with_message(message)
+                .with_color(*color),
+        );
+    }
+    if let Some(help) = help {
+        report = report.with_help(help);
+    }
+    report
+        .finish()
+        .eprint((path.to_owned(), Source::from(source)))
+        .unwrap();
+}
+
+/// The candidate closest to `word`, if one is close enough that it was likely meant instead.
+pub(crate) fn did_you_mean<'a>(
+    word: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let max_distance = (word.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(word, candidate), candidate))
+        .filter(|&(distance, _)| distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// How many characters have to be inserted, removed, replaced or swapped with the next one to turn
+/// `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b) = (a.chars().collect::<Vec<_>>(), b.chars().collect::<Vec<_>>());
+    // The distance between the first i characters of a and the first j characters of b
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for i in 0..=a.len() {
+        for j in 0..=b.len() {
+            distances[i][j] = match (i, j) {
+                (0, j) => j,
+                (i, 0) => i,
+                (i, j) => {
+                    let mut distance = (distances[i - 1][j - 1] + (a[i - 1] != b[j - 1]) as usize)
+                        .min(distances[i - 1][j] + 1)
+                        .min(distances[i][j - 1] + 1);
+                    if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                        distance = distance.min(distances[i - 2][j - 2] + 1);
+                    }
+                    distance
+                }
+            };
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// The line and column of `offset` in `source`, both counting from 1.
+pub fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let (mut line, mut column) = (1, 1);
+    for (_, c) in source.char_indices().take_while(|&(i, _)| i < offset) {
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    (line, column)
+}
+
+pub(crate) fn offset_range<T>(range: Range<T>, offset: T) -> Range<T>
+where
+    T: Add<Output = T> + Copy,
+{
+    range.start + offset..range.end + offset
+}
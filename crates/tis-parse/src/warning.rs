@@ -0,0 +1,199 @@
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use ariadne::{Color, ReportKind};
+use enum_iterator::{all, Sequence};
+
+use crate::utils::emit;
+
+/// Something in a program that's likely a mistake, but doesn't stop it from running.
+#[derive(Debug, Clone, Copy, Sequence, PartialEq, Eq)]
+pub enum Warning {
+    /// A label nothing jumps to, though a `jro` might still land on it.
+    UnusedLabel,
+    /// An instruction using a port with no node on the other end, which blocks forever.
+    PortUnconnected,
+    /// A number past what a node can hold, which is saturated to -999 or 999.
+    LiteralOutOfRange,
+}
+
+/// What's done when a program has a warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl Warning {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        all::<Warning>()
+            .find(|warning| warning.name() == name)
+            .ok_or_else(|| {
+                format!(
+                    "Unknown warning: '{}', expected {}",
+                    name,
+                    all::<Warning>()
+                        .map(Warning::name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            })
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Warning::UnusedLabel => "unused-label",
+            Warning::PortUnconnected => "port-unconnected",
+            Warning::LiteralOutOfRange => "literal-out-of-range",
+        }
+    }
+
+    // Codes are never reused, see `ErrorCode`
+    fn number(self) -> u32 {
+        match self {
+            Warning::UnusedLabel => 1,
+            Warning::PortUnconnected => 2,
+            Warning::LiteralOutOfRange => 3,
+        }
+    }
+
+    /// The code shown with the warning, like `W0001`.
+    pub(crate) fn code(self) -> String {
+        format!("W{:04}", self.number())
+    }
+
+    pub fn explanation(self) -> &'static str {
+        match self {
+            Warning::UnusedLabel => {
+                "A label is never jumped to. This is allowed unless -W unused-label is given,
+since a jro might still land on it.
+
+Example:
+
+    @0,0
+    start: mov 1 down
+    mov 2 down
+
+The label is only noise here, so it can be removed:
+
+    @0,0
+    mov 1 down
+    mov 2 down"
+            }
+            Warning::PortUnconnected => {
+                "An instruction reads from or writes to a port that has no node on the other
+end, so it waits there forever.
+
+Example:
+
+    @0,0
+    mov up acc
+    mov acc down
+    @number_console_out 0,-1
+
+Put a node on the other end of the port, or wire the port somewhere else:
+
+    @number_console_in 0,1
+    @0,0
+    mov up acc
+    mov acc down
+    @number_console_out 0,-1"
+            }
+            Warning::LiteralOutOfRange => {
+                "A number in the code is outside of -999 to 999, which is all a node can hold.
+It's saturated to -999 or 999, so the program doesn't do what it says. With
+--strict this is an error.
+
+Example:
+
+    @0,0
+    add 1500
+
+This adds 999. Write the number that's actually meant, or split it up:
+
+    @0,0
+    add 999
+    add 501"
+            }
+        }
+    }
+
+    fn default_level(self) -> Level {
+        match self {
+            Warning::UnusedLabel => Level::Allow,
+            Warning::PortUnconnected => Level::Warn,
+            Warning::LiteralOutOfRange => Level::Warn,
+        }
+    }
+}
+
+// Levels given with -W, -A and --deny, which override the default ones
+static LEVELS: Mutex<Vec<(Warning, Level)>> = Mutex::new(Vec::new());
+// Whether every warning that isn't allowed is an error, from --deny warnings
+static DENY_WARNINGS: AtomicBool = AtomicBool::new(false);
+// Warnings already reported, which aren't reported again when a program is parsed again
+static REPORTED: Mutex<Vec<(Warning, String, usize)>> = Mutex::new(Vec::new());
+
+pub fn set_level(warning: Warning, level: Level) {
+    let mut levels = LEVELS.lock().unwrap();
+    levels.retain(|&(other, _)| other != warning);
+    levels.push((warning, level));
+}
+
+pub fn set_deny_warnings(deny: bool) {
+    DENY_WARNINGS.store(deny, Ordering::Relaxed);
+}
+
+fn level(warning: Warning) -> Level {
+    let level = LEVELS
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|&&(other, _)| other == warning)
+        .map_or(warning.default_level(), |&(_, level)| level);
+    match level {
+        Level::Warn if DENY_WARNINGS.load(Ordering::Relaxed) => Level::Deny,
+        level => level,
+    }
+}
+
+/// Reports `warning` in the program at `path` like an error is reported, unless it's allowed.
+/// Gives whether the warning is denied, in which case the program shouldn't run.
+pub(crate) fn warn(
+    warning: Warning,
+    path: &str,
+    offset: usize,
+    message: &str,
+    labels: &[(Range<usize>, &str, Color)],
+) -> bool {
+    let level = level(warning);
+    if level == Level::Allow {
+        return false;
+    }
+
+    let mut reported = REPORTED.lock().unwrap();
+    let key = (warning, path.to_owned(), offset);
+    if !reported.contains(&key) {
+        reported.push(key);
+        let kind = match level {
+            Level::Deny => ReportKind::Error,
+            _ => ReportKind::Warning,
+        };
+        emit(
+            kind,
+            path,
+            offset,
+            &warning.code(),
+            &format!("{} [{}]", message, warning.name()),
+            labels,
+            None,
+        );
+    }
+    level == Level::Deny
+}
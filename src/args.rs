@@ -0,0 +1,582 @@
+use std::{collections::HashMap, env, path::Path, time::Duration};
+
+use crate::{
+    checkpoint::Checkpointer,
+    config::Config,
+    cost::Costs,
+    extension::{Extension, Extensions},
+    message::{set_lang, Lang},
+    node::stack_node::Overflow,
+    number::{parse_numbers, Number},
+    parse_tis::{
+        normalize::{read_stdin, STDIN},
+        Fragment,
+    },
+    position::{parse_position, Coords, Position},
+    stream::StreamTarget,
+    topology::Topology,
+    trace::TraceFilter,
+    utils::{set_accessible, set_color, set_plain_errors, ColorChoice},
+    warning::{set_deny_warnings, set_level, Level, Warning},
+};
+
+pub(crate) struct Args {
+    /// Whether to run the tests written into the program instead of the program itself.
+    pub(crate) test: bool,
+    /// Whether to only check the program for errors instead of running it.
+    pub(crate) check: bool,
+    /// Whether the tests should take whatever the program writes as their new expectations.
+    pub(crate) bless: bool,
+    /// The puzzle the program is a solution to, which the tests are taken from.
+    pub(crate) puzzle: Option<String>,
+    /// The first of the program files, which the tests are in.
+    pub(crate) path: String,
+    /// Every program file loaded into the machine, with where its nodes go.
+    pub(crate) fragments: Vec<Fragment>,
+    pub(crate) expect_output: Option<Vec<Number>>,
+    pub(crate) max_cycles: Option<usize>,
+    /// How long the run, or every test, can go on for in real time, if there's a limit.
+    pub(crate) timeout: Option<Duration>,
+    /// How many values can be written in total before the run ends, if there's a limit.
+    pub(crate) output_limit: Option<usize>,
+    /// How many values every one of these output nodes has to write before the run ends.
+    pub(crate) node_output_limits: HashMap<Position, usize>,
+    /// Values that halt the machine once written, by the output node that has to write them or by
+    /// any of them.
+    pub(crate) halt_on_output: Vec<(Option<Position>, Number)>,
+    /// How many values a second console and file outputs can be written at, if there's a limit.
+    pub(crate) max_output_rate: Option<u32>,
+    /// How many ticks a second the machine runs at, if it's held back, which can be changed with
+    /// keys while it runs.
+    pub(crate) speed: Option<u32>,
+    /// Whether what output nodes write to stdout is held back until the run is over.
+    pub(crate) collect_output: bool,
+    /// What stacks do when they're full, unless their settings say otherwise.
+    pub(crate) overflow: Overflow,
+    /// How positions are written in the program, on the command line and in reports.
+    pub(crate) coords: Coords,
+    /// The extensions programs may use.
+    pub(crate) extensions: Extensions,
+    pub(crate) map_in: HashMap<Position, StreamTarget>,
+    pub(crate) map_out: HashMap<Position, StreamTarget>,
+    pub(crate) metrics_addr: Option<String>,
+    pub(crate) screenshot: Option<String>,
+    pub(crate) topology: Topology,
+    pub(crate) watch: bool,
+    pub(crate) keep_state: bool,
+    pub(crate) checkpointer: Option<Checkpointer>,
+    pub(crate) resume: Option<String>,
+    pub(crate) verify_determinism: Option<usize>,
+    /// How many threads instruction nodes tick on.
+    pub(crate) threads: usize,
+    /// Where the state of the machine is written when SIGUSR1 is received, instead of stderr.
+    pub(crate) dump_file: Option<String>,
+    /// Whether to print what every node did once the run is over.
+    pub(crate) stats: bool,
+    /// Where to write how the run went as JSON once it's over.
+    pub(crate) summary_json: Option<String>,
+    /// Which events to print as they happen, if any are.
+    pub(crate) trace: Option<TraceFilter>,
+    /// Where every value handed from one node to another is written.
+    pub(crate) io_transcript: Option<String>,
+    /// What to add up the cost of the run by, besides cycles.
+    pub(crate) costs: Option<Costs>,
+    /// Whether to explain every instruction in plain words as it runs.
+    pub(crate) explain_run: bool,
+    /// Whether to ask for the result of every instruction before showing it.
+    pub(crate) quiz: bool,
+    /// Where to write where every node, label and instruction is in the program.
+    pub(crate) emit_symbols: Option<String>,
+    /// Whether to rewrite the code of every node into fewer instructions before running it.
+    pub(crate) optimize: bool,
+    /// Whether nodes run loops that don't use any port ahead of the machine.
+    pub(crate) jit: bool,
+    /// Whether chains of nodes only passing values on run as a single node.
+    pub(crate) fuse: bool,
+}
+
+impl Args {
+    pub(crate) fn parse() -> Result<Self, String> {
+        let mut args = env::args().skip(1).peekable();
+        let test = args.next_if_eq("test").is_some();
+        let check = !test && args.next_if_eq("check").is_some();
+
+        // Flags override the defaults from the configuration file
+        let config = Config::load()?;
+
+        let mut paths = Vec::new();
+        // Offsets are only parsed once it's known how positions are written
+        let mut offsets = Vec::new();
+        let mut rotations = Vec::new();
+        let mut mirrors = Vec::new();
+        let mut expect_output = None;
+        let mut max_cycles = config.max_cycles;
+        let mut timeout = None;
+        let mut max_output_rate = None;
+        let mut speed = None;
+        let mut output_limits = Vec::new();
+        let mut halt_on_output = Vec::new();
+        let mut collect_output = false;
+        let mut overflow = config.overflow;
+        let mut color = config.color;
+        let mut plain_errors = false;
+        let mut lang = config.lang;
+        let mut accessible = config.accessible;
+        let mut warning_levels = Vec::new();
+        let mut deny_warnings = false;
+        // Mappings are only parsed once it's known how positions are written
+        let mut map_in = Vec::new();
+        let mut map_out = Vec::new();
+        let mut coords = Coords::Plane;
+        let mut extensions = Vec::new();
+        let mut metrics_addr = None;
+        let mut screenshot = None;
+        let mut topology = Topology::Plane;
+        let mut watch = false;
+        let mut keep_state = false;
+        let mut checkpoint_every = None;
+        let mut checkpoint_file = None;
+        let mut resume = None;
+        let mut verify_determinism = false;
+        let mut verify_every = None;
+        let mut threads = 1;
+        let mut bless = false;
+        let mut puzzle = None;
+        let mut dump_file = None;
+        let mut stats = false;
+        let mut summary_json = None;
+        let mut trace = false;
+        let mut trace_filter = None;
+        let mut trace_only = None;
+        let mut io_transcript = None;
+        let mut costs = None;
+        let mut explain_run = false;
+        let mut quiz = false;
+        let mut emit_symbols = None;
+        let mut optimize = false;
+        let mut jit = false;
+        let mut fuse = false;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--expect-output" => {
+                    let values = value_of(&mut args, &arg)?;
+                    expect_output = Some(parse_numbers(&values)?);
+                }
+                "--max-cycles" => {
+                    let cycles = value_of(&mut args, &arg)?;
+                    max_cycles = Some(
+                        cycles
+                            .parse()
+                            .map_err(|_| format!("Invalid cycle count: '{}'", cycles))?,
+                    );
+                }
+                "--timeout" => timeout = Some(parse_timeout(&value_of(&mut args, &arg)?)?),
+                "--max-output-rate" => {
+                    let rate = value_of(&mut args, &arg)?;
+                    max_output_rate = Some(
+                        rate.parse()
+                            .ok()
+                            .filter(|&rate| rate > 0)
+                            .ok_or_else(|| format!("Invalid output rate: '{}'", rate))?,
+                    );
+                }
+                "--speed" => {
+                    let ticks = value_of(&mut args, &arg)?;
+                    speed = Some(
+                        ticks
+                            .parse()
+                            .ok()
+                            .filter(|&ticks| ticks > 0)
+                            .ok_or_else(|| format!("Invalid speed: '{}'", ticks))?,
+                    );
+                }
+                "--collect-output" => collect_output = true,
+                "--output-limit" => output_limits.push(value_of(&mut args, &arg)?),
+                "--halt-on-output" => halt_on_output.push(value_of(&mut args, &arg)?),
+                "--overflow" => overflow = Some(Overflow::parse(&value_of(&mut args, &arg)?)?),
+                "--color" => color = Some(ColorChoice::parse(&value_of(&mut args, &arg)?)?),
+                "--plain-errors" => plain_errors = true,
+                "--accessible" => accessible = true,
+                "--lang" => lang = Some(Lang::parse(&value_of(&mut args, &arg)?)?),
+                "-W" => {
+                    warning_levels.push((Warning::parse(&value_of(&mut args, &arg)?)?, Level::Warn))
+                }
+                "-A" => warning_levels
+                    .push((Warning::parse(&value_of(&mut args, &arg)?)?, Level::Allow)),
+                // Only what changes what a program means is made an error
+                "--strict" => warning_levels.push((Warning::LiteralOutOfRange, Level::Deny)),
+                "--deny" => match value_of(&mut args, &arg)?.as_str() {
+                    "warnings" => deny_warnings = true,
+                    warning => warning_levels.push((Warning::parse(warning)?, Level::Deny)),
+                },
+                "--map-in" => map_in.push(value_of(&mut args, &arg)?),
+                "--map-out" => map_out.push(value_of(&mut args, &arg)?),
+                "--coords" => coords = Coords::parse(&value_of(&mut args, &arg)?)?,
+                "--extension" | "--extensions" => {
+                    for name in value_of(&mut args, &arg)?.split(',') {
+                        extensions.push(Extension::parse(name.trim())?);
+                    }
+                }
+                "--metrics-addr" => metrics_addr = Some(value_of(&mut args, &arg)?),
+                "--screenshot" => screenshot = Some(value_of(&mut args, &arg)?),
+                "--watch" => watch = true,
+                "--keep-state" => keep_state = true,
+                "--checkpoint-every" => {
+                    let cycles = value_of(&mut args, &arg)?;
+                    checkpoint_every = Some(
+                        cycles
+                            .parse()
+                            .ok()
+                            .filter(|&cycles| cycles > 0)
+                            .ok_or_else(|| format!("Invalid cycle count: '{}'", cycles))?,
+                    );
+                }
+                "--checkpoint-file" => checkpoint_file = Some(value_of(&mut args, &arg)?),
+                "--verify-determinism" => verify_determinism = true,
+                "--verify-every" => {
+                    let cycles = value_of(&mut args, &arg)?;
+                    verify_every = Some(
+                        cycles
+                            .parse()
+                            .ok()
+                            .filter(|&cycles| cycles > 0)
+                            .ok_or_else(|| format!("Invalid cycle count: '{}'", cycles))?,
+                    );
+                }
+                "--threads" => {
+                    let count = value_of(&mut args, &arg)?;
+                    threads = count
+                        .parse()
+                        .ok()
+                        .filter(|&threads| threads > 0)
+                        .ok_or_else(|| format!("Invalid thread count: '{}'", count))?;
+                }
+                "--bless" => bless = true,
+                "--puzzle" => puzzle = Some(value_of(&mut args, &arg)?),
+                "--stats" => stats = true,
+                "--summary-json" => summary_json = Some(value_of(&mut args, &arg)?),
+                "--trace" => trace = true,
+                "--trace-filter" => trace_filter = Some(value_of(&mut args, &arg)?),
+                "--trace-only" => trace_only = Some(value_of(&mut args, &arg)?),
+                "--io-transcript" => io_transcript = Some(value_of(&mut args, &arg)?),
+                "--explain-run" => explain_run = true,
+                "--quiz" => quiz = true,
+                "--emit-symbols" => emit_symbols = Some(value_of(&mut args, &arg)?),
+                "--optimize" => optimize = true,
+                "--jit" => jit = true,
+                "--fuse" => fuse = true,
+                "--costs" => costs = Some(Costs::load(&value_of(&mut args, &arg)?)?),
+                "--dump-file" => dump_file = Some(value_of(&mut args, &arg)?),
+                "--resume" => resume = Some(value_of(&mut args, &arg)?),
+                "--wrap" => topology = Topology::torus(&value_of(&mut args, &arg)?)?,
+                "--offset" => offsets.push(value_of(&mut args, &arg)?),
+                "--rotate" => rotations.push(value_of(&mut args, &arg)?),
+                "--mirror-x" => mirrors.push(value_of(&mut args, &arg)?),
+                _ if arg.starts_with("--") => return Err(format!("Unknown flag: {}", arg)),
+                _ => paths.push(arg),
+            }
+        }
+
+        let checkpointer = match (checkpoint_every, checkpoint_file) {
+            (Some(every), Some(path)) => Some(Checkpointer::new(every, path)),
+            (None, None) => None,
+            _ => {
+                return Err(
+                    "--checkpoint-every and --checkpoint-file must be used together".to_owned(),
+                )
+            }
+        };
+
+        set_color(color.unwrap_or(ColorChoice::Auto));
+        set_accessible(accessible);
+        set_plain_errors(plain_errors);
+        set_lang(lang.unwrap_or(Lang::En));
+        for (warning, level) in warning_levels {
+            set_level(warning, level);
+        }
+        set_deny_warnings(deny_warnings);
+        let map_in = map_in
+            .iter()
+            .map(|mapping| parse_mapping(mapping, coords))
+            .collect::<Result<_, _>>()?;
+        let map_out = map_out
+            .iter()
+            .map(|mapping| parse_mapping(mapping, coords))
+            .collect::<Result<_, _>>()?;
+        let halt_on_output = halt_on_output
+            .iter()
+            .map(|sentinel| parse_sentinel(sentinel, coords))
+            .collect::<Result<_, _>>()?;
+        let mut output_limit = None;
+        let mut node_output_limits = HashMap::new();
+        for limit in &output_limits {
+            match parse_output_limit(limit, coords)? {
+                (Some(position), limit) => {
+                    node_output_limits.insert(position, limit);
+                }
+                (None, limit) => output_limit = Some(limit),
+            }
+        }
+
+        if bless && !test {
+            return Err("--bless can only be used with test".to_owned());
+        }
+        if puzzle.is_some() && !test {
+            return Err("--puzzle can only be used with test".to_owned());
+        }
+        if bless && puzzle.is_some() {
+            return Err("--bless can't be used with --puzzle".to_owned());
+        }
+
+        if (trace_filter.is_some() || trace_only.is_some()) && !trace {
+            return Err("--trace-filter and --trace-only need --trace".to_owned());
+        }
+        let trace = trace
+            .then(|| TraceFilter::parse(trace_filter.as_deref(), trace_only.as_deref(), coords))
+            .transpose()?;
+
+        // Both count instructions as they're written
+        if optimize && (watch || emit_symbols.is_some()) {
+            return Err("--optimize can't be used with --watch or --emit-symbols".to_owned());
+        }
+
+        // Nodes running ahead only have their registers right once they've caught up
+        if jit && (watch || explain_run || quiz || checkpointer.is_some() || resume.is_some()) {
+            return Err(
+                "--jit can't be used with --watch, --explain-run, --quiz or checkpoints".to_owned(),
+            );
+        }
+
+        // The nodes of a fused chain are gone, along with their registers and code
+        if fuse && (watch || explain_run || quiz || checkpointer.is_some() || resume.is_some()) {
+            return Err(
+                "--fuse can't be used with --watch, --explain-run, --quiz or checkpoints"
+                    .to_owned(),
+            );
+        }
+
+        // Both hold on to what's written to stdout, each until a different time
+        if collect_output && quiz {
+            return Err("--collect-output can't be used with --quiz".to_owned());
+        }
+
+        // Both read keys from the terminal
+        if speed.is_some() && quiz {
+            return Err("--speed can't be used with --quiz".to_owned());
+        }
+
+        if verify_every.is_some() && !verify_determinism {
+            return Err("--verify-every needs --verify-determinism".to_owned());
+        }
+
+        let mut fragments = paths.into_iter().map(Fragment::new).collect::<Vec<_>>();
+        for offset in &offsets {
+            let (name, offset) = parse_offset(offset, coords)?;
+            find_fragment(&mut fragments, name, "offset")?.offset = offset;
+        }
+        for rotation in &rotations {
+            let (name, turns) = parse_rotation(rotation)?;
+            find_fragment(&mut fragments, name, "rotate")?.turns = turns;
+        }
+        for name in &mirrors {
+            find_fragment(&mut fragments, name, "mirror")?.mirror_x = true;
+        }
+        if fragments.len() > 1 && (watch || emit_symbols.is_some()) {
+            return Err("--watch and --emit-symbols need a single program file".to_owned());
+        }
+        // Read right away, so nothing else gets to read from stdin first
+        match fragments
+            .iter_mut()
+            .filter(|fragment| fragment.path == "-")
+            .collect::<Vec<_>>()[..]
+        {
+            [] => {}
+            [ref mut fragment] => {
+                if watch {
+                    return Err("--watch can't be used with a program from stdin".to_owned());
+                }
+                if bless {
+                    return Err("--bless can't be used with a program from stdin".to_owned());
+                }
+                read_stdin().map_err(|e| format!("Couldn't read the program from stdin: {}", e))?;
+                fragment.path = STDIN.to_owned();
+            }
+            _ => return Err("Only one program can be read from stdin".to_owned()),
+        }
+        let path = fragments
+            .first()
+            .ok_or("No path provided".to_owned())?
+            .path
+            .clone();
+
+        Ok(Self {
+            test,
+            check,
+            bless,
+            puzzle,
+            path,
+            fragments,
+            expect_output,
+            max_cycles,
+            timeout,
+            output_limit,
+            node_output_limits,
+            halt_on_output,
+            max_output_rate,
+            speed,
+            collect_output,
+            overflow: overflow.unwrap_or(Overflow::Block),
+            coords,
+            extensions: extensions.into_iter().collect(),
+            map_in,
+            map_out,
+            metrics_addr,
+            screenshot,
+            topology,
+            watch,
+            keep_state,
+            checkpointer,
+            resume,
+            verify_determinism: verify_determinism.then_some(verify_every.unwrap_or(1)),
+            threads,
+            dump_file,
+            stats,
+            summary_json,
+            trace,
+            io_transcript,
+            costs,
+            explain_run,
+            quiz,
+            emit_symbols,
+            optimize,
+            jit,
+            fuse,
+        })
+    }
+}
+
+fn value_of(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("Expected a value after {}", flag))
+}
+
+/// The program file called `name`, which is its path or its name without `.tis`, for `what` to be
+/// done to it.
+fn find_fragment<'a>(
+    fragments: &'a mut [Fragment],
+    name: &str,
+    what: &str,
+) -> Result<&'a mut Fragment, String> {
+    fragments
+        .iter_mut()
+        .find(|fragment| {
+            fragment.path == name || Path::new(&fragment.path).file_stem() == Some(name.as_ref())
+        })
+        .ok_or_else(|| format!("There's no program file called {} to {}", name, what))
+}
+
+/// Parses how far the nodes of a program file are moved, written as `name=x,y` in `coords`.
+fn parse_offset(offset: &str, coords: Coords) -> Result<(&str, Position), String> {
+    let (name, by) = offset
+        .split_once('=')
+        .ok_or_else(|| format!("Expected `name=x,y`, got '{}'", offset))?;
+    let invalid = || format!("Invalid offset: '{}'", by);
+    let coordinates = by
+        .split(',')
+        .map(|coordinate| coordinate.trim().parse().map_err(|_| invalid()))
+        .collect::<Result<Vec<i32>, _>>()?;
+    // Positions written in the game's coordinates grow downwards
+    let y = |y: i32| match coords {
+        Coords::Plane => y,
+        Coords::Game => -y,
+    };
+    match coordinates[..] {
+        [x, dy] => Ok((name, Position::new(x, y(dy)))),
+        [x, dy, z] => Ok((name, Position::new(x, y(dy)).with_z(z))),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parses how far a program file is turned clockwise, written as `name=degrees`, giving the number
+/// of quarter turns.
+fn parse_rotation(rotation: &str) -> Result<(&str, u8), String> {
+    let (name, degrees) = rotation
+        .split_once('=')
+        .ok_or_else(|| format!("Expected `name=degrees`, got '{}'", rotation))?;
+    let degrees = degrees
+        .trim()
+        .parse::<i32>()
+        .ok()
+        .filter(|degrees| degrees % 90 == 0)
+        .ok_or_else(|| {
+            format!(
+                "Invalid rotation: '{}', expected a multiple of 90 degrees",
+                degrees
+            )
+        })?;
+    Ok((name, (degrees / 90).rem_euclid(4) as u8))
+}
+
+/// Parses how long a run can take, written as a number followed by `ms`, `s`, `m` or `h`, or a
+/// number of seconds alone, like `30s` or `1.5`.
+fn parse_timeout(timeout: &str) -> Result<Duration, String> {
+    let (number, unit) = match timeout.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(i) => timeout.split_at(i),
+        None => (timeout, "s"),
+    };
+    let seconds = match unit {
+        "ms" => 0.001,
+        "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        _ => return Err(format!("Invalid timeout: '{}'", timeout)),
+    };
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|&number| number > 0.0)
+        .and_then(|number| Duration::try_from_secs_f64(number * seconds).ok())
+        .ok_or_else(|| format!("Invalid timeout: '{}'", timeout))
+}
+
+/// Parses how many values can be written before the run ends, written as `count` for every output
+/// node together or `x,y=count` for a single one.
+fn parse_output_limit(limit: &str, coords: Coords) -> Result<(Option<Position>, usize), String> {
+    let (position, count) = match limit.split_once('=') {
+        Some((position, count)) => (Some(parse_position(position, coords)?), count),
+        None => (None, limit),
+    };
+    let count = count
+        .trim()
+        .parse()
+        .ok()
+        .filter(|&count| count > 0)
+        .ok_or_else(|| format!("Invalid output limit: '{}'", count))?;
+    Ok((position, count))
+}
+
+/// Parses a value that halts the machine once written, written as `value` for any output node or
+/// `x,y=value` for a single one.
+fn parse_sentinel(sentinel: &str, coords: Coords) -> Result<(Option<Position>, Number), String> {
+    let (position, value) = match sentinel.split_once('=') {
+        Some((position, value)) => (Some(parse_position(position, coords)?), value),
+        None => (None, sentinel),
+    };
+    let value = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid sentinel: '{}'", value))?;
+    Ok((position, value))
+}
+
+/// Parses a node stream mapping written as `x,y=target`.
+fn parse_mapping(mapping: &str, coords: Coords) -> Result<(Position, StreamTarget), String> {
+    let (position, target) = mapping
+        .split_once('=')
+        .ok_or_else(|| format!("Expected `x,y=target`, got '{}'", mapping))?;
+    Ok((
+        parse_position(position, coords)?,
+        StreamTarget::parse(target)?,
+    ))
+}
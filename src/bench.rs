@@ -0,0 +1,164 @@
+use std::{env, fs, time::Instant};
+
+use tis_parse::Load;
+
+use crate::tis::Tis;
+
+/// How many cycles each workload runs for with `tis-cli bench --internal`.
+const DEFAULT_CYCLES: usize = 100_000;
+
+/// A generated program that keeps the machine busy without any input or output, for measuring how
+/// fast the machine runs.
+pub struct Workload {
+    pub name: &'static str,
+    /// The sizes it's measured at, which mean something different for every workload.
+    pub sizes: &'static [usize],
+    source: fn(usize) -> String,
+}
+
+pub const WORKLOADS: [Workload; 4] = [
+    Workload {
+        name: "mov-chain",
+        sizes: &[16, 256],
+        source: mov_chain,
+    },
+    Workload {
+        name: "ping-pong",
+        sizes: &[8, 128],
+        source: ping_pong,
+    },
+    Workload {
+        name: "any-traffic",
+        sizes: &[4, 16],
+        source: any_traffic,
+    },
+    Workload {
+        name: "countdown",
+        sizes: &[8, 128],
+        source: countdown,
+    },
+];
+
+impl Workload {
+    /// The program at `size`.
+    pub fn source(&self, size: usize) -> String {
+        (self.source)(size)
+    }
+
+    /// A machine running the program at `size`.
+    pub fn load(&self, size: usize) -> Tis {
+        // Programs are only ever loaded from files
+        let path = env::temp_dir().join(format!("tis-bench-{}-{}.tis", self.name, size));
+        let path = path.to_str().unwrap();
+        fs::write(path, self.source(size)).unwrap();
+        let tis = Tis::load(path);
+        let _ = fs::remove_file(path);
+        tis.unwrap_or_else(|e| panic!("The {} workload doesn't load: {:?}", self.name, e))
+    }
+}
+
+/// A row of `length` nodes, each passing on what the one before it wrote.
+fn mov_chain(length: usize) -> String {
+    let mut source = "@0,0\nadd 1\nmov acc right\n".to_owned();
+    for x in 1..length - 1 {
+        source += &format!("@{},0\nmov left right\n", x);
+    }
+    source += &format!("@{},0\nmov left nil\n", length - 1);
+    source
+}
+
+/// `pairs` pairs of nodes, each sending a counter back and forth.
+fn ping_pong(pairs: usize) -> String {
+    let mut source = String::new();
+    for y in 0..pairs {
+        source += &format!("@0,{}\nmov acc right\nmov right acc\nadd 1\n", y);
+        source += &format!("@1,{}\nmov left acc\nmov acc left\n", y);
+    }
+    source
+}
+
+/// A `side` by `side` square of nodes all reading and writing `any`, half of them writing first
+/// and the other half reading first.
+fn any_traffic(side: usize) -> String {
+    let mut source = String::new();
+    for y in 0..side {
+        for x in 0..side {
+            source += &format!("@{},{}\n", x, y);
+            source += if (x + y) % 2 == 0 {
+                "mov acc any\nmov any acc\nadd 1\n"
+            } else {
+                "mov any acc\nmov acc any\n"
+            };
+        }
+    }
+    source
+}
+
+/// `pairs` pairs of nodes, each counting down from 100 before telling the other one.
+fn countdown(pairs: usize) -> String {
+    let mut source = String::new();
+    for y in 0..pairs {
+        source += &format!(
+            "@0,{}\nmov 100 acc\nloop: sub 1\njgz loop\nmov acc right\n",
+            y
+        );
+        source += &format!("@1,{}\nmov left nil\n", y);
+    }
+    source
+}
+
+/// Runs every workload for a while and prints how many cycles a second it ran at, like
+/// `tis-cli bench --internal`, with nodes running loops ahead of the machine with `--jit` and
+/// chains of nodes passing values on fused with `--fuse`.
+pub(crate) fn bench(args: &[String]) -> Result<(), String> {
+    let mut internal = false;
+    let mut jit = false;
+    let mut fuse = false;
+    let mut cycles = DEFAULT_CYCLES;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--internal" => internal = true,
+            "--jit" => jit = true,
+            "--fuse" => fuse = true,
+            "--cycles" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("Expected a value after {}", arg))?;
+                cycles = value
+                    .parse()
+                    .ok()
+                    .filter(|&cycles| cycles > 0)
+                    .ok_or_else(|| format!("Invalid cycle count: '{}'", value))?;
+            }
+            _ => return Err(format!("Unexpected argument: {}", arg)),
+        }
+    }
+    // Only the built-in workloads can be measured so far
+    if !internal {
+        return Err("Expected --internal, to measure the built-in workloads".to_owned());
+    }
+
+    for workload in &WORKLOADS {
+        for &size in workload.sizes {
+            let mut tis = workload.load(size);
+            if jit {
+                tis.run_ahead();
+            }
+            if fuse {
+                tis.fuse();
+            }
+            let start = Instant::now();
+            for _ in 0..cycles {
+                tis.step();
+            }
+            let elapsed = start.elapsed();
+            println!(
+                "{:<16} {:>12.0} cycles/s",
+                format!("{}/{}", workload.name, size),
+                cycles as f64 / elapsed.as_secs_f64()
+            );
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,438 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    io::{self, IsTerminal, Write},
+    time::Instant,
+};
+
+use crate::{
+    args::Args,
+    bench::bench,
+    checkpoint::Checkpoint,
+    cost,
+    diagnostic::explain,
+    doc::doc,
+    expect::diff_outputs,
+    export::export,
+    heatmap,
+    layout::layout,
+    metrics::Metrics,
+    narrate::Narrator,
+    optimize::Rewrite,
+    parse_tis::{parse_fragments, reload},
+    position::Position,
+    puzzle::init_puzzle,
+    quiz::Quiz,
+    scaffold::new_program,
+    signal,
+    speed::Speed,
+    stats,
+    stream::Streams,
+    summary::{write_summary, Termination},
+    symbols::emit_symbols,
+    test_runner::run_tests,
+    tis::{TickStatus, Tis},
+    trace::trace,
+    transcript::record_transfers,
+    watch::Watcher,
+};
+
+/// What a run that went on for longer than `--timeout` allows exits with, the same as `timeout`.
+const TIMED_OUT_EXIT_CODE: i32 = 124;
+
+/// Runs the program given on the command line, giving the code to exit with. Errors that were
+/// already reported are `None`.
+pub fn run_code() -> Result<i32, Option<String>> {
+    // Explaining a code doesn't need a program, so none of the other arguments apply
+    if env::args().nth(1).as_deref() == Some("explain") {
+        return explain(env::args().nth(2)).map(|()| 0).map_err(Some);
+    }
+    if env::args().nth(1).as_deref() == Some("bench") {
+        return bench(&env::args().skip(2).collect::<Vec<_>>())
+            .map(|()| 0)
+            .map_err(Some);
+    }
+    if env::args().nth(1).as_deref() == Some("export") {
+        return export(&env::args().skip(2).collect::<Vec<_>>()).map(|()| 0);
+    }
+    if env::args().nth(1).as_deref() == Some("stats") {
+        return heatmap::stats(&env::args().skip(2).collect::<Vec<_>>()).map(|()| 0);
+    }
+    if env::args().nth(1).as_deref() == Some("doc") {
+        return doc(&env::args().skip(2).collect::<Vec<_>>()).map(|()| 0);
+    }
+    if env::args().nth(1).as_deref() == Some("layout") {
+        return layout(&env::args().skip(2).collect::<Vec<_>>()).map(|()| 0);
+    }
+    if env::args().nth(1).as_deref() == Some("new") {
+        return new_program(&env::args().skip(2).collect::<Vec<_>>())
+            .map(|()| 0)
+            .map_err(Some);
+    }
+    if env::args().nth(1).as_deref() == Some("init-puzzle") {
+        return init_puzzle(&env::args().skip(2).collect::<Vec<_>>())
+            .map(|()| 0)
+            .map_err(Some);
+    }
+
+    let args = Args::parse()?;
+    if args.test {
+        return run_tests(args).map(|()| 0);
+    }
+    if args.check {
+        // The program is only loaded, the same as for a test
+        let mut tis = Tis::new()
+            .with_topology(args.topology)
+            .with_overflow(args.overflow)
+            .with_coords(args.coords)
+            .with_extensions(args.extensions);
+        parse_fragments(
+            &mut tis,
+            &args.fragments,
+            &mut Streams::test(&[]).with_coords(args.coords),
+        )?;
+        if args.optimize {
+            let rewrites = tis.optimize();
+            report_rewrites(&tis, &rewrites);
+        }
+        if args.fuse {
+            let chains = tis.fuse();
+            report_chains(&tis, &chains);
+        }
+        return Ok(0);
+    }
+
+    let mut tis = Tis::new()
+        .with_topology(args.topology)
+        .with_overflow(args.overflow)
+        .with_coords(args.coords)
+        .with_extensions(args.extensions)
+        .with_threads(args.threads);
+    if let Some(expected) = &args.expect_output {
+        tis = tis.with_expected_outputs(expected.len());
+    }
+    for &(position, value) in &args.halt_on_output {
+        tis = tis.with_sentinel(position, value);
+    }
+    let mut quiz = args.quiz.then(Quiz::open).transpose()?;
+    let mut streams = Streams::new(args.map_in, args.map_out)
+        .with_coords(args.coords)
+        .with_max_output_rate(args.max_output_rate)
+        .with_collected_output(args.collect_output)
+        .with_console_output(quiz.as_ref().map(Quiz::output))
+        .with_screenshot(args.screenshot)
+        .with_recording(args.verify_determinism.is_some());
+    let mut watcher = args.watch.then(|| Watcher::new(args.path.clone()));
+    parse_fragments(&mut tis, &args.fragments, &mut streams)?;
+    streams.check_all_used()?;
+    if args.optimize {
+        let rewrites = tis.optimize();
+        report_rewrites(&tis, &rewrites);
+    }
+    if args.jit {
+        tis.run_ahead();
+    }
+    if args.fuse {
+        let chains = tis.fuse();
+        report_chains(&tis, &chains);
+    }
+    if let Some(path) = &args.emit_symbols {
+        emit_symbols(&args.path, path, args.coords, args.extensions)?;
+    }
+    if let Some(filter) = args.trace {
+        trace(&mut tis, filter);
+    }
+    if let Some(path) = &args.io_transcript {
+        record_transfers(&mut tis, path)?;
+    }
+    let costs = args.costs.map(|costs| costs.track(&mut tis));
+    let narrator = args.explain_run.then(|| Narrator::new(&mut tis));
+    if let Some(quiz) = &mut quiz {
+        quiz.observe(&mut tis);
+    }
+
+    // A second machine running the same program on the same input, which has to end up in the
+    // same state as the first one every step of the way. It only ever runs on one thread, so it
+    // also checks that --threads changes nothing
+    let mut twin = match args.verify_determinism {
+        Some(_) => {
+            let mut twin = Tis::new()
+                .with_topology(args.topology)
+                .with_overflow(args.overflow)
+                .with_coords(args.coords)
+                .with_extensions(args.extensions);
+            parse_fragments(&mut twin, &args.fragments, &mut streams.replay())?;
+            if args.optimize {
+                twin.optimize();
+            }
+            if args.jit {
+                twin.run_ahead();
+            }
+            if args.fuse {
+                twin.fuse();
+            }
+            Some(twin)
+        }
+        None => None,
+    };
+
+    if let Some(path) = &args.resume {
+        let checkpoint = Checkpoint::read(path)?;
+        for tis in Some(&mut tis).into_iter().chain(&mut twin) {
+            tis.restore(&checkpoint)
+                .map_err(|e| format!("Couldn't resume from {}: {}", path, e))?;
+        }
+    }
+    let mut verified_cycle = tis.cycle();
+
+    let mut metrics = args
+        .metrics_addr
+        .as_deref()
+        .map(|address| Metrics::serve(address, &tis))
+        .transpose()?;
+
+    for &position in args.node_output_limits.keys() {
+        if tis.node_at(position).is_none() {
+            return Err(Some(format!(
+                "No output node at {} to limit",
+                position.shown(args.coords)
+            )));
+        }
+    }
+    for position in args
+        .halt_on_output
+        .iter()
+        .filter_map(|&(position, _)| position)
+    {
+        if tis.node_at(position).is_none() {
+            return Err(Some(format!(
+                "No output node at {} to halt on",
+                position.shown(args.coords)
+            )));
+        }
+    }
+    // How many values every output node has written, for --output-limit
+    let mut written = HashMap::<Position, usize>::new();
+
+    let mut outputs = Vec::new();
+    let mut exit_code = 0;
+    let mut termination = Termination::Blocked;
+    let deadline = args.timeout.map(|timeout| Instant::now() + timeout);
+    signal::install();
+    let mut speed = args
+        .speed
+        .map(|speed| Speed::new(speed, !(streams.reads_stdin() && io::stdin().is_terminal())));
+    loop {
+        if let Some(speed) = &mut speed {
+            speed.pace();
+        }
+        if let Some(quiz) = &mut quiz {
+            quiz.prepare(&tis);
+        }
+        let status = tis.tick();
+        if let Some(quiz) = &mut quiz {
+            quiz.ask(&tis);
+        }
+        if let Some(narrator) = &narrator {
+            narrator.narrate(&tis);
+        }
+        if let (Some(twin), Some(every)) = (&mut twin, args.verify_determinism) {
+            twin.tick();
+            if tis.cycle().is_multiple_of(every) {
+                if tis.state_hash() != twin.state_hash() {
+                    return Err(Some(if every == 1 {
+                        format!("The two runs first differed at cycle {}", tis.cycle())
+                    } else {
+                        format!(
+                            "The two runs first differed between cycles {} and {}",
+                            verified_cycle + 1,
+                            tis.cycle()
+                        )
+                    }));
+                }
+                verified_cycle = tis.cycle();
+            }
+        }
+        if let Some(metrics) = &mut metrics {
+            metrics.refresh(&tis);
+        }
+        if let Some(checkpointer) = &args.checkpointer {
+            if checkpointer.is_due(tis.cycle()) {
+                // Losing one checkpoint is better than losing the whole run
+                if let Err(e) = tis.checkpoint().write(checkpointer.path()) {
+                    eprintln!("{}", e);
+                }
+            }
+        }
+        if let Some(watcher) = &mut watcher {
+            if watcher.changed(tis.cycle()) {
+                // A broken edit shouldn't stop the program, the old code just keeps running
+                match reload(&mut tis, &args.fragments[0], args.keep_state) {
+                    Ok(()) => eprintln!("Reloaded {}", watcher.path()),
+                    Err(Some(e)) => eprintln!("{}", e),
+                    Err(None) => {}
+                }
+                if let Some(twin) = &mut twin {
+                    // Any error was already reported for the first machine
+                    let _ = reload(twin, &args.fragments[0], args.keep_state);
+                }
+            }
+        }
+
+        outputs.extend_from_slice(tis.outputs());
+        for output in tis.outputs() {
+            *written.entry(output.position).or_default() += 1;
+        }
+        if signal::dump_requested() {
+            let report = signal::report(&tis, "State");
+            // A failed dump shouldn't stop the run, there's always the next one
+            match &args.dump_file {
+                Some(path) => {
+                    if let Err(e) = fs::write(path, report) {
+                        eprintln!("Couldn't write the state to {}: {}", path, e);
+                    }
+                }
+                None => eprint!("{}", report),
+            }
+        }
+        signal::wait_while_paused(&tis);
+        if signal::interrupted() {
+            termination = Termination::Interrupted;
+            break;
+        }
+        // Nothing changes while the machine is stuck, so it's shown as it is before waiting
+        if let (TickStatus::AllBlocked, Some(metrics)) = (&status, &mut metrics) {
+            metrics.publish(&tis);
+        }
+        match status {
+            TickStatus::Progress => {}
+            // A watched file can still change and get the machine going again
+            TickStatus::AllBlocked if watcher.is_some() => {
+                if let Some(watcher) = &mut watcher {
+                    watcher.wait();
+                }
+            }
+            TickStatus::AllBlocked => {
+                if !tis.waits_for_input() {
+                    break;
+                }
+                // A run limited to so many cycles or so much time has to keep counting them, so only
+                // other runs sit idle until something comes in
+                if args.max_cycles.is_none() && deadline.is_none() {
+                    tis.wait_for_input();
+                }
+            }
+            TickStatus::Halted(code) => {
+                exit_code = code;
+                termination = Termination::Halted;
+                break;
+            }
+            TickStatus::Trapped(message) => {
+                eprintln!("{}", message);
+                exit_code = 1;
+                termination = Termination::Trapped;
+                break;
+            }
+            TickStatus::OutputComplete => {
+                termination = Termination::OutputComplete;
+                break;
+            }
+        }
+        // A limit for every node together is reached by all of them, and the limits of single
+        // nodes once every one of them is
+        let limited = args
+            .output_limit
+            .is_some_and(|limit| outputs.len() >= limit)
+            || (!args.node_output_limits.is_empty()
+                && args.node_output_limits.iter().all(|(position, &limit)| {
+                    written.get(position).is_some_and(|&count| count >= limit)
+                }));
+        if limited {
+            termination = Termination::OutputLimit;
+            break;
+        }
+        if args.max_cycles.is_some_and(|max| tis.cycle() >= max) {
+            termination = Termination::MaxCycles;
+            break;
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            termination = Termination::TimedOut;
+            break;
+        }
+    }
+    tis.finish()?;
+    if args.collect_output {
+        write_collected(&tis, &streams)?;
+    }
+    if let Some(path) = &args.summary_json {
+        write_summary(path, &tis, termination, exit_code, &outputs)?;
+    }
+    if args.stats {
+        eprint!("{}", stats::report(&tis));
+    }
+    if let Some(costs) = &costs {
+        eprint!("{}", cost::report(&tis, costs));
+    }
+    if let Some(quiz) = &quiz {
+        eprintln!("{}", quiz.score());
+    }
+
+    // What was written so far can't be compared with what's expected, so the state is shown instead
+    if termination == Termination::Interrupted {
+        eprint!("{}", signal::report(&tis, "Interrupted"));
+        return Ok(signal::INTERRUPTED_EXIT_CODE);
+    }
+    // A run that timed out is cut short the same way, but only shows what was asked for
+    if termination == Termination::TimedOut {
+        eprintln!("Timed out after {} cycles", tis.cycle());
+        return Ok(TIMED_OUT_EXIT_CODE);
+    }
+
+    if let Some(expected) = &args.expect_output {
+        if let Some(diff) = diff_outputs(&tis, expected, &outputs) {
+            return Err(Some(diff));
+        }
+    }
+
+    Ok(exit_code)
+}
+
+/// Points out everything `--optimize` changed in the program of `tis`.
+fn report_rewrites(tis: &Tis, rewrites: &[(Position, Rewrite)]) {
+    for (position, rewrite) in rewrites {
+        eprintln!("Optimized {}: {}", tis.label(*position), rewrite);
+    }
+}
+
+/// Writes what every output node wrote with `--collect-output` to stdout, a node at a time, with
+/// which node it was on stderr.
+fn write_collected(tis: &Tis, streams: &Streams) -> Result<(), String> {
+    let mut stdout = io::stdout();
+    for (position, output) in streams.collected() {
+        if output.is_empty() {
+            continue;
+        }
+        eprintln!("Output of {}:", tis.label(position));
+        stdout
+            .write_all(&output)
+            .and_then(|()| stdout.flush())
+            .map_err(|e| format!("Couldn't write output: {}", e))?;
+        // The next node's output starts on a line of its own
+        if !output.ends_with(b"\n") {
+            eprintln!();
+        }
+    }
+    Ok(())
+}
+
+/// Points out every chain of nodes `--fuse` runs as a single node in `tis`.
+fn report_chains(tis: &Tis, chains: &[Vec<Position>]) {
+    for chain in chains {
+        eprintln!(
+            "Fused {} nodes from {} to {}",
+            chain.len(),
+            tis.label(chain[0]),
+            tis.label(chain[chain.len() - 1])
+        );
+    }
+}
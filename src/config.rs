@@ -0,0 +1,97 @@
+use std::{env, fs, io, path::PathBuf};
+
+use crate::{message::Lang, node::stack_node::Overflow, utils::ColorChoice};
+
+/// Defaults read from `~/.config/tis-cli/config.toml`, which flags on the command line override.
+///
+/// Only the simplest part of TOML is understood: one `key = value` per line, where values are
+/// booleans, whole numbers or double quoted strings, along with `#` comments.
+#[derive(Default)]
+pub(crate) struct Config {
+    pub(crate) color: Option<ColorChoice>,
+    pub(crate) max_cycles: Option<usize>,
+    pub(crate) overflow: Option<Overflow>,
+    pub(crate) lang: Option<Lang>,
+    /// Whether reports are written as sentences, like `--accessible`.
+    pub(crate) accessible: bool,
+}
+
+impl Config {
+    /// Loads the configuration file, if there is one.
+    pub(crate) fn load() -> Result<Self, String> {
+        let Some(path) = path() else {
+            return Ok(Self::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text)
+                .map_err(|e| format!("Invalid configuration in {}: {}", path.display(), e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Couldn't read {}: {}", path.display(), e)),
+        }
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut config = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("expected `key = value` on line {}", i + 1))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            let invalid = |expected| {
+                format!(
+                    "expected {} for {} on line {}, got {}",
+                    expected,
+                    key,
+                    i + 1,
+                    value
+                )
+            };
+            let string = || {
+                value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .ok_or_else(|| invalid("a string"))
+            };
+            match key {
+                "color" => config.color = Some(ColorChoice::parse(string()?)?),
+                "max_cycles" => {
+                    config.max_cycles = Some(value.parse().map_err(|_| invalid("a number"))?)
+                }
+                "overflow" => config.overflow = Some(Overflow::parse(string()?)?),
+                "lang" => config.lang = Some(Lang::parse(string()?)?),
+                "accessible" => {
+                    config.accessible = value.parse().map_err(|_| invalid("true or false"))?
+                }
+                _ => return Err(format!("unknown setting {} on line {}", key, i + 1)),
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Where the configuration file is, following the XDG base directory specification.
+fn path() -> Option<PathBuf> {
+    let config_home = match env::var_os("XDG_CONFIG_HOME") {
+        Some(config_home) if !config_home.is_empty() => PathBuf::from(config_home),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_home.join("tis-cli").join("config.toml"))
+}
+
+/// Removes a `#` comment from the end of a line, unless the `#` is inside a string.
+pub(crate) fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
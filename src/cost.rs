@@ -0,0 +1,150 @@
+use std::{cell::RefCell, collections::HashMap, fmt::Write, fs, rc::Rc};
+
+use crate::{
+    config::strip_comment, event::Event, instruction::MNEMONICS, position::Position, tis::Tis,
+    utils::accessible,
+};
+
+/// What running a program costs by some measure other than cycles, like energy, read from a file
+/// given with `--costs`. The file is written like the configuration file, with a weight per line:
+///
+/// ```toml
+/// mov = 2
+/// jro = 3.5
+/// # For every value sent, paid by the sender
+/// transfer = 1
+/// # For every cycle a node waits on a port
+/// blocked = 0.1
+/// ```
+///
+/// Anything without a weight is free.
+pub(crate) struct Costs {
+    instructions: HashMap<&'static str, f64>,
+    transfer: f64,
+    blocked: f64,
+}
+
+/// What every node has cost so far, see [`Costs::track`].
+pub(crate) type CostTotals = Rc<RefCell<HashMap<Position, f64>>>;
+
+impl Costs {
+    pub(crate) fn load(path: &str) -> Result<Self, String> {
+        let text =
+            fs::read_to_string(path).map_err(|e| format!("Couldn't read {}: {}", path, e))?;
+        Self::parse(&text).map_err(|e| format!("Invalid costs in {}: {}", path, e))
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut costs = Self {
+            instructions: HashMap::new(),
+            transfer: 0.0,
+            blocked: 0.0,
+        };
+        for (i, line) in text.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("expected `key = weight` on line {}", i + 1))?;
+            let (key, value) = (key.trim(), value.trim());
+            let weight = value
+                .parse::<f64>()
+                .ok()
+                .filter(|weight| weight.is_finite())
+                .ok_or_else(|| {
+                    format!(
+                        "expected a number for {} on line {}, got {}",
+                        key,
+                        i + 1,
+                        value
+                    )
+                })?;
+
+            match key {
+                "transfer" => costs.transfer = weight,
+                "blocked" => costs.blocked = weight,
+                _ => {
+                    let mnemonic = MNEMONICS
+                        .into_iter()
+                        .find(|&mnemonic| mnemonic == key.to_lowercase())
+                        .ok_or_else(|| format!("unknown cost {} on line {}", key, i + 1))?;
+                    costs.instructions.insert(mnemonic, weight);
+                }
+            }
+        }
+        Ok(costs)
+    }
+
+    /// Adds up what every node costs from now on.
+    pub(crate) fn track(self, tis: &mut Tis) -> CostTotals {
+        let totals = CostTotals::default();
+        for node in tis.nodes() {
+            totals.borrow_mut().insert(node.position, 0.0);
+        }
+        // Code reloaded with --watch keeps being charged for the instructions it had at the start
+        let mnemonics = tis.mnemonics();
+
+        let tracked = totals.clone();
+        tis.observe(move |_, event| {
+            let (position, cost) = match *event {
+                Event::InstructionExecuted { position, pointer } => {
+                    let cost = mnemonics
+                        .get(&position)
+                        .and_then(|mnemonics| mnemonics.get(pointer))
+                        .and_then(|mnemonic| self.instructions.get(mnemonic));
+                    (position, cost.copied().unwrap_or(0.0))
+                }
+                Event::ValueTransferred { from, .. } => (from, self.transfer),
+                Event::NodeBlocked { position } => (position, self.blocked),
+                Event::NodeHalted { .. } => return,
+            };
+            *tracked.borrow_mut().entry(position).or_insert(0.0) += cost;
+        });
+        totals
+    }
+}
+
+/// What every node cost, and the whole machine.
+pub(crate) fn report(tis: &Tis, totals: &CostTotals) -> String {
+    let totals = totals.borrow();
+    let rows = tis
+        .nodes()
+        .map(|node| {
+            (
+                tis.label(node.position),
+                totals.get(&node.position).copied().unwrap_or(0.0),
+            )
+        })
+        .chain([("Total".to_owned(), totals.values().sum())])
+        .collect::<Vec<_>>();
+
+    if accessible() {
+        let ((_, total), nodes) = rows.split_last().unwrap();
+        let mut report = nodes
+            .iter()
+            .map(|(label, cost)| format!("Node {} cost {}.\n", label, format_cost(*cost)))
+            .collect::<String>();
+        writeln!(report, "The total cost is {}.", format_cost(*total)).unwrap();
+        return report;
+    }
+
+    let width = rows
+        .iter()
+        .map(|(label, _)| label.len())
+        .chain(["Node".len()])
+        .max()
+        .unwrap();
+    let mut report = format!("{:<1$}  Cost\n", "Node", width);
+    for (label, cost) in rows {
+        writeln!(report, "{:<2$}  {}", label, format_cost(cost), width).unwrap();
+    }
+    report
+}
+
+// Fractional weights add up to long fractions that don't mean anything
+fn format_cost(cost: f64) -> String {
+    let cost = format!("{:.3}", cost);
+    cost.trim_end_matches('0').trim_end_matches('.').to_owned()
+}
@@ -0,0 +1,349 @@
+use std::{fmt::Write, fs, path::Path};
+
+use crate::{
+    extension::Extensions,
+    parse_tis::{node_sources, normalize::read_program, NodeSource},
+    position::Coords,
+};
+
+/// Writes an overview of the program at the path in `args` for sharing it, made from its `##` doc
+/// comments, like `tis-cli doc prog.tis`. The overview is Markdown on stdout, or written to the
+/// file given with `--output`, which gets HTML instead if its name ends in `.html`.
+///
+/// `##` lines right above a label document the label, and the others in a node document the node.
+/// The ones before the first node document the whole program.
+pub(crate) fn doc(args: &[String]) -> Result<(), Option<String>> {
+    let mut output = None;
+    let mut path = None;
+    let mut coords = Coords::Plane;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => {
+                output = Some(
+                    args.next()
+                        .ok_or_else(|| format!("Expected a value after {}", arg))?,
+                )
+            }
+            "--coords" => {
+                coords = Coords::parse(
+                    args.next()
+                        .ok_or_else(|| format!("Expected a value after {}", arg))?,
+                )?
+            }
+            _ if arg.starts_with("--") => return Err(Some(format!("Unknown flag: {}", arg))),
+            _ if path.is_none() => path = Some(arg),
+            _ => return Err(Some(format!("Unexpected argument: {}", arg))),
+        }
+    }
+    let path = path.ok_or("No path provided".to_owned())?;
+
+    let mut nodes = node_sources(path, coords, Extensions::default())?;
+    // In the order the game numbers its nodes
+    nodes.sort_by_key(|node| (node.position.z, -node.position.y, node.position.x));
+    let code = read_program(path).map_err(|e| format!("Couldn't read {}: {}", path, e))?;
+    let prose = &code[..code.find('@').unwrap_or(code.len())];
+
+    let title = Path::new(path)
+        .file_stem()
+        .map_or(path.to_owned(), |stem| stem.to_string_lossy().into_owned());
+    let overview = Overview {
+        title,
+        description: doc_lines(prose).collect(),
+        nodes: nodes
+            .iter()
+            .map(|node| NodeDoc::new(node, coords))
+            .collect(),
+    };
+
+    match output {
+        Some(output) => {
+            let html = output.ends_with(".html");
+            let contents = overview.render(if html { &Html } else { &Markdown });
+            fs::write(output, contents)
+                .map_err(|e| Some(format!("Couldn't write {}: {}", output, e)))
+        }
+        None => {
+            print!("{}", overview.render(&Markdown));
+            Ok(())
+        }
+    }
+}
+
+/// What the `##` lines in `text` say, without the `##`.
+fn doc_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().filter_map(doc_line)
+}
+
+fn doc_line(line: &str) -> Option<&str> {
+    let doc = line.trim_start().strip_prefix("##")?;
+    Some(doc.strip_prefix(' ').unwrap_or(doc).trim_end())
+}
+
+/// The columns of the tables of nodes and of labels.
+const NODE_COLUMNS: [&str; 6] = [
+    "Node",
+    "Name",
+    "Kind",
+    "Instructions",
+    "Locked",
+    "Description",
+];
+const LABEL_COLUMNS: [&str; 4] = ["Label", "Node", "Instruction", "Description"];
+
+/// A node along with what its doc comments say about it and its labels.
+struct NodeDoc<'a> {
+    source: &'a NodeSource,
+    /// How [`NodeSource::position`] is written.
+    coords: Coords,
+    doc: Vec<&'a str>,
+    /// The doc comments of every label, in the order of [`NodeSource::labels`].
+    labels: Vec<Vec<&'a str>>,
+}
+
+impl<'a> NodeDoc<'a> {
+    fn new(source: &'a NodeSource, coords: Coords) -> Self {
+        let mut doc = Vec::new();
+        let mut labels = vec![Vec::new(); source.labels.len()];
+        // The doc comments since the last line that wasn't one
+        let mut pending = Vec::new();
+        let mut offset = source.offset;
+        for line in source.text.split('\n') {
+            let end = offset + line.len();
+            if let Some(line) = doc_line(line) {
+                pending.push(line);
+            } else {
+                match source
+                    .labels
+                    .iter()
+                    .position(|(_, _, span)| (offset..=end).contains(&span.start))
+                {
+                    Some(label) => labels[label].append(&mut pending),
+                    None => doc.append(&mut pending),
+                }
+            }
+            offset = end + 1;
+        }
+        doc.append(&mut pending);
+        Self {
+            source,
+            coords,
+            doc,
+            labels,
+        }
+    }
+
+    /// The node in the table of nodes, see [`NODE_COLUMNS`].
+    fn row(&self) -> Vec<String> {
+        let source = self.source;
+        vec![
+            source.position.shown(self.coords).to_string(),
+            source.name.clone().unwrap_or_default(),
+            source.kind.clone().unwrap_or_else(|| "code".to_owned()),
+            match source.kind {
+                Some(_) => String::new(),
+                None => source.instructions.len().to_string(),
+            },
+            if source.locked { "yes" } else { "" }.to_owned(),
+            self.doc.join(" "),
+        ]
+    }
+
+    /// The labels of the node in the table of labels, see [`LABEL_COLUMNS`].
+    fn label_rows(&self) -> impl Iterator<Item = Vec<String>> + '_ {
+        self.source
+            .labels
+            .iter()
+            .zip(&self.labels)
+            .map(|((name, index, _), doc)| {
+                vec![
+                    name.clone(),
+                    self.source.position.shown(self.coords).to_string(),
+                    index.to_string(),
+                    doc.join(" "),
+                ]
+            })
+    }
+
+    /// What's shown for the node on the grid.
+    fn short_name(&self) -> &str {
+        match (&self.source.name, &self.source.kind) {
+            (Some(name), _) => name,
+            (None, Some(kind)) => kind,
+            (None, None) => "code",
+        }
+    }
+}
+
+/// Everything the doc comments of a program say, along with where its nodes are.
+struct Overview<'a> {
+    title: String,
+    description: Vec<&'a str>,
+    nodes: Vec<NodeDoc<'a>>,
+}
+
+impl Overview<'_> {
+    fn render(&self, format: &dyn Format) -> String {
+        let mut out = String::new();
+        format.heading(&mut out, 1, &self.title);
+        if !self.description.is_empty() {
+            format.paragraph(&mut out, &self.description.join(" "));
+        }
+
+        format.heading(&mut out, 2, "Grid");
+        let mut layers = self
+            .nodes
+            .iter()
+            .map(|node| node.source.position.z)
+            .collect::<Vec<_>>();
+        layers.dedup();
+        for &z in &layers {
+            if layers.len() > 1 {
+                format.heading(&mut out, 3, &format!("Layer {}", z));
+            }
+            self.grid(&mut out, format, z);
+        }
+
+        format.heading(&mut out, 2, "Nodes");
+        let rows = self.nodes.iter().map(NodeDoc::row).collect::<Vec<_>>();
+        format.table(&mut out, &NODE_COLUMNS, &rows);
+
+        let mut labels = self
+            .nodes
+            .iter()
+            .flat_map(NodeDoc::label_rows)
+            .collect::<Vec<_>>();
+        if !labels.is_empty() {
+            // Stable, so labels with the same name stay in the order of their nodes
+            labels.sort_by(|a, b| a[0].cmp(&b[0]));
+            format.heading(&mut out, 2, "Labels");
+            format.table(&mut out, &LABEL_COLUMNS, &labels);
+        }
+        format.finish(out)
+    }
+
+    /// A table laid out like the nodes on layer `z`, with the top row highest up.
+    fn grid(&self, out: &mut String, format: &dyn Format, z: i32) {
+        let nodes = self
+            .nodes
+            .iter()
+            .filter(|node| node.source.position.z == z)
+            .collect::<Vec<_>>();
+        let xs = nodes.iter().map(|node| node.source.position.x);
+        let ys = nodes.iter().map(|node| node.source.position.y);
+        let (min_x, max_x) = (xs.clone().min().unwrap(), xs.max().unwrap());
+        let (min_y, max_y) = (ys.clone().min().unwrap(), ys.max().unwrap());
+
+        let header = (min_x..=max_x).map(|x| x.to_string()).collect::<Vec<_>>();
+        let rows = (min_y..=max_y)
+            .rev()
+            .map(|y| {
+                let mut row = vec![y.to_string()];
+                row.extend((min_x..=max_x).map(|x| {
+                    nodes
+                        .iter()
+                        .find(|node| (node.source.position.x, node.source.position.y) == (x, y))
+                        .map_or(String::new(), |node| node.short_name().to_owned())
+                }));
+                row
+            })
+            .collect::<Vec<_>>();
+        format.table(
+            out,
+            &[""]
+                .into_iter()
+                .chain(header.iter().map(String::as_str))
+                .collect::<Vec<_>>(),
+            &rows,
+        );
+    }
+}
+
+/// How an overview is written out.
+trait Format {
+    fn heading(&self, out: &mut String, level: usize, text: &str);
+    fn paragraph(&self, out: &mut String, text: &str);
+    fn table(&self, out: &mut String, header: &[&str], rows: &[Vec<String>]);
+    /// Wraps up everything that was written.
+    fn finish(&self, out: String) -> String;
+}
+
+struct Markdown;
+
+impl Markdown {
+    fn row<'a>(out: &mut String, cells: impl Iterator<Item = &'a str>) {
+        for cell in cells {
+            write!(out, "| {} ", cell.replace('|', "\\|")).unwrap();
+        }
+        out.push_str("|\n");
+    }
+}
+
+impl Format for Markdown {
+    fn heading(&self, out: &mut String, level: usize, text: &str) {
+        writeln!(out, "{} {}\n", "#".repeat(level), text).unwrap();
+    }
+
+    fn paragraph(&self, out: &mut String, text: &str) {
+        writeln!(out, "{}\n", text).unwrap();
+    }
+
+    fn table(&self, out: &mut String, header: &[&str], rows: &[Vec<String>]) {
+        Self::row(out, header.iter().copied());
+        Self::row(out, header.iter().map(|_| "---"));
+        for row in rows {
+            Self::row(out, row.iter().map(String::as_str));
+        }
+        out.push('\n');
+    }
+
+    fn finish(&self, out: String) -> String {
+        out
+    }
+}
+
+struct Html;
+
+/// `text` with everything that means something in HTML escaped.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Format for Html {
+    fn heading(&self, out: &mut String, level: usize, text: &str) {
+        writeln!(out, "<h{0}>{1}</h{0}>", level, escape(text)).unwrap();
+    }
+
+    fn paragraph(&self, out: &mut String, text: &str) {
+        writeln!(out, "<p>{}</p>", escape(text)).unwrap();
+    }
+
+    fn table(&self, out: &mut String, header: &[&str], rows: &[Vec<String>]) {
+        out.push_str("<table>\n<tr>");
+        for cell in header {
+            write!(out, "<th>{}</th>", escape(cell)).unwrap();
+        }
+        out.push_str("</tr>\n");
+        for row in rows {
+            out.push_str("<tr>");
+            for cell in row {
+                write!(out, "<td>{}</td>", escape(cell)).unwrap();
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+    }
+
+    fn finish(&self, out: String) -> String {
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<style>\nbody {{ \
+             font-family: sans-serif; }}\ntable {{ border-collapse: collapse; margin-bottom: 1em; \
+             }}\nth, td {{ border: 1px solid #999; padding: 0.2em 0.6em; }}\n</style>\n</head>\n\
+             <body>\n{}</body>\n</html>\n",
+            out
+        )
+    }
+}
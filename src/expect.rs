@@ -0,0 +1,105 @@
+use std::fmt::Write;
+
+use ariadne::{Color, Fmt};
+
+use crate::{
+    number::Number,
+    tis::{Output, Tis},
+    utils::{accessible, color},
+};
+
+/// Compares the outputs produced by `tis` against the expected values, returning a rendered diff
+/// if they don't match.
+pub(crate) fn diff_outputs(tis: &Tis, expected: &[Number], actual: &[Output]) -> Option<String> {
+    let divergence = (0..expected.len().max(actual.len()))
+        .find(|&i| expected.get(i).copied() != actual.get(i).map(|output| output.value))?;
+
+    let mut diff = String::new();
+    match actual.get(divergence) {
+        Some(output) => writeln!(
+            diff,
+            "Output mismatch: first divergence at value #{} (cycle {})",
+            divergence + 1,
+            output.cycle
+        ),
+        None => writeln!(
+            diff,
+            "Output mismatch: expected {} values but only {} were produced",
+            expected.len(),
+            actual.len()
+        ),
+    }
+    .unwrap();
+
+    // Reading out a whole table isn't much use, so only the values that differ are listed
+    if accessible() {
+        for i in divergence..expected.len().max(actual.len()) {
+            let expected = expected.get(i).copied();
+            let output = actual.get(i);
+            if expected == output.map(|output| output.value) {
+                continue;
+            }
+            let expected = expected.map_or("nothing".to_owned(), |value| value.to_string());
+            match output {
+                Some(output) => writeln!(
+                    diff,
+                    "Value {}: expected {}, got {} from node {} at cycle {}.",
+                    i + 1,
+                    expected,
+                    output.value,
+                    tis.label(output.position),
+                    output.cycle
+                ),
+                None => writeln!(diff, "Value {}: expected {}, got nothing.", i + 1, expected),
+            }
+            .unwrap();
+        }
+        return Some(diff.trim_end().to_owned());
+    }
+
+    writeln!(diff).unwrap();
+    let columns = ["#", "expected", "actual", "node", "cycle"];
+    let rows = (0..expected.len().max(actual.len()))
+        .map(|i| {
+            let output = actual.get(i);
+            [
+                (i + 1).to_string(),
+                expected.get(i).map_or("-".to_owned(), Number::to_string),
+                output.map_or("-".to_owned(), |output| output.value.to_string()),
+                output.map_or("-".to_owned(), |output| tis.label(output.position)),
+                output.map_or("-".to_owned(), |output| output.cycle.to_string()),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let widths = [0, 1, 2, 3, 4].map(|column| {
+        rows.iter()
+            .map(|row| row[column].len())
+            .fold(columns[column].len(), usize::max)
+    });
+
+    let (red, yellow) = match color() {
+        true => (Some(Color::Red), Some(Color::Yellow)),
+        false => (None, None),
+    };
+    let format_row = |row: [&str; 5]| {
+        row.iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:>width$}", cell))
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+
+    writeln!(diff, "  {}", format_row(columns)).unwrap();
+    for (i, row) in rows.iter().enumerate() {
+        let line = format_row(row.each_ref().map(String::as_str));
+        if i == divergence {
+            writeln!(diff, "{} {}", ">".fg(red), line.fg(red)).unwrap();
+        } else if expected.get(i).copied() != actual.get(i).map(|output| output.value) {
+            writeln!(diff, "{} {}", "!".fg(yellow), line).unwrap();
+        } else {
+            writeln!(diff, "  {}", line).unwrap();
+        }
+    }
+
+    Some(diff.trim_end().to_owned())
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use crate::{
+    parse_tis::game_nodes,
+    position::{Coords, Position, GAME_COLUMNS, GAME_ROWS},
+};
+
+/// How much code fits in a node in the game.
+const MAX_LINES: usize = 15;
+const MAX_LINE_LENGTH: usize = 18;
+
+/// Writes the program at the path in `args` to stdout in another format, like
+/// `tis-cli export --format tis100-save prog.tis`.
+pub(crate) fn export(args: &[String]) -> Result<(), Option<String>> {
+    let mut format = None;
+    let mut path = None;
+    let mut coords = Coords::Plane;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                format = Some(
+                    args.next()
+                        .ok_or_else(|| format!("Expected a value after {}", arg))?,
+                )
+            }
+            "--coords" => {
+                coords = Coords::parse(
+                    args.next()
+                        .ok_or_else(|| format!("Expected a value after {}", arg))?,
+                )?
+            }
+            _ if arg.starts_with("--") => return Err(Some(format!("Unknown flag: {}", arg))),
+            _ if path.is_none() => path = Some(arg),
+            _ => return Err(Some(format!("Unexpected argument: {}", arg))),
+        }
+    }
+    let path = path.ok_or("No path provided".to_owned())?;
+
+    match format.map(String::as_str) {
+        Some("tis100-save") => print!("{}", tis100_save(path, coords)?),
+        Some(format) => {
+            return Err(Some(format!(
+                "Unknown format: '{}', the only one is tis100-save",
+                format
+            )))
+        }
+        None => return Err(Some("Expected --format tis100-save".to_owned())),
+    }
+    Ok(())
+}
+
+/// The program at `path` as the game saves solutions, which can be pasted into its save files.
+/// The game's nodes are numbered row by row from the top left, see [`Coords`].
+fn tis100_save(path: &str, coords: Coords) -> Result<String, Option<String>> {
+    let mut codes = HashMap::new();
+    for (position, code) in game_nodes(path, coords)? {
+        let Some(number) = position.in_game().2 else {
+            return Err(Some(format!(
+                "The node at {} is outside of the game's grid, which goes from {} to {}",
+                position.shown(coords),
+                Position::game_node(0).unwrap().shown(coords),
+                Position::game_node(GAME_COLUMNS * GAME_ROWS - 1)
+                    .unwrap()
+                    .shown(coords)
+            )));
+        };
+        if code.lines().count() > MAX_LINES {
+            return Err(Some(format!(
+                "The node at {} has more than the {} lines of code the game fits in a node",
+                position.shown(coords),
+                MAX_LINES
+            )));
+        }
+        if let Some(line) = code
+            .lines()
+            .find(|line| line.trim_end().chars().count() > MAX_LINE_LENGTH)
+        {
+            return Err(Some(format!(
+                "The line `{}` of the node at {} is longer than the {} characters the game fits \
+                 on a line",
+                line.trim(),
+                position.shown(coords),
+                MAX_LINE_LENGTH
+            )));
+        }
+        codes.insert(number, code);
+    }
+
+    let mut save = String::new();
+    for number in 0..GAME_COLUMNS * GAME_ROWS {
+        save += &format!("@{}\n", number);
+        if let Some(code) = codes.get(&number) {
+            for line in code.lines() {
+                save += line.trim_end();
+                save += "\n";
+            }
+        }
+        save += "\n";
+    }
+    Ok(save)
+}
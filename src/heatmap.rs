@@ -0,0 +1,204 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Write,
+    fs,
+};
+
+use crate::position::{parse_position, Coords, Position};
+
+/// How far apart nodes are drawn, and how big they are.
+const CELL: i32 = 80;
+const NODE: i32 = 44;
+
+/// Reads a transcript written with `--io-transcript` and writes how many values went over every
+/// edge between two nodes, like `tis-cli stats --heatmap out.svg io.log`. The heatmap is a CSV
+/// file instead of an SVG image if its path ends in `.csv`.
+pub(crate) fn stats(args: &[String]) -> Result<(), Option<String>> {
+    let mut heatmap = None;
+    let mut path = None;
+    let mut coords = Coords::Plane;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--heatmap" => {
+                heatmap = Some(
+                    args.next()
+                        .ok_or_else(|| format!("Expected a value after {}", arg))?,
+                )
+            }
+            "--coords" => {
+                coords = Coords::parse(
+                    args.next()
+                        .ok_or_else(|| format!("Expected a value after {}", arg))?,
+                )?
+            }
+            _ if arg.starts_with("--") => return Err(Some(format!("Unknown flag: {}", arg))),
+            _ if path.is_none() => path = Some(arg),
+            _ => return Err(Some(format!("Unexpected argument: {}", arg))),
+        }
+    }
+    let path = path.ok_or("No transcript provided".to_owned())?;
+    let heatmap = heatmap.ok_or("Expected --heatmap and where to write it".to_owned())?;
+
+    let transcript = fs::read_to_string(path)
+        .map_err(|e| format!("Couldn't read transcript {}: {}", path, e))?;
+    let traffic = traffic(&transcript, coords).map_err(|e| format!("{}: {}", path, e))?;
+    if traffic.is_empty() {
+        return Err(Some(format!("No values were sent in {}", path)));
+    }
+
+    let contents = if heatmap.ends_with(".csv") {
+        csv(&traffic, coords)
+    } else {
+        svg(&traffic, coords)
+    };
+    fs::write(heatmap, contents)
+        .map_err(|e| Some(format!("Couldn't write heatmap {}: {}", heatmap, e)))
+}
+
+/// How many values went each way between two nodes, by the nodes at either end.
+type Traffic = HashMap<(Position, Position), u64>;
+
+fn traffic(transcript: &str, coords: Coords) -> Result<Traffic, String> {
+    let mut traffic = HashMap::new();
+    for (number, line) in transcript.lines().enumerate() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        let invalid = || format!("Invalid transfer on line {}: '{}'", number + 1, line);
+        let [_, from, to, _] = line.split_whitespace().collect::<Vec<_>>()[..] else {
+            return Err(invalid());
+        };
+        let from = parse_position(from, coords).map_err(|_| invalid())?;
+        let to = parse_position(to, coords).map_err(|_| invalid())?;
+        *traffic.entry((from, to)).or_insert(0) += 1;
+    }
+    Ok(traffic)
+}
+
+fn csv(traffic: &Traffic, coords: Coords) -> String {
+    let mut edges = traffic.iter().collect::<Vec<_>>();
+    edges.sort_by_key(|((from, to), _)| (key(from), key(to)));
+
+    let mut csv = "from,to,values\n".to_owned();
+    for ((from, to), count) in edges {
+        writeln!(
+            csv,
+            "\"{}\",\"{}\",{}",
+            from.shown(coords),
+            to.shown(coords),
+            count
+        )
+        .unwrap();
+    }
+    csv
+}
+
+/// An image of every node with a value in the transcript, and the edges between them colored and
+/// sized by how many values went over them either way. Layers are drawn side by side.
+fn svg(traffic: &Traffic, coords: Coords) -> String {
+    let mut edges = HashMap::new();
+    for (&(from, to), &count) in traffic {
+        // Either way over an edge is the same traffic
+        let edge = if key(&from) <= key(&to) {
+            (from, to)
+        } else {
+            (to, from)
+        };
+        *edges.entry(edge).or_insert(0) += count;
+    }
+    let nodes = edges
+        .keys()
+        .flat_map(|&(from, to)| [from, to])
+        .map(|position| key(&position))
+        .collect::<BTreeSet<_>>();
+    let busiest = edges.values().copied().max().unwrap_or(1);
+
+    let (min_x, max_x) = bounds(nodes.iter().map(|&(_, x, _)| x));
+    let (min_y, max_y) = bounds(nodes.iter().map(|&(_, _, y)| y));
+    let layers = nodes.iter().map(|&(z, _, _)| z).collect::<BTreeSet<_>>();
+    let layer_width = max_x - min_x + 2;
+    // Where the middle of a node goes, with y growing downwards in the image
+    let center = |position: &Position| {
+        let layer = layers.iter().position(|&z| z == position.z).unwrap() as i32;
+        (
+            (layer * layer_width + position.x - min_x) * CELL + CELL / 2,
+            (max_y - position.y) * CELL + CELL / 2,
+        )
+    };
+
+    let width = layers.len() as i32 * layer_width * CELL;
+    let height = (max_y - min_y + 1) * CELL;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" \
+         viewBox=\"0 0 {0} {1}\" font-family=\"monospace\" font-size=\"11\" \
+         text-anchor=\"middle\">\n",
+        width, height
+    );
+    writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>").unwrap();
+
+    let mut edges = edges.into_iter().collect::<Vec<_>>();
+    // The busiest edges are drawn last so they're on top
+    edges.sort_by_key(|&((from, to), count)| (count, key(&from), key(&to)));
+    for ((from, to), count) in edges {
+        let heat = count as f64 / busiest as f64;
+        let (x1, y1) = center(&from);
+        let (x2, y2) = center(&to);
+        writeln!(
+            svg,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"rgb(255,{},0)\" \
+             stroke-width=\"{:.1}\"><title>{} - {}: {}</title></line>",
+            x1,
+            y1,
+            x2,
+            y2,
+            (220.0 * (1.0 - heat)) as u8,
+            2.0 + 10.0 * heat,
+            from.shown(coords),
+            to.shown(coords),
+            count
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\">{}</text>",
+            (x1 + x2) / 2,
+            (y1 + y2) / 2 - 4,
+            count
+        )
+        .unwrap();
+    }
+
+    for (z, x, y) in nodes {
+        let position = Position::new(x, y).with_z(z);
+        let (x, y) = center(&position);
+        writeln!(
+            svg,
+            "<rect x=\"{}\" y=\"{}\" width=\"{2}\" height=\"{2}\" fill=\"#eee\" stroke=\"#333\"/>",
+            x - NODE / 2,
+            y - NODE / 2,
+            NODE
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\">{}</text>",
+            x,
+            y + 4,
+            position.shown(coords)
+        )
+        .unwrap();
+    }
+    svg + "</svg>\n"
+}
+
+/// Orders positions by layer first, then like they're read on the grid.
+fn key(position: &Position) -> (i32, i32, i32) {
+    (position.z, position.x, position.y)
+}
+
+fn bounds(values: impl Iterator<Item = i32>) -> (i32, i32) {
+    values.fold((i32::MAX, i32::MIN), |(min, max), value| {
+        (min.min(value), max.max(value))
+    })
+}
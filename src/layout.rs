@@ -0,0 +1,47 @@
+use crate::{extension::Extensions, parse_tis::node_texts, position::Coords};
+
+/// Lists every node in the program at the path in `args` with its position written both ways, see
+/// [`Coords`], along with its number if it's on the game's grid, like `tis-cli layout prog.tis`.
+pub(crate) fn layout(args: &[String]) -> Result<(), Option<String>> {
+    let mut path = None;
+    let mut coords = Coords::Plane;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--coords" => {
+                coords = Coords::parse(
+                    args.next()
+                        .ok_or_else(|| format!("Expected a value after {}", arg))?,
+                )?
+            }
+            _ if arg.starts_with("--") => return Err(Some(format!("Unknown flag: {}", arg))),
+            _ if path.is_none() => path = Some(arg),
+            _ => return Err(Some(format!("Unexpected argument: {}", arg))),
+        }
+    }
+    let path = path.ok_or("No path provided".to_owned())?;
+
+    let mut positions = node_texts(path, coords, Extensions::default())?
+        .into_keys()
+        .collect::<Vec<_>>();
+    // In the order the game numbers its nodes
+    positions.sort_by_key(|position| (position.z, -position.y, position.x));
+
+    println!("{:<6}{:<12}Plane", "Node", "Game");
+    for position in positions {
+        let (x, y, number) = position.in_game();
+        let layer = match position.z {
+            0 => String::new(),
+            z => format!(", {}", z),
+        };
+        println!(
+            "{:<6}{:<12}{}, {}{}",
+            number.map_or(String::new(), |number| format!("@{}", number)),
+            format!("{}, {}{}", x, y, layer),
+            position.x,
+            position.y,
+            layer
+        );
+    }
+    Ok(())
+}
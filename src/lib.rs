@@ -0,0 +1,54 @@
+//! A TIS-100 emulator.
+//!
+//! Besides the `tis-cli` binary, the machine can be embedded: load a program with [`Load::load`],
+//! then run it a cycle at a time with [`Tis::step`] and look at any node with [`Tis::node_at`].
+//!
+//! The machine itself is in `tis-core`, which needs nothing but `alloc` without its default `std`
+//! feature, and loading programs is in `tis-parse`. Depending on `tis-core` alone keeps the
+//! terminal-rendering dependencies of the other two out.
+
+mod args;
+mod bench;
+mod cli;
+mod config;
+mod cost;
+mod doc;
+mod expect;
+mod export;
+mod heatmap;
+mod layout;
+mod metrics;
+mod narrate;
+mod puzzle;
+mod quiz;
+mod scaffold;
+mod signal;
+mod speed;
+mod stats;
+mod summary;
+mod symbols;
+mod test_runner;
+mod trace;
+mod transcript;
+mod watch;
+
+// The machine and loading programs are in crates of their own, but used here as if they weren't
+use tis_core::{
+    checkpoint, event, extension, instruction, node, number, optimize, position, register, stream,
+    tis, topology,
+};
+use tis_parse::{diagnostic, message, parse_tis, utils, warning};
+
+pub use tis_core::{
+    CustomNode, Direction, Event, NodeView, Number, Output, Position, Registers, TickReport,
+    TickStatus, Tis,
+};
+pub use tis_parse::Load;
+
+// Only public so the binary can call it
+#[doc(hidden)]
+pub use crate::cli::run_code;
+
+// Only public so the benchmarks can use them
+#[doc(hidden)]
+pub use crate::bench::{Workload, WORKLOADS};
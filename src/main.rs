@@ -1,32 +1,16 @@
-mod direction;
-mod instruction;
-mod node;
-mod number;
-mod parse_tis;
-mod position;
-mod register;
-mod tis;
-mod utils;
+use std::process;
 
-use std::env::args;
-
-use parse_tis::parse;
-use tis::TIS;
+use tis_cli::run_code;
 
 fn main() {
-    if let Err(Some(e)) = run_code() {
-        eprintln!("{}", e);
-    }
-}
-
-fn run_code() -> Result<(), Option<String>> {
-    let mut args = args();
-    args.next();
-
-    let mut tis = TIS::new();
-    parse(&mut tis, args.next().ok_or("No path provided".to_owned())?)?;
-
-    loop {
-        tis.tick();
+    match run_code() {
+        Ok(0) => {}
+        Ok(code) => process::exit(code),
+        Err(e) => {
+            if let Some(e) = e {
+                eprintln!("{}", e);
+            }
+            process::exit(1);
+        }
     }
 }
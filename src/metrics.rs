@@ -0,0 +1,134 @@
+use std::{
+    fmt::Write as _,
+    io::{Read, Write},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::tis::{NodeStats, Tis};
+
+/// How often the served metrics are refreshed from the running machine.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long a connection gets to send its request before it's answered anyway.
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Serves Prometheus-style metrics about the machine over HTTP.
+///
+/// The machine itself isn't shared with the serving thread, instead a rendered snapshot is
+/// published every so often.
+pub(crate) struct Metrics {
+    snapshot: Arc<Mutex<String>>,
+    last_refresh: Instant,
+}
+
+impl Metrics {
+    pub(crate) fn serve(address: &str, tis: &Tis) -> Result<Self, String> {
+        let listener = TcpListener::bind(address)
+            .map_err(|e| format!("Couldn't serve metrics on {}: {}", address, e))?;
+        let snapshot = Arc::new(Mutex::new(render(tis)));
+
+        let served = snapshot.clone();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                // The request itself doesn't matter, every path gets the metrics. A client that
+                // never sends one mustn't hold up everyone after it
+                let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request);
+
+                let body = served.lock().unwrap().clone();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+            }
+        });
+
+        Ok(Self {
+            snapshot,
+            last_refresh: Instant::now(),
+        })
+    }
+
+    /// Publishes a new snapshot if the last one is old enough.
+    pub(crate) fn refresh(&mut self, tis: &Tis) {
+        // Checking the time every cycle would slow the machine down noticeably
+        if !tis.cycle().is_multiple_of(256) || self.last_refresh.elapsed() < REFRESH_INTERVAL {
+            return;
+        }
+        self.publish(tis);
+    }
+
+    /// Publishes a new snapshot right away, like when the machine is about to sit idle.
+    pub(crate) fn publish(&mut self, tis: &Tis) {
+        self.last_refresh = Instant::now();
+        *self.snapshot.lock().unwrap() = render(tis);
+    }
+}
+
+fn render(tis: &Tis) -> String {
+    let mut metrics = String::new();
+
+    writeln!(
+        metrics,
+        "# HELP tis_cycles_total Cycles executed by the machine."
+    )
+    .unwrap();
+    writeln!(metrics, "# TYPE tis_cycles_total counter").unwrap();
+    writeln!(metrics, "tis_cycles_total {}", tis.cycle()).unwrap();
+
+    let mut stats = tis.stats().collect::<Vec<_>>();
+    stats.sort_by_key(|(position, _)| (position.z, position.x, position.y));
+
+    let mut counter = |name, help, value: fn(&NodeStats) -> u64| {
+        writeln!(metrics, "# HELP {} {}", name, help).unwrap();
+        writeln!(metrics, "# TYPE {} counter", name).unwrap();
+        for (position, stats) in &stats {
+            let node_name = match tis.name(**position) {
+                Some(node_name) => format!(",name=\"{}\"", escape(node_name)),
+                None => String::new(),
+            };
+            writeln!(
+                metrics,
+                "{}{{x=\"{}\",y=\"{}\",z=\"{}\"{}}} {}",
+                name,
+                position.x,
+                position.y,
+                position.z,
+                node_name,
+                value(stats)
+            )
+            .unwrap();
+        }
+    };
+    counter(
+        "tis_node_values_sent_total",
+        "Values sent by each node to its neighbors.",
+        |stats| stats.sent,
+    );
+    counter(
+        "tis_node_values_received_total",
+        "Values received by each node from its neighbors.",
+        |stats| stats.received,
+    );
+    counter(
+        "tis_node_blocked_cycles_total",
+        "Cycles each node spent waiting on a port.",
+        |stats| stats.blocked_cycles,
+    );
+
+    metrics
+}
+
+/// `value` with the characters that can't be in a label value as they are escaped.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,164 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    event::Event,
+    instruction::Instruction,
+    position::Position,
+    register::{Register, RegisterOrNumber},
+    tis::{Registers, Tis},
+};
+
+/// Explains every instruction a node finishes running in plain words, for `--explain-run`.
+pub(crate) struct Narrator {
+    // The instructions that finished during the last cycle, by node and pointer
+    executed: Rc<RefCell<Vec<(Position, usize)>>>,
+}
+
+impl Narrator {
+    pub(crate) fn new(tis: &mut Tis) -> Self {
+        let executed = Rc::new(RefCell::new(Vec::new()));
+        let collected = executed.clone();
+        tis.observe(move |_, event| {
+            if let Event::InstructionExecuted { position, pointer } = *event {
+                collected.borrow_mut().push((position, pointer));
+            }
+        });
+        Self { executed }
+    }
+
+    /// Prints a line to stderr for every instruction that finished during the last cycle, like
+    /// `node 1, 0: copies a value from UP into ACC; ACC is now 5`.
+    pub(crate) fn narrate(&self, tis: &Tis) {
+        for (position, pointer) in self.executed.borrow_mut().drain(..) {
+            let (Some(instruction), Some(registers)) = (
+                tis.instruction(position, pointer),
+                tis.node_at(position).and_then(|node| node.registers),
+            ) else {
+                continue;
+            };
+            eprintln!(
+                "cycle {}, node {}: {}",
+                tis.cycle(),
+                tis.label(position),
+                explain(instruction, registers)
+            );
+        }
+    }
+}
+
+/// What `instruction` did, given the registers it left behind.
+fn explain(instruction: Instruction, after: Registers) -> String {
+    let acc = after.accumulator;
+    match instruction {
+        Instruction::Noop => "does nothing".to_owned(),
+        Instruction::Move(source, Register::Accumulator) => {
+            format!(
+                "copies {} into ACC; ACC is now {}",
+                source_name(source),
+                acc
+            )
+        }
+        Instruction::Move(source, Register::General(register)) => format!(
+            "copies {} into R{}; R{} is now {}",
+            source_name(source),
+            register,
+            register,
+            after.general[register as usize]
+        ),
+        Instruction::Move(source, Register::Nil) => {
+            format!("takes {} and throws it away", source_name(source))
+        }
+        Instruction::Move(source, destination) => format!(
+            "sends {} to {}",
+            source_name(source),
+            register_name(destination)
+        ),
+        Instruction::MoveEqualZero(source, destination) => format!(
+            "sends {} to {} if ACC was zero",
+            source_name(source),
+            register_name(destination)
+        ),
+        Instruction::MoveNotZero(source, destination) => format!(
+            "sends {} to {} if ACC wasn't zero",
+            source_name(source),
+            register_name(destination)
+        ),
+        Instruction::Swap => format!(
+            "swaps ACC and BAK; ACC is now {} and BAK is now {}",
+            acc, after.backup
+        ),
+        Instruction::Save => format!("copies ACC into BAK; BAK is now {}", after.backup),
+        Instruction::Add(source) => {
+            format!("adds {} to ACC; ACC is now {}", source_name(source), acc)
+        }
+        Instruction::Subtract(source) => format!(
+            "subtracts {} from ACC; ACC is now {}",
+            source_name(source),
+            acc
+        ),
+        Instruction::Negate => format!("flips the sign of ACC; ACC is now {}", acc),
+        Instruction::Jump(target) => format!("jumps to instruction {}", target),
+        Instruction::JumpEqualZero(target) => conditional_jump(target, "zero", acc.value() == 0),
+        Instruction::JumpNotZero(target) => conditional_jump(target, "not zero", acc.value() != 0),
+        Instruction::JumpGreaterThanZero(target) => {
+            conditional_jump(target, "above zero", acc.value() > 0)
+        }
+        Instruction::JumpLessThanZero(target) => {
+            conditional_jump(target, "below zero", acc.value() < 0)
+        }
+        Instruction::Push(source) => format!("pushes {} onto its stack", source_name(source)),
+        Instruction::Pop(destination) => format!(
+            "pops a value off its stack into {}",
+            register_name(destination)
+        ),
+        Instruction::Call(target) => format!("calls instruction {}", target),
+        Instruction::Return => format!("returns to instruction {}", after.pointer),
+        Instruction::ReadNext(destination) => format!(
+            "reads the next value of its data into {}",
+            register_name(destination)
+        ),
+        Instruction::ReadIndexed(destination) => format!(
+            "reads the value of its data at ACC into {}",
+            register_name(destination)
+        ),
+        Instruction::JumpRelative(offset) => format!(
+            "jumps by {}; the next instruction is {}",
+            source_name(offset),
+            after.pointer
+        ),
+    }
+}
+
+fn conditional_jump(target: usize, condition: &str, jumped: bool) -> String {
+    match jumped {
+        true => format!(
+            "jumps to instruction {} because ACC is {}",
+            target, condition
+        ),
+        false => format!("doesn't jump to {} because ACC isn't {}", target, condition),
+    }
+}
+
+/// What an instruction reads, like `the value 5` or `a value from UP`.
+pub(crate) fn source_name(source: RegisterOrNumber) -> String {
+    match source {
+        RegisterOrNumber::Number(number) => format!("the value {}", number),
+        RegisterOrNumber::Register(Register::Accumulator) => "ACC".to_owned(),
+        RegisterOrNumber::Register(Register::Backup) => "BAK".to_owned(),
+        RegisterOrNumber::Register(Register::General(register)) => format!("R{}", register),
+        RegisterOrNumber::Register(Register::Nil) => "zero (NIL)".to_owned(),
+        RegisterOrNumber::Register(register) => format!("a value from {}", register_name(register)),
+    }
+}
+
+fn register_name(register: Register) -> String {
+    match register {
+        Register::Accumulator => "ACC".to_owned(),
+        Register::Backup => "BAK".to_owned(),
+        Register::General(register) => format!("R{}", register),
+        Register::Nil => "NIL".to_owned(),
+        Register::Direction(direction) => direction.to_string().to_uppercase(),
+        Register::Any => "ANY".to_owned(),
+        Register::Last => "LAST".to_owned(),
+    }
+}
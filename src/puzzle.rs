@@ -0,0 +1,272 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{self, BufRead, IsTerminal, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    number::{parse_numbers, Number},
+    position::{parse_position, Coords, Position},
+    topology::parse_size,
+};
+
+/// What every test gets as its input when none are given, like the game's puzzles.
+const DEFAULT_INPUTS: &str = "random:39:-99:99 random:39:-99:99 random:39:-99:99";
+
+/// How the input of a test is made.
+enum Generator {
+    /// The values as they're given, like `1,2,3`.
+    Values(Vec<Number>),
+    /// `count` values picked at random from `min` to `max`, like `random:39:-99:99`.
+    Random { count: usize, min: i32, max: i32 },
+    /// Every value from `from` to `to`, counting down if `to` is smaller, like `range:1:10`.
+    Range { from: i32, to: i32 },
+}
+
+impl Generator {
+    fn parse(generator: &str) -> Result<Self, String> {
+        let invalid = || {
+            format!(
+                "Invalid input: '{}', expected values like 1,2,3, random:COUNT:MIN:MAX or \
+                 range:FROM:TO with values from -999 to 999",
+                generator
+            )
+        };
+        let value = |value: &str| {
+            value
+                .parse()
+                .ok()
+                .filter(|value| (-999..=999).contains(value))
+                .ok_or_else(invalid)
+        };
+        match generator.split(':').collect::<Vec<_>>()[..] {
+            ["random", count, min, max] => {
+                let count = count.parse().map_err(|_| invalid())?;
+                let (min, max) = (value(min)?, value(max)?);
+                if min > max {
+                    return Err(invalid());
+                }
+                Ok(Self::Random { count, min, max })
+            }
+            ["range", from, to] => Ok(Self::Range {
+                from: value(from)?,
+                to: value(to)?,
+            }),
+            _ => parse_numbers(generator)
+                .map(Self::Values)
+                .map_err(|_| invalid()),
+        }
+    }
+
+    fn generate(&self, rng: &mut Rng) -> Vec<Number> {
+        match *self {
+            Self::Values(ref values) => values.clone(),
+            Self::Random { count, min, max } => (0..count)
+                .map(|_| Number::from(rng.between(min, max)))
+                .collect(),
+            Self::Range { from, to } if from <= to => (from..=to).map(Number::from).collect(),
+            Self::Range { from, to } => (to..=from).rev().map(Number::from).collect(),
+        }
+    }
+}
+
+/// A SplitMix64 generator, which is all picking inputs needs. The same seed always gives the same
+/// inputs.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn between(&mut self, min: i32, max: i32) -> i32 {
+        min + (self.next() % (max - min + 1) as u64) as i32
+    }
+}
+
+/// Asks for a setting on the terminal, giving `default` back if nothing is typed in.
+fn ask(question: &str, default: &str) -> Result<String, String> {
+    print!("{} [{}]: ", question, default);
+    io::stdout()
+        .flush()
+        .map_err(|e| format!("Couldn't ask for the puzzle's settings: {}", e))?;
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .map_err(|e| format!("Couldn't read the puzzle's settings: {}", e))?;
+    match answer.trim() {
+        "" => Ok(default.to_owned()),
+        answer => Ok(answer.to_owned()),
+    }
+}
+
+/// Creates the spec of a new puzzle for others to solve, like
+/// `tis-cli init-puzzle --size 4x3 --damaged 1,1 --input range:1:10 sum`, which creates
+/// `sum.puzzle.tis`. Whatever isn't given with a flag is asked for when stdin is a terminal.
+///
+/// The puzzle comes with a test for every input and empty nodes to write a reference solution in,
+/// which `tis-cli test --bless` then takes the expected outputs of the tests from.
+pub(crate) fn init_puzzle(args: &[String]) -> Result<(), String> {
+    let mut size = None;
+    let mut damaged = None::<Vec<String>>;
+    let mut inputs = None::<Vec<String>>;
+    let mut seed = None;
+    let mut name = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("Expected a value after {}", arg))
+        };
+        match arg.as_str() {
+            "--size" => size = Some(value()?),
+            "--damaged" => damaged.get_or_insert_with(Vec::new).push(value()?),
+            "--input" => inputs.get_or_insert_with(Vec::new).push(value()?),
+            "--seed" => {
+                let value = value()?;
+                seed = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("Invalid seed: '{}'", value))?,
+                );
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown flag: {}", arg)),
+            _ if name.is_none() => name = Some(arg),
+            _ => return Err(format!("Unexpected argument: {}", arg)),
+        }
+    }
+    let name = name.ok_or("Expected a name for the puzzle, like tis-cli init-puzzle sum")?;
+    let name = name
+        .strip_suffix(".tis")
+        .unwrap_or(name)
+        .trim_end_matches(".puzzle");
+
+    let interactive = io::stdin().is_terminal();
+    let size = match size {
+        Some(size) => size,
+        None if interactive => ask("Grid size", "4x3")?,
+        None => "4x3".to_owned(),
+    };
+    let damaged = match damaged {
+        Some(damaged) => damaged,
+        None if interactive => ask("Damaged nodes, like 1,0 2,2", "none")?
+            .split_whitespace()
+            .filter(|position| *position != "none")
+            .map(str::to_owned)
+            .collect(),
+        None => Vec::new(),
+    };
+    let inputs = match inputs {
+        Some(inputs) => inputs,
+        None if interactive => ask(
+            "Inputs of the tests, like 1,2,3, random:COUNT:MIN:MAX or range:FROM:TO",
+            DEFAULT_INPUTS,
+        )?
+        .split_whitespace()
+        .map(str::to_owned)
+        .collect(),
+        None => DEFAULT_INPUTS.split(' ').map(str::to_owned).collect(),
+    };
+
+    let invalid_size = || format!("Invalid grid size: '{}'", size);
+    let (width, height) = parse_size(&size).ok_or_else(invalid_size)?;
+    let width = i32::try_from(width).map_err(|_| invalid_size())?;
+    let height = i32::try_from(height).map_err(|_| invalid_size())?;
+    let damaged = damaged
+        .iter()
+        .map(|position| {
+            let position = parse_position(position, Coords::Plane)?;
+            if position.z != 0
+                || !(0..width).contains(&position.x)
+                || !(0..height).contains(&position.y)
+            {
+                return Err(format!(
+                    "The damaged node at {} isn't on the grid",
+                    position.shown(Coords::Plane)
+                ));
+            }
+            Ok(position)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let generators = inputs
+        .iter()
+        .map(|input| Generator::parse(input))
+        .collect::<Result<Vec<_>, _>>()?;
+    if generators.is_empty() {
+        return Err("A puzzle needs at least one test".to_owned());
+    }
+
+    let random = generators
+        .iter()
+        .any(|generator| matches!(generator, Generator::Random { .. }));
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64
+    });
+    let mut rng = Rng(seed);
+
+    let path = format!("{}.puzzle.tis", name);
+    let mut puzzle = format!("{}: describe what the puzzle asks for here.\n", name);
+    if random {
+        writeln!(puzzle, "The inputs were picked with --seed {}.", seed).unwrap();
+    }
+    for generator in &generators {
+        let input = generator
+            .generate(&mut rng)
+            .iter()
+            .map(Number::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+        // Left empty for --bless to fill in
+        write!(puzzle, "\n#! input {}\n#! expect\n", input).unwrap();
+    }
+
+    // Like the game, the input is above the top left node and the output below the bottom right one
+    writeln!(puzzle, "\n@number_console_in locked 0, {}\n", height).unwrap();
+    let mut reference = true;
+    for y in (0..height).rev() {
+        for x in 0..width {
+            if damaged.contains(&Position::new(x, y)) {
+                writeln!(puzzle, "@{}, {} locked\n# Damaged\n", x, y).unwrap();
+                continue;
+            }
+            writeln!(puzzle, "@{}, {}", x, y).unwrap();
+            if reference {
+                reference = false;
+                writeln!(
+                    puzzle,
+                    "# Write a reference solution in the nodes that aren't locked, then have the \
+                     tests expect what\n# it writes with: tis-cli test --bless {}\n# Take it out \
+                     again before sharing the puzzle.",
+                    path
+                )
+                .unwrap();
+            }
+            puzzle.push('\n');
+        }
+    }
+    writeln!(puzzle, "@number_console_out locked {}, -1", width - 1).unwrap();
+
+    if Path::new(&path).exists() {
+        return Err(format!("{} already exists", path));
+    }
+    fs::write(&path, puzzle).map_err(|e| format!("Couldn't write {}: {}", path, e))?;
+
+    println!("Created {}", path);
+    println!(
+        "Write a reference solution in it, then fill in what the tests expect with: tis-cli test \
+         --bless {}",
+        path
+    );
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    rc::Rc,
+};
+
+use crate::{
+    event::Event,
+    instruction::Instruction,
+    narrate::source_name,
+    number::Number,
+    position::Position,
+    register::Register,
+    stream::OutputStream,
+    tis::{Registers, Tis},
+};
+
+/// Where the answers are typed in, which isn't stdin since console input nodes may be reading it.
+#[cfg(windows)]
+const TERMINAL: &str = "CONIN$";
+#[cfg(not(windows))]
+const TERMINAL: &str = "/dev/tty";
+
+/// Asks for the result of every instruction that changes ACC and every value a node sends before
+/// showing it, for `--quiz`.
+pub(crate) struct Quiz {
+    // None once the answers have run out, after which the program just runs
+    answers: Option<BufReader<File>>,
+    // What console output nodes wrote during the last cycle, held back so it can't give answers away
+    held: Rc<RefCell<Vec<u8>>>,
+    // The registers of every node before the last cycle, which the questions start from
+    before: HashMap<Position, Registers>,
+    events: Rc<RefCell<Vec<Event>>>,
+    asked: usize,
+    right: usize,
+}
+
+struct Held(Rc<RefCell<Vec<u8>>>);
+
+impl Write for Held {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Quiz {
+    pub(crate) fn open() -> Result<Self, String> {
+        let answers = File::open(TERMINAL)
+            .map_err(|e| format!("Couldn't open the terminal to read answers from: {}", e))?;
+        Ok(Self {
+            answers: Some(BufReader::new(answers)),
+            held: Rc::new(RefCell::new(Vec::new())),
+            before: HashMap::new(),
+            events: Rc::new(RefCell::new(Vec::new())),
+            asked: 0,
+            right: 0,
+        })
+    }
+
+    /// Where console output nodes writing to stdout write instead, until the cycle's questions
+    /// have been answered.
+    pub(crate) fn output(&self) -> OutputStream {
+        OutputStream::new(Held(self.held.clone()))
+    }
+
+    pub(crate) fn observe(&mut self, tis: &mut Tis) {
+        let events = self.events.clone();
+        tis.observe(move |_, event| {
+            if matches!(
+                event,
+                Event::InstructionExecuted { .. } | Event::ValueTransferred { .. }
+            ) {
+                events.borrow_mut().push(*event);
+            }
+        });
+    }
+
+    /// Remembers the registers the next cycle starts from.
+    pub(crate) fn prepare(&mut self, tis: &Tis) {
+        self.before = tis
+            .nodes()
+            .filter_map(|node| Some((node.position, node.registers?)))
+            .collect();
+    }
+
+    /// Asks about everything that happened during the last cycle, then shows what was held back.
+    pub(crate) fn ask(&mut self, tis: &Tis) {
+        for event in self.events.take() {
+            let Some((question, answer)) = self.question(tis, event) else {
+                continue;
+            };
+            eprintln!("cycle {}, {}", tis.cycle(), question);
+            let Some(guess) = self.guess() else {
+                break;
+            };
+            self.asked += 1;
+            if guess == answer {
+                self.right += 1;
+                eprintln!("Right!");
+            } else {
+                eprintln!("No, it's {}", answer);
+            }
+        }
+
+        let held = self.held.take();
+        if !held.is_empty() {
+            let mut stdout = io::stdout();
+            // Stdout going away ends the run through the output nodes soon enough
+            let _ = stdout.write_all(&held).and_then(|()| stdout.flush());
+        }
+    }
+
+    fn question(&self, tis: &Tis, event: Event) -> Option<(String, Number)> {
+        match event {
+            Event::InstructionExecuted { position, pointer } => {
+                let before = self.before.get(&position)?;
+                let after = tis.node_at(position)?.registers?;
+                let did = match tis.instruction(position, pointer)? {
+                    Instruction::Move(source, Register::Accumulator) => {
+                        format!("copies {} into ACC", source_name(source))
+                    }
+                    Instruction::Add(source) => format!("adds {} to ACC", source_name(source)),
+                    Instruction::Subtract(source) => {
+                        format!("subtracts {} from ACC", source_name(source))
+                    }
+                    Instruction::Negate => "flips the sign of ACC".to_owned(),
+                    Instruction::Swap => format!("swaps ACC with BAK, which was {}", before.backup),
+                    _ => return None,
+                };
+                Some((
+                    format!(
+                        "node {} {}. ACC was {}, what is it now?",
+                        tis.label(position),
+                        did,
+                        before.accumulator
+                    ),
+                    after.accumulator,
+                ))
+            }
+            // Only values sent by running code are worth guessing
+            Event::ValueTransferred { from, to, value } if self.before.contains_key(&from) => {
+                Some((
+                    format!(
+                        "node {} sends a value to node {}. What is it?",
+                        tis.label(from),
+                        tis.label(to)
+                    ),
+                    value,
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Reads answers until one is a number, or None if there are no more.
+    fn guess(&mut self) -> Option<Number> {
+        let answers = self.answers.as_mut()?;
+        loop {
+            eprint!("> ");
+            let mut line = String::new();
+            if !matches!(answers.read_line(&mut line), Ok(1..)) {
+                eprintln!();
+                self.answers = None;
+                return None;
+            }
+            match line.trim().parse() {
+                Ok(guess) => return Some(guess),
+                Err(_) => eprintln!("'{}' isn't a number", line.trim()),
+            }
+        }
+    }
+
+    /// How many answers were right, like `Score: 3/5`.
+    pub(crate) fn score(&self) -> String {
+        format!("Score: {}/{}", self.right, self.asked)
+    }
+}
@@ -0,0 +1,115 @@
+use std::{fmt::Write, fs, path::Path};
+
+/// What a new program starts out as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Template {
+    /// The game's grid of 4 by 3 empty nodes, read from above the top left one and written to
+    /// below the bottom right one.
+    Blank,
+    /// A single empty node between the input and the output.
+    Single,
+    /// A single node that already solves the puzzle.
+    Echo,
+}
+
+impl Template {
+    fn parse(template: &str) -> Result<Self, String> {
+        match template {
+            "blank" => Ok(Self::Blank),
+            "single" => Ok(Self::Single),
+            "echo" => Ok(Self::Echo),
+            _ => Err(format!(
+                "Unknown template: '{}', expected blank, single or echo",
+                template
+            )),
+        }
+    }
+
+    /// The input and output nodes, which the puzzle locks so solutions can't move them.
+    fn console(self) -> [&'static str; 2] {
+        match self {
+            Template::Blank => [
+                "@number_console_in locked 0, 3",
+                "@number_console_out locked 3, -1",
+            ],
+            Template::Single | Template::Echo => [
+                "@number_console_in locked 0, 1",
+                "@number_console_out locked 0, -1",
+            ],
+        }
+    }
+
+    /// The nodes a solution is written in.
+    fn nodes(self) -> String {
+        match self {
+            Template::Blank => {
+                let mut nodes = String::new();
+                for y in (0..3).rev() {
+                    for x in 0..4 {
+                        writeln!(nodes, "@{}, {}\n", x, y).unwrap();
+                    }
+                }
+                nodes
+            }
+            Template::Single => "@0, 0\n# Read from up, write to down\n\n".to_owned(),
+            Template::Echo => "@0, 0\nmov up down\n\n".to_owned(),
+        }
+    }
+}
+
+/// The puzzle every template is for, which its tests check.
+const PUZZLE: &str = "Write every value read from the input to the output.
+
+#! input 1, 2, 3
+#! expect 1, 2, 3
+
+#! input -999, 0, 999
+#! expect -999, 0, 999
+";
+
+/// Creates a program to start from along with the puzzle it solves, like
+/// `tis-cli new --template echo hello`, which creates `hello.tis` and `hello.puzzle.tis`.
+pub(crate) fn new_program(args: &[String]) -> Result<(), String> {
+    let mut template = Template::Blank;
+    let mut name = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--template" => {
+                template = Template::parse(
+                    args.next()
+                        .ok_or_else(|| format!("Expected a value after {}", arg))?,
+                )?
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown flag: {}", arg)),
+            _ if name.is_none() => name = Some(arg),
+            _ => return Err(format!("Unexpected argument: {}", arg)),
+        }
+    }
+    let name = name.ok_or("Expected a name for the program, like tis-cli new hello")?;
+    let name = name.strip_suffix(".tis").unwrap_or(name);
+
+    let [input, output] = template.console();
+    let program = format!("{}\n\n{}{}\n", input, template.nodes(), output);
+    let puzzle = format!("{}\n{}\n\n{}\n", PUZZLE, input, output);
+
+    let program_path = format!("{}.tis", name);
+    let puzzle_path = format!("{}.puzzle.tis", name);
+    // Nothing is written unless both can be, so half a program is never left behind
+    for path in [&program_path, &puzzle_path] {
+        if Path::new(path).exists() {
+            return Err(format!("{} already exists", path));
+        }
+    }
+    fs::write(&program_path, program)
+        .map_err(|e| format!("Couldn't write {}: {}", program_path, e))?;
+    fs::write(&puzzle_path, puzzle)
+        .map_err(|e| format!("Couldn't write {}: {}", puzzle_path, e))?;
+
+    println!("Created {} and {}", program_path, puzzle_path);
+    println!(
+        "Test it with: tis-cli test --puzzle {} {}",
+        puzzle_path, program_path
+    );
+    Ok(())
+}
@@ -0,0 +1,138 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::{extension::Extension, tis::Tis};
+
+/// What an interrupted run exits with, like any other program stopped by Ctrl-C.
+pub(crate) const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static DUMP_REQUESTED: AtomicBool = AtomicBool::new(false);
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Makes Ctrl-C ask the run to stop after the current cycle instead of killing it, SIGUSR1 ask for
+/// the state of the machine, and Ctrl-Z show it before suspending the process. Pressing Ctrl-C a
+/// second time still exits right away, since the machine may be stuck somewhere no signal gets to.
+#[cfg(unix)]
+pub(crate) fn install() {
+    handle(libc::SIGINT, interrupt);
+    handle(libc::SIGUSR1, dump);
+    handle(libc::SIGTSTP, pause);
+}
+
+#[cfg(not(unix))]
+pub(crate) fn install() {}
+
+/// Has `handler` handle `signal`. Reads from the console it comes in the middle of aren't restarted
+/// but fail with EINTR, so a machine waiting on input still gets to see to the signal.
+#[cfg(unix)]
+fn handle(signal: libc::c_int, handler: extern "C" fn(libc::c_int)) {
+    // SAFETY: sigaction is plain data, and the handlers only touch atomics until they exit
+    unsafe {
+        let mut action = std::mem::zeroed::<libc::sigaction>();
+        action.sa_sigaction = handler as libc::sighandler_t;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(signal, &action, std::ptr::null_mut());
+    }
+}
+
+#[cfg(unix)]
+extern "C" fn interrupt(_: libc::c_int) {
+    if INTERRUPTED.swap(true, Ordering::Relaxed) {
+        // Input may be being read without echoing it, or keys as they're pressed for --speed,
+        // which would stay that way otherwise
+        // SAFETY: termios is plain data, and these calls are all async-signal-safe
+        unsafe {
+            let mut termios = std::mem::zeroed::<libc::termios>();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) == 0 {
+                termios.c_lflag |= libc::ECHO | libc::ICANON;
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios);
+            }
+            libc::_exit(INTERRUPTED_EXIT_CODE);
+        }
+    }
+}
+
+#[cfg(unix)]
+extern "C" fn dump(_: libc::c_int) {
+    DUMP_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+#[cfg(unix)]
+extern "C" fn pause(_: libc::c_int) {
+    PAUSED.store(true, Ordering::Relaxed);
+}
+
+/// Stops the process the way Ctrl-Z does without a handler, until it's continued.
+#[cfg(unix)]
+fn suspend() {
+    // SAFETY: the default action only stops the process, and raise returns once it's continued
+    unsafe {
+        libc::signal(libc::SIGTSTP, libc::SIG_DFL);
+        libc::raise(libc::SIGTSTP);
+    }
+    handle(libc::SIGTSTP, pause);
+}
+
+#[cfg(not(unix))]
+fn suspend() {}
+
+/// Whether Ctrl-C has been pressed since [`install`].
+pub(crate) fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+/// Whether SIGUSR1 has been received since this was last called.
+pub(crate) fn dump_requested() -> bool {
+    DUMP_REQUESTED.swap(false, Ordering::Relaxed)
+}
+
+/// Shows the state of the machine once Ctrl-Z has been pressed, then suspends the process like
+/// Ctrl-Z always does, holding the run up until it's resumed with `fg`.
+pub(crate) fn wait_while_paused(tis: &Tis) {
+    if !PAUSED.swap(false, Ordering::Relaxed) {
+        return;
+    }
+    eprint!("{}", report(tis, "Paused"));
+    suspend();
+    eprintln!("Resumed at cycle {}", tis.cycle());
+}
+
+/// The state of every node, along with what it did until now, under `heading`.
+pub(crate) fn report(tis: &Tis, heading: &str) -> String {
+    let mut report = format!("{} at cycle {}\n", heading, tis.cycle());
+    let stats = tis.stats().collect::<HashMap<_, _>>();
+    for node in tis.nodes() {
+        write!(report, "  {}:", tis.label(node.position)).unwrap();
+        if let Some(registers) = node.registers {
+            write!(
+                report,
+                " acc {}, bak {}, next instruction {},",
+                registers.accumulator, registers.backup, registers.pointer
+            )
+            .unwrap();
+            if tis.extensions().contains(Extension::Registers) {
+                for (i, register) in registers.general.iter().enumerate() {
+                    write!(report, " r{} {},", i, register).unwrap();
+                }
+            }
+        }
+        // What a hung program is stuck on
+        match (node.blocked, node.pending) {
+            (true, Some(value)) => write!(report, " waiting to write {},", value).unwrap(),
+            (true, None) => write!(report, " waiting to read,").unwrap(),
+            (false, _) => {}
+        }
+        let stats = stats[&node.position];
+        writeln!(
+            report,
+            " sent {}, received {}, blocked for {} cycles",
+            stats.sent, stats.received, stats.blocked_cycles
+        )
+        .unwrap();
+    }
+    report
+}
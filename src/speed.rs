@@ -0,0 +1,187 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::signal;
+
+/// The speeds the number keys pick, from `1` to `9`.
+const PRESETS: [u32; 9] = [1, 2, 5, 10, 20, 50, 100, 200, 500];
+
+/// How often keys are looked at in turbo, which doesn't wait between ticks.
+const TURBO_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Holds the machine to `--speed` ticks a second, which keys pressed on the terminal change while
+/// it runs: `+` and `-` double and halve it, `1` to `9` pick one of [`PRESETS`], and `t` turns
+/// turbo on, which runs the machine as fast as it goes until `t` is pressed again.
+pub(crate) struct Speed {
+    ticks_per_second: u32,
+    turbo: bool,
+    // When the last tick started, or in turbo when keys were last looked at
+    last: Instant,
+    // None when keys can't be read from the terminal
+    keys: Option<Keys>,
+}
+
+impl Speed {
+    /// Only reads keys if `read_keys` is set, since they can't be told apart from what's typed for
+    /// console input nodes reading the same terminal.
+    pub(crate) fn new(ticks_per_second: u32, read_keys: bool) -> Self {
+        let keys = read_keys.then(Keys::open).flatten();
+        if keys.is_some() {
+            eprintln!(
+                "Press + or - to speed up or slow down, 1 to 9 to pick a speed and t for turbo"
+            );
+        }
+        Self {
+            ticks_per_second,
+            turbo: false,
+            last: Instant::now(),
+            keys,
+        }
+    }
+
+    /// Waits until the next tick is due, seeing to any keys pressed in the meantime.
+    pub(crate) fn pace(&mut self) {
+        if self.turbo {
+            let now = Instant::now();
+            if now - self.last >= TURBO_CHECK_INTERVAL {
+                self.last = now;
+                self.read_key(Duration::ZERO);
+            }
+            return;
+        }
+        loop {
+            let interval = Duration::from_secs(1) / self.ticks_per_second;
+            let due = self.last + interval;
+            let now = Instant::now();
+            if now >= due || signal::interrupted() {
+                // A machine that fell behind, like while it waited for input, doesn't make up for
+                // it all at once
+                self.last = if now.saturating_duration_since(due) < interval {
+                    due
+                } else {
+                    now
+                };
+                return;
+            }
+            self.read_key(due - now);
+            if self.turbo {
+                self.last = Instant::now();
+                return;
+            }
+        }
+    }
+
+    /// Waits up to `timeout` for a key and sees to it.
+    fn read_key(&mut self, timeout: Duration) {
+        let Some(keys) = &mut self.keys else {
+            thread::sleep(timeout);
+            return;
+        };
+        match keys.next(timeout) {
+            Some(b'+' | b'=') => self.set(self.ticks_per_second.saturating_mul(2)),
+            Some(b'-') => self.set((self.ticks_per_second / 2).max(1)),
+            Some(key @ b'1'..=b'9') => self.set(PRESETS[usize::from(key - b'1')]),
+            Some(b't') => {
+                self.turbo = !self.turbo;
+                if self.turbo {
+                    eprintln!("Turbo on");
+                } else {
+                    eprintln!(
+                        "Turbo off, running at {} ticks a second",
+                        self.ticks_per_second
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn set(&mut self, ticks_per_second: u32) {
+        self.ticks_per_second = ticks_per_second;
+        // The speed can be picked during turbo, for once it's off
+        if !self.turbo {
+            eprintln!("Running at {} ticks a second", ticks_per_second);
+        }
+    }
+}
+
+/// The terminal while keys pressed on it are read as they come instead of a line at a time, without
+/// echoing them. It goes back to how it was once this is dropped.
+#[cfg(unix)]
+struct Keys {
+    terminal: std::fs::File,
+    original: libc::termios,
+}
+
+#[cfg(unix)]
+impl Keys {
+    /// Opens the terminal, which isn't stdin since that may not be the terminal at all.
+    fn open() -> Option<Self> {
+        use std::os::fd::AsRawFd;
+
+        let terminal = std::fs::File::open("/dev/tty").ok()?;
+        // SAFETY: termios is plain data, and it's only used once tcgetattr has filled it in
+        let mut original = unsafe { std::mem::zeroed::<libc::termios>() };
+        if unsafe { libc::tcgetattr(terminal.as_raw_fd(), &mut original) } != 0 {
+            return None;
+        }
+
+        let mut keys = original;
+        // Ctrl-C and Ctrl-Z still send their signals
+        keys.c_lflag &= !(libc::ICANON | libc::ECHO);
+        keys.c_cc[libc::VMIN] = 0;
+        keys.c_cc[libc::VTIME] = 0;
+        unsafe { libc::tcsetattr(terminal.as_raw_fd(), libc::TCSANOW, &keys) };
+        Some(Self { terminal, original })
+    }
+
+    /// The next key pressed within `timeout`, if there is one. A signal coming in cuts the wait
+    /// short.
+    fn next(&mut self, timeout: Duration) -> Option<u8> {
+        use std::{io::Read, os::fd::AsRawFd};
+
+        let mut poll = libc::pollfd {
+            fd: self.terminal.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // Rounded up, so a wait shorter than a millisecond doesn't spin
+        let timeout = timeout.as_micros().div_ceil(1000).min(i32::MAX as u128) as i32;
+        // SAFETY: poll is given the one pollfd there is, which outlives the call
+        if unsafe { libc::poll(&mut poll, 1, timeout) } <= 0 {
+            return None;
+        }
+        let mut key = [0];
+        match self.terminal.read(&mut key) {
+            Ok(1) => Some(key[0]),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Keys {
+    fn drop(&mut self) {
+        use std::os::fd::AsRawFd;
+
+        // SAFETY: original was filled in by tcgetattr
+        unsafe { libc::tcsetattr(self.terminal.as_raw_fd(), libc::TCSANOW, &self.original) };
+    }
+}
+
+#[cfg(not(unix))]
+struct Keys;
+
+#[cfg(not(unix))]
+impl Keys {
+    fn open() -> Option<Self> {
+        None
+    }
+
+    fn next(&mut self, timeout: Duration) -> Option<u8> {
+        thread::sleep(timeout);
+        None
+    }
+}
@@ -0,0 +1,85 @@
+use std::{collections::HashMap, fmt::Write};
+
+use crate::{tis::Tis, utils::accessible};
+
+/// A table of what every node did during the run, with how idle instruction nodes were like the
+/// game shows it, for `--stats`.
+pub(crate) fn report(tis: &Tis) -> String {
+    if accessible() {
+        return sentences(tis);
+    }
+    let stats = tis.stats().collect::<HashMap<_, _>>();
+    let rows = tis
+        .nodes()
+        .map(|node| {
+            let stats = stats[&node.position];
+            // Only instruction nodes have anything to be idle from
+            let (executed, idle) = match node.registers {
+                Some(_) => (
+                    stats.executed.to_string(),
+                    format!("{:.0}%", stats.idle(tis.cycle()) * 100.0),
+                ),
+                None => (String::new(), String::new()),
+            };
+            [
+                tis.label(node.position),
+                executed,
+                stats.blocked_cycles.to_string(),
+                idle,
+                stats.sent.to_string(),
+                stats.received.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let header = ["Node", "Executed", "Blocked", "Idle", "Sent", "Received"];
+    let widths = (0..header.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].len())
+                .chain([header[column].len()])
+                .max()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let mut report = format!("{} cycles\n", tis.cycle());
+    for row in [header.map(str::to_owned)].iter().chain(&rows) {
+        let mut line = String::new();
+        for (column, cell) in row.iter().enumerate() {
+            // The node goes on the left and the numbers on the right
+            match column {
+                0 => write!(line, "{:<1$}", cell, widths[column]).unwrap(),
+                _ => write!(line, "  {:>1$}", cell, widths[column]).unwrap(),
+            }
+        }
+        writeln!(report, "{}", line).unwrap();
+    }
+    report
+}
+
+/// The same as [`report`] with a sentence for every node, for `--accessible`.
+fn sentences(tis: &Tis) -> String {
+    let stats = tis.stats().collect::<HashMap<_, _>>();
+    let mut report = format!("The run took {} cycles.\n", tis.cycle());
+    for node in tis.nodes() {
+        let stats = stats[&node.position];
+        write!(report, "Node {}", tis.label(node.position)).unwrap();
+        if node.registers.is_some() {
+            write!(
+                report,
+                " executed {} instructions, was idle {:.0}% of the time,",
+                stats.executed,
+                stats.idle(tis.cycle()) * 100.0
+            )
+            .unwrap();
+        }
+        writeln!(
+            report,
+            " was blocked for {} cycles, sent {} values and received {} values.",
+            stats.blocked_cycles, stats.sent, stats.received
+        )
+        .unwrap();
+    }
+    report
+}
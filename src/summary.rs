@@ -0,0 +1,105 @@
+use std::{collections::HashMap, fs};
+
+use crate::{
+    symbols::{list, string},
+    tis::{Output, Tis},
+};
+
+/// Why a run stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Termination {
+    /// Nothing was happening anymore, and nothing from outside the machine could change that.
+    Blocked,
+    /// A node stopped the machine.
+    Halted,
+    /// A node ran into something it can't go on from.
+    Trapped,
+    /// As many values as were expected have been written.
+    OutputComplete,
+    /// As many values as `--output-limit` allows have been written.
+    OutputLimit,
+    /// The run went on for as many cycles as it was allowed to.
+    MaxCycles,
+    /// The run went on for as long as `--timeout` allows.
+    TimedOut,
+    /// The run was interrupted with Ctrl-C.
+    Interrupted,
+}
+
+impl Termination {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Blocked => "blocked",
+            Self::Halted => "halted",
+            Self::Trapped => "trapped",
+            Self::OutputComplete => "output-complete",
+            Self::OutputLimit => "output-limit",
+            Self::MaxCycles => "max-cycles",
+            Self::TimedOut => "timeout",
+            Self::Interrupted => "interrupted",
+        }
+    }
+}
+
+/// Writes how the run went to `path` as JSON for `--summary-json`: why it stopped, how many
+/// cycles it took, the registers every node ended with, what it did like `--stats` shows it and
+/// every value written.
+pub(crate) fn write_summary(
+    path: &str,
+    tis: &Tis,
+    termination: Termination,
+    exit_code: i32,
+    outputs: &[Output],
+) -> Result<(), String> {
+    let stats = tis.stats().collect::<HashMap<_, _>>();
+    let nodes = tis
+        .nodes()
+        .map(|node| {
+            let position = node.position;
+            let stats = stats[&position];
+            // Only instruction nodes run instructions, like in the table of --stats
+            let (executed, acc, bak) = match node.registers {
+                Some(registers) => (
+                    stats.executed.to_string(),
+                    registers.accumulator.to_string(),
+                    registers.backup.to_string(),
+                ),
+                None => ("null".to_owned(), "null".to_owned(), "null".to_owned()),
+            };
+            format!(
+                "{{\"position\": [{}, {}, {}], \"name\": {}, \"acc\": {}, \"bak\": {}, \
+                 \"executed\": {}, \"blocked\": {}, \"sent\": {}, \"received\": {}}}",
+                position.x,
+                position.y,
+                position.z,
+                tis.name(position).map_or("null".to_owned(), string),
+                acc,
+                bak,
+                executed,
+                stats.blocked_cycles,
+                stats.sent,
+                stats.received
+            )
+        })
+        .collect::<Vec<_>>();
+    let outputs = outputs
+        .iter()
+        .map(|output| {
+            format!(
+                "{{\"position\": [{}, {}, {}], \"cycle\": {}, \"value\": {}}}",
+                output.position.x, output.position.y, output.position.z, output.cycle, output.value
+            )
+        })
+        .collect::<Vec<_>>();
+    let json = format!(
+        "{{\n  \"termination\": {},\n  \"exit_code\": {},\n  \"cycles\": {},\n  \"nodes\": {},\n  \
+         \"outputs\": {}\n}}\n",
+        string(termination.name()),
+        exit_code,
+        tis.cycle(),
+        list(&nodes, 2),
+        list(&outputs, 2)
+    );
+
+    fs::write(path, json).map_err(|e| format!("Couldn't write the summary to {}: {}", path, e))
+}
@@ -0,0 +1,108 @@
+use std::{fmt::Write, fs, ops::Range};
+
+use crate::{
+    extension::Extensions,
+    parse_tis::{node_sources, normalize::read_program},
+    position::Coords,
+    utils::line_column,
+};
+
+/// Writes where everything in the program at `program` is to `path` as JSON, for debuggers and
+/// other tools to find the source of a node and instruction the machine is at. Every node has its
+/// labels and instructions by index, which is what the machine's instruction pointer counts.
+///
+/// Spans are byte offsets into the program as it's parsed, with tabs expanded to spaces, along with
+/// the line and column they start at.
+pub(crate) fn emit_symbols(
+    program: &str,
+    path: &str,
+    coords: Coords,
+    extensions: Extensions,
+) -> Result<(), Option<String>> {
+    let nodes = node_sources(program, coords, extensions)?;
+    let source = read_program(program).map_err(|e| format!("Couldn't read {}: {}", program, e))?;
+    let span = |span: &Range<usize>| {
+        let (line, column) = line_column(&source, span.start);
+        format!(
+            "{{\"start\": {}, \"end\": {}, \"line\": {}, \"column\": {}}}",
+            span.start, span.end, line, column
+        )
+    };
+
+    let nodes = nodes
+        .iter()
+        .map(|node| {
+            let labels = node
+                .labels
+                .iter()
+                .map(|(name, index, label)| {
+                    format!(
+                        "{{\"name\": {}, \"instruction\": {}, \"span\": {}}}",
+                        string(name),
+                        index,
+                        span(label)
+                    )
+                })
+                .collect::<Vec<_>>();
+            let instructions = node
+                .instructions
+                .iter()
+                .enumerate()
+                .map(|(index, instruction)| {
+                    format!("{{\"index\": {}, \"span\": {}}}", index, span(instruction))
+                })
+                .collect::<Vec<_>>();
+            let position = node.position;
+            format!(
+                "{{\n      \"position\": [{}, {}, {}],\n      \"name\": {},\n      \
+                 \"kind\": {},\n      \"locked\": {},\n      \"span\": {},\n      \
+                 \"labels\": {},\n      \"instructions\": {}\n    }}",
+                position.x,
+                position.y,
+                position.z,
+                node.name.as_deref().map_or("null".to_owned(), string),
+                string(node.kind.as_deref().unwrap_or("code")),
+                node.locked,
+                span(&(node.offset..node.offset + node.text.len())),
+                list(&labels, 6),
+                list(&instructions, 6),
+            )
+        })
+        .collect::<Vec<_>>();
+    let json = format!(
+        "{{\n  \"program\": {},\n  \"nodes\": {}\n}}\n",
+        string(program),
+        list(&nodes, 2)
+    );
+
+    fs::write(path, json).map_err(|e| Some(format!("Couldn't write symbols to {}: {}", path, e)))
+}
+
+/// A JSON array of `items`, one on each line, closing at `indent`.
+pub(crate) fn list(items: &[String], indent: usize) -> String {
+    if items.is_empty() {
+        return "[]".to_owned();
+    }
+    let inner = " ".repeat(indent + 2);
+    format!(
+        "[\n{}{}\n{}]",
+        inner,
+        items.join(&format!(",\n{}", inner)),
+        " ".repeat(indent)
+    )
+}
+
+/// `text` as a JSON string.
+pub(crate) fn string(text: &str) -> String {
+    let mut string = String::from('"');
+    for c in text.chars() {
+        match c {
+            '"' => string += "\\\"",
+            '\\' => string += "\\\\",
+            '\n' => string += "\\n",
+            c if c.is_control() => write!(string, "\\u{:04x}", c as u32).unwrap(),
+            c => string.push(c),
+        }
+    }
+    string + "\""
+}
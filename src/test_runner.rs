@@ -0,0 +1,389 @@
+use std::{collections::HashMap, fs::write, time::Instant};
+
+use crate::{
+    args::Args,
+    expect::diff_outputs,
+    extension::Extensions,
+    number::{parse_numbers, Number},
+    parse_tis::{node_texts, normalize::read_source, parse_fragments},
+    position::Coords,
+    stream::Streams,
+    tis::{Output, TickStatus, Tis},
+};
+
+/// How long a test may run when `--max-cycles` isn't given.
+const DEFAULT_MAX_CYCLES: usize = 100_000;
+
+/// A test written into a program with `#! input` and `#! expect` lines, along with an optional
+/// `#! cycles` line giving how many cycles it should take.
+pub(crate) struct TestCase {
+    /// The line the test starts on, counting from 1.
+    pub(crate) line: usize,
+    pub(crate) input: Vec<Number>,
+    pub(crate) expect: Vec<Number>,
+    pub(crate) cycles: Option<usize>,
+
+    // Where each directive of the test is, counting from 0, so the test can be blessed
+    input_lines: Vec<usize>,
+    expect_lines: Vec<usize>,
+    cycles_line: Option<usize>,
+}
+
+impl TestCase {
+    fn new(line: usize) -> Self {
+        Self {
+            line,
+            input: Vec::new(),
+            expect: Vec::new(),
+            cycles: None,
+
+            input_lines: Vec::new(),
+            expect_lines: Vec::new(),
+            cycles_line: None,
+        }
+    }
+}
+
+/// Finds the tests in a program. Values can be spread over several lines of the same kind, and an
+/// `input` line after an `expect` line starts the next test.
+pub(crate) fn parse_tests(code: &str) -> Result<Vec<TestCase>, String> {
+    let mut tests: Vec<TestCase> = Vec::new();
+    for (i, line) in code.lines().enumerate() {
+        let line_number = i + 1;
+        let Some(directive) = line.trim_start().strip_prefix("#!") else {
+            continue;
+        };
+        // A shebang isn't a directive
+        if line_number == 1 && directive.starts_with('/') {
+            continue;
+        }
+
+        let directive = directive.trim();
+        let (name, values) = directive
+            .split_once(char::is_whitespace)
+            .unwrap_or((directive, ""));
+        match name {
+            "input" | "expect" => {
+                let values =
+                    parse_numbers(values).map_err(|e| format!("Line {}: {}", line_number, e))?;
+                let test = match tests.last_mut() {
+                    // Input after an expectation belongs to the next test
+                    Some(test) if name == "expect" || test.expect_lines.is_empty() => test,
+                    _ => {
+                        tests.push(TestCase::new(line_number));
+                        tests.last_mut().unwrap()
+                    }
+                };
+                if name == "input" {
+                    test.input.extend(values);
+                    test.input_lines.push(i);
+                } else {
+                    test.expect.extend(values);
+                    test.expect_lines.push(i);
+                }
+            }
+            "cycles" => {
+                let test = tests.last_mut().ok_or_else(|| {
+                    format!("Line {}: there's no test for these cycles", line_number)
+                })?;
+                if test.cycles_line.is_some() {
+                    return Err(format!(
+                        "Line {}: the test on line {} already has a cycle count",
+                        line_number, test.line
+                    ));
+                }
+                test.cycles = Some(
+                    values
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("Line {}: invalid cycle count", line_number))?,
+                );
+                test.cycles_line = Some(i);
+            }
+            _ => {
+                return Err(format!(
+                    "Unknown test directive on line {}: '{}', expected input, expect or cycles",
+                    line_number, name
+                ))
+            }
+        }
+    }
+
+    Ok(tests)
+}
+
+/// Runs the program once for every test written into it, see [`parse_tests`].
+pub(crate) fn run_tests(args: Args) -> Result<(), Option<String>> {
+    if args.expect_output.is_some()
+        || !args.map_in.is_empty()
+        || !args.map_out.is_empty()
+        || args.watch
+        || args.checkpointer.is_some()
+        || args.resume.is_some()
+        || args.verify_determinism.is_some()
+    {
+        return Err(Some(
+            "Only --max-cycles, --timeout and --wrap can be used when testing".to_owned(),
+        ));
+    }
+
+    // A solution is tested against the tests of its puzzle, which it can't change
+    let tests_path = match &args.puzzle {
+        Some(puzzle) => {
+            check_locked(puzzle, &args.path, args.coords, args.extensions)?;
+            puzzle
+        }
+        None => &args.path,
+    };
+    let code =
+        read_source(tests_path).map_err(|e| format!("Couldn't read {}: {}", tests_path, e))?;
+    let tests = parse_tests(&code)?;
+    if tests.is_empty() {
+        return Err(Some(format!(
+            "{} has no tests, add some with #! input and #! expect lines",
+            tests_path
+        )));
+    }
+    let max_cycles = args.max_cycles.unwrap_or(DEFAULT_MAX_CYCLES);
+
+    if args.bless {
+        let mut blessed = HashMap::new();
+        for test in &tests {
+            let (_, outputs, cycles, timed_out) = run_test(&args, test, max_cycles, true)?;
+            if timed_out {
+                return Err(Some(format!(
+                    "The test on line {} timed out after {} cycles",
+                    test.line, cycles
+                )));
+            }
+            if outputs.is_empty() {
+                return Err(Some(format!(
+                    "The test on line {} didn't write anything in {} cycles",
+                    test.line, cycles
+                )));
+            }
+            let values = outputs
+                .iter()
+                .map(|output| output.value)
+                .collect::<Vec<_>>();
+            println!(
+                "Test on line {}: now expects {} in {} cycles",
+                test.line,
+                join(&values),
+                cycles
+            );
+            blessed.insert(test.line, (values, cycles));
+        }
+        write(&args.path, bless(&code, &tests, &blessed))
+            .map_err(|e| format!("Couldn't write {}: {}", args.path, e))?;
+        return Ok(());
+    }
+
+    if let Some(test) = tests.iter().find(|test| test.expect.is_empty()) {
+        return Err(Some(format!(
+            "The test on line {} doesn't expect anything, write what it should with --bless",
+            test.line
+        )));
+    }
+
+    let mut failed = 0;
+    for test in &tests {
+        let (tis, outputs, cycles, timed_out) = run_test(&args, test, max_cycles, false)?;
+        if timed_out {
+            failed += 1;
+            println!(
+                "Test on line {}: failed, timed out after {} cycles",
+                test.line, cycles
+            );
+            continue;
+        }
+        match (diff_outputs(&tis, &test.expect, &outputs), test.cycles) {
+            (None, Some(expected)) if expected != cycles => {
+                failed += 1;
+                println!(
+                    "Test on line {}: failed, took {} cycles instead of {}",
+                    test.line, cycles, expected
+                );
+            }
+            (None, _) => println!("Test on line {}: ok in {} cycles", test.line, cycles),
+            (Some(diff), _) => {
+                failed += 1;
+                println!("Test on line {}: failed\n{}", test.line, diff);
+            }
+        }
+    }
+    println!("{} passed, {} failed", tests.len() - failed, failed);
+
+    if failed > 0 {
+        return Err(None);
+    }
+    Ok(())
+}
+
+/// Makes sure every node the puzzle locks is in the solution just as the puzzle has it.
+fn check_locked(
+    puzzle: &str,
+    solution: &str,
+    coords: Coords,
+    extensions: Extensions,
+) -> Result<(), Option<String>> {
+    let solution_nodes = node_texts(solution, coords, extensions)?;
+    let mut puzzle_nodes = node_texts(puzzle, coords, extensions)?
+        .into_iter()
+        .filter(|(_, (locked, _))| *locked)
+        .collect::<Vec<_>>();
+    puzzle_nodes.sort_by_key(|(position, _)| (position.z, position.x, position.y));
+
+    for (position, (_, text)) in puzzle_nodes {
+        match solution_nodes.get(&position) {
+            Some((_, solution_text)) if *solution_text == text => {}
+            Some(_) => {
+                return Err(Some(format!(
+                    "The locked node at {} was changed from the one in {}",
+                    position.shown(coords),
+                    puzzle
+                )))
+            }
+            None => {
+                return Err(Some(format!(
+                    "The locked node at {} from {} is missing",
+                    position.shown(coords),
+                    puzzle
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs the program until it has written as many values as the test expects or it's stuck, giving
+/// back the machine, what it wrote, the cycle it wrote the last value on and whether it ran out of
+/// time first. When blessing, how many values there'll be isn't known, so the program always runs
+/// until it's stuck.
+fn run_test(
+    args: &Args,
+    test: &TestCase,
+    max_cycles: usize,
+    bless: bool,
+) -> Result<(Tis, Vec<Output>, usize, bool), Option<String>> {
+    let mut tis = Tis::new()
+        .with_topology(args.topology)
+        .with_overflow(args.overflow)
+        .with_coords(args.coords)
+        .with_extensions(args.extensions);
+    parse_fragments(
+        &mut tis,
+        &args.fragments,
+        &mut Streams::test(&test.input).with_coords(args.coords),
+    )?;
+    if args.optimize {
+        tis.optimize();
+    }
+    if args.jit {
+        tis.run_ahead();
+    }
+    if args.fuse {
+        tis.fuse();
+    }
+
+    let mut outputs: Vec<Output> = Vec::new();
+    let deadline = args.timeout.map(|timeout| Instant::now() + timeout);
+    let mut timed_out = false;
+    let mut trap = None;
+    while tis.cycle() < max_cycles {
+        if !bless && outputs.len() >= test.expect.len() {
+            break;
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            timed_out = true;
+            break;
+        }
+        let status = tis.tick();
+        outputs.extend_from_slice(tis.outputs());
+        if let TickStatus::Trapped(message) = status {
+            trap = Some(message);
+            break;
+        }
+        // A program that's stuck or stopped won't write anything more
+        if status != TickStatus::Progress {
+            break;
+        }
+    }
+    tis.finish()?;
+    if let Some(message) = trap {
+        return Err(Some(format!(
+            "The test on line {} trapped: {}",
+            test.line, message
+        )));
+    }
+
+    let cycles = match outputs.last() {
+        Some(output) if bless => output.cycle,
+        _ => tis.cycle(),
+    };
+    Ok((tis, outputs, cycles, timed_out))
+}
+
+/// Rewrites the tests in `code` to expect what they wrote when they were run, as given by
+/// `blessed` for the line each test starts on.
+fn bless(code: &str, tests: &[TestCase], blessed: &HashMap<usize, (Vec<Number>, usize)>) -> String {
+    // What to put in place of a line, where leaving a line out removes it
+    let mut replaced: HashMap<usize, Vec<String>> = HashMap::new();
+    let lines = code.lines().collect::<Vec<_>>();
+
+    for test in tests {
+        let (values, cycles) = &blessed[&test.line];
+        let directives = vec![
+            format!("#! expect {}", join(values)),
+            format!("#! cycles {}", cycles),
+        ];
+
+        for &line in test.expect_lines.iter().chain(&test.cycles_line) {
+            replaced.insert(line, Vec::new());
+        }
+        match test.expect_lines.first() {
+            Some(&line) => {
+                replaced.insert(line, directives);
+            }
+            // A test that doesn't expect anything yet gets its expectations right after its input
+            None => {
+                let line = *test.input_lines.last().unwrap();
+                replaced.insert(
+                    line,
+                    [lines[line].to_owned()]
+                        .into_iter()
+                        .chain(directives)
+                        .collect(),
+                );
+            }
+        }
+    }
+
+    let mut blessed_code = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        match replaced.get(&i) {
+            Some(replacement) => {
+                for line in replacement {
+                    blessed_code.push_str(line);
+                    blessed_code.push('\n');
+                }
+            }
+            None => {
+                blessed_code.push_str(line);
+                blessed_code.push('\n');
+            }
+        }
+    }
+    if !code.ends_with('\n') {
+        blessed_code.pop();
+    }
+    blessed_code
+}
+
+fn join(values: &[Number]) -> String {
+    values
+        .iter()
+        .map(Number::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,114 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    event::Event,
+    instruction::MNEMONICS,
+    position::{parse_position, Coords, Position},
+    tis::Tis,
+};
+
+/// Which events `--trace` prints, so traces of big machines stay readable.
+pub(crate) struct TraceFilter {
+    /// Only events of these nodes, from `--trace-filter "1,0;2,1"`.
+    nodes: Option<HashSet<Position>>,
+    /// Only these instructions running, from `--trace-only mov,jro`.
+    instructions: Option<HashSet<&'static str>>,
+}
+
+impl TraceFilter {
+    /// Reads `--trace-filter` and `--trace-only`, with the positions of nodes written in `coords`.
+    pub(crate) fn parse(
+        nodes: Option<&str>,
+        instructions: Option<&str>,
+        coords: Coords,
+    ) -> Result<Self, String> {
+        let nodes = nodes
+            .map(|nodes| {
+                nodes
+                    .split(';')
+                    .map(|position| parse_position(position, coords))
+                    .collect()
+            })
+            .transpose()?;
+        let instructions = instructions
+            .map(|instructions| {
+                instructions
+                    .split(',')
+                    .map(|instruction| {
+                        let instruction = instruction.trim().to_lowercase();
+                        MNEMONICS
+                            .into_iter()
+                            .find(|&mnemonic| mnemonic == instruction)
+                            .ok_or_else(|| format!("Unknown instruction: '{}'", instruction))
+                    })
+                    .collect()
+            })
+            .transpose()?;
+        Ok(Self {
+            nodes,
+            instructions,
+        })
+    }
+
+    fn shows(&self, event: &Event, mnemonic: Option<&str>) -> bool {
+        let positions = match *event {
+            Event::InstructionExecuted { position, .. }
+            | Event::NodeBlocked { position }
+            | Event::NodeHalted { position } => vec![position],
+            Event::ValueTransferred { from, to, .. } => vec![from, to],
+        };
+        let node_shown = self
+            .nodes
+            .as_ref()
+            .is_none_or(|nodes| positions.iter().any(|position| nodes.contains(position)));
+        // Picking instructions leaves out everything that isn't one of them running
+        let instruction_shown = self.instructions.as_ref().is_none_or(|instructions| {
+            mnemonic.is_some_and(|mnemonic| instructions.contains(mnemonic))
+        });
+        node_shown && instruction_shown
+    }
+}
+
+/// Prints every event `filter` lets through to stderr as it happens, a line each.
+pub(crate) fn trace(tis: &mut Tis, filter: TraceFilter) {
+    // The machine can't be looked at while it's running a cycle, so what's printed is gathered
+    // up front. Code reloaded with --watch is traced with the instructions it had at the start.
+    let labels = tis
+        .nodes()
+        .map(|node| (node.position, tis.label(node.position)))
+        .collect::<HashMap<_, _>>();
+    let mnemonics = tis.mnemonics();
+
+    tis.observe(move |cycle, event| {
+        let mnemonic = match *event {
+            Event::InstructionExecuted { position, pointer } => mnemonics
+                .get(&position)
+                .and_then(|mnemonics| mnemonics.get(pointer).copied()),
+            _ => None,
+        };
+        if !filter.shows(event, mnemonic) {
+            return;
+        }
+        match *event {
+            Event::InstructionExecuted { position, pointer } => eprintln!(
+                "{}: {} ran {} at {}",
+                cycle,
+                labels[&position],
+                mnemonic.unwrap_or("?"),
+                pointer
+            ),
+            Event::ValueTransferred { from, to, value } => {
+                eprintln!(
+                    "{}: {} sent {} to {}",
+                    cycle, labels[&from], value, labels[&to]
+                )
+            }
+            Event::NodeBlocked { position } => {
+                eprintln!("{}: {} is blocked", cycle, labels[&position])
+            }
+            Event::NodeHalted { position } => {
+                eprintln!("{}: {} halted", cycle, labels[&position])
+            }
+        }
+    });
+}
@@ -0,0 +1,50 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use crate::{
+    event::Event,
+    position::{Coords, Position},
+    tis::Tis,
+};
+
+/// Writes every value handed from one node to another to the file at `path`, a line each with the
+/// cycle, the node it came from, the node it went to and the value, like `12 1,0 2,0 -5`.
+pub(crate) fn record_transfers(tis: &mut Tis, path: &str) -> Result<(), String> {
+    let file =
+        File::create(path).map_err(|e| format!("Couldn't create transcript {}: {}", path, e))?;
+    let mut writer = BufWriter::new(file);
+    writeln!(writer, "# cycle from to value")
+        .map_err(|e| format!("Couldn't write transcript {}: {}", path, e))?;
+
+    let path = path.to_owned();
+    let coords = tis.coords();
+    let mut failed = false;
+    tis.observe(move |cycle, event| {
+        let Event::ValueTransferred { from, to, value } = *event else {
+            return;
+        };
+        // A transcript with a gap in it is worse than none, so writing stops at the first error
+        if failed {
+            return;
+        }
+        if let Err(e) = writeln!(
+            writer,
+            "{} {} {} {}",
+            cycle,
+            compact(from, coords),
+            compact(to, coords),
+            value
+        ) {
+            eprintln!("Couldn't write transcript {}: {}", path, e);
+            failed = true;
+        }
+    });
+    Ok(())
+}
+
+/// A position without spaces, so a transcript line can be split on them.
+fn compact(position: Position, coords: Coords) -> String {
+    position.shown(coords).to_string().replace(", ", ",")
+}
@@ -0,0 +1,61 @@
+use std::{
+    fs, mem, thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How often the source file is checked for changes.
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Notices when the source file of the running program is saved.
+pub(crate) struct Watcher {
+    path: String,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+    // Set after waiting for the next check, so it happens whatever cycle the machine is at
+    due: bool,
+}
+
+impl Watcher {
+    pub(crate) fn new(path: String) -> Self {
+        let modified = modified(&path);
+        Self {
+            path,
+            modified,
+            last_check: Instant::now(),
+            due: false,
+        }
+    }
+
+    pub(crate) fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Whether the file has changed since this last returned true.
+    pub(crate) fn changed(&mut self, cycle: usize) -> bool {
+        // Checking the time every cycle would slow the machine down noticeably
+        let due = mem::take(&mut self.due) || cycle.is_multiple_of(256);
+        if !due || self.last_check.elapsed() < CHECK_INTERVAL {
+            return false;
+        }
+        self.last_check = Instant::now();
+
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+        true
+    }
+
+    /// Sleeps until the file is checked again, for when the machine has nothing else to do.
+    pub(crate) fn wait(&mut self) {
+        thread::sleep(CHECK_INTERVAL.saturating_sub(self.last_check.elapsed()));
+        self.due = true;
+    }
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
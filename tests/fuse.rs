@@ -0,0 +1,147 @@
+//! Runs programs routing values through chains of nodes that only pass them on with `--fuse` and
+//! without, which have to write the same values on the same cycles and take as many cycles, since
+//! a fused chain still takes as long to get a value through as its nodes did.
+
+use std::{
+    env, fs,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+const INPUT: &str = "4\n-2\n0\n9\n15\n3\n";
+const CYCLES: &str = "5000";
+
+/// Numbers going through two nodes on either side of one adding one to them.
+const STRAIGHT: &str = "\
+@NUMBER_CONSOLE_IN 0, 0
+@1,0
+mov left right
+@2,0
+mov left right
+@3,0
+mov left acc
+add 1
+mov acc right
+@4,0
+mov left right
+@5,0
+mov left right
+@NUMBER_CONSOLE_OUT 6, 0
+";
+
+/// Numbers going up, right and back down on their way to the output.
+const TURNS: &str = "\
+@NUMBER_CONSOLE_IN 0, 0
+@1,0
+mov left up
+@1,1
+mov down right
+@2,1
+mov left right
+@3,1
+mov left down
+@NUMBER_CONSOLE_OUT 3, 0
+";
+
+/// Numbers piling up in a chain in front of a node that takes its time with every one, so the
+/// chain has to hold on to them the way its nodes would, then going through another chain.
+const BACKED_UP: &str = "\
+@NUMBER_CONSOLE_IN 0, 0
+@1,0
+mov left right
+@2,0
+mov left right
+@3,0
+mov left right
+@4,0
+mov left acc
+sav
+mov 20 acc
+loop: sub 1
+jgz loop
+swp
+neg
+mov acc right
+@5,0
+mov left right
+@6,0
+mov left right
+@NUMBER_CONSOLE_OUT 7, 0
+";
+
+/// Runs the program in `source` with `flags`, giving back what it wrote and its summary without
+/// the nodes, since the nodes of a fused chain aren't there anymore.
+fn run(name: &str, source: &str, flags: &[&str]) -> (String, String) {
+    let dir = env::temp_dir();
+    let path = dir.join(format!("tis-fuse-{}.tis", name));
+    let summary = dir.join(format!("tis-fuse-{}-{}.json", name, flags.len()));
+    fs::write(&path, source).unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tis-cli"))
+        .arg(&path)
+        .args(["--max-cycles", CYCLES, "--summary-json"])
+        .arg(&summary)
+        .args(flags)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        // Where the chains that were fused are reported
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(INPUT.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "{} failed with {:?}: {}",
+        name,
+        flags,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let written = fs::read_to_string(&summary).unwrap();
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(summary);
+    let (head, rest) = written.split_once("\"nodes\"").unwrap();
+    let (_, outputs) = rest.split_once("\"outputs\"").unwrap();
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        format!("{}{}", head, outputs),
+    )
+}
+
+fn run_alike(name: &str, source: &str, flags: &[&str]) {
+    let unfused = run(name, source, &[]);
+    assert!(!unfused.0.is_empty(), "{} didn't write anything", name);
+    assert_eq!(
+        unfused,
+        run(name, source, flags),
+        "{} ran differently with {:?}",
+        name,
+        flags
+    );
+}
+
+#[test]
+fn straight_runs_alike() {
+    run_alike("straight", STRAIGHT, &["--fuse"]);
+}
+
+#[test]
+fn turns_run_alike() {
+    run_alike("turns", TURNS, &["--fuse"]);
+}
+
+#[test]
+fn backed_up_runs_alike() {
+    run_alike("backed-up", BACKED_UP, &["--fuse"]);
+}
+
+/// Fused chains and nodes running ahead together, with a chain on either side of a looping node.
+#[test]
+fn backed_up_runs_alike_with_jit() {
+    run_alike("backed-up-jit", BACKED_UP, &["--fuse", "--jit"]);
+}
@@ -0,0 +1,166 @@
+//! Runs programs whose nodes spend most of their time in loops that don't use any port with `--jit`
+//! and without, which have to write the same values on the same cycles and take as many cycles.
+
+use std::{
+    env, fs,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// What's typed in for the programs reading numbers, the biggest one a little past what a number
+/// can be.
+const INPUT: &str = "5\n0\n3\n12\n-4\n7\n3000\n1\n";
+const CYCLES: &str = "50000";
+
+/// Counts every number down to zero, doubles it, then counts that down two at a time before adding
+/// one, so both nodes in between spend most of their cycles looping.
+const COUNTDOWN: &str = "\
+@NUMBER_CONSOLE_IN 0, 0
+@1,0
+mov left acc
+sav
+loop: sub 1
+jgz loop
+swp
+add acc
+mov acc right
+@2,0
+mov left acc
+sav
+loop: sub 2
+jgz loop
+swp
+add 1
+mov acc right
+@NUMBER_CONSOLE_OUT 3, 0
+";
+
+/// Spins eight times over for every one a number counts down from, then writes what's left of the
+/// count. For the bigger numbers, that's many more instructions than a node may run ahead at once.
+const LONG_LOOPS: &str = "\
+@NUMBER_CONSOLE_IN 0, 0
+@1,0
+mov left acc
+sav
+outer: swp
+sub 1
+jlz done
+swp
+mov 8 acc
+inner: sub 1
+jgz inner
+jmp outer
+done: mov acc right
+@NUMBER_CONSOLE_OUT 2, 0
+";
+
+/// Sorts every number by its sign with `jez`, `jlz` and `jro`, busy for a while on each.
+const BRANCHES: &str = "\
+@NUMBER_CONSOLE_IN 0, 0
+@1,0
+mov left acc
+jez zero
+jlz negative
+sav
+mov 7 acc
+spin: sub 1
+jgz spin
+swp
+jro 2
+zero: mov 100 acc
+jmp out
+negative: neg
+sav
+mov 3 acc
+wait: sub 1
+jnz wait
+swp
+out: mov acc right
+@NUMBER_CONSOLE_OUT 2, 0
+";
+
+/// Two nodes sending a counter back and forth, with the first one busy in between, and every
+/// value written out as it goes by.
+const PING_PONG: &str = "\
+@0,0
+mov acc right
+mov right acc
+add 1
+sav
+loop: sub 3
+jgz loop
+swp
+@1,0
+mov left acc
+neg
+mov acc down
+mov acc left
+@NUMBER_CONSOLE_OUT 1, -1
+";
+
+/// Runs the program in `source` with `flags`, giving back what it wrote and its summary without
+/// the nodes, whose registers a node running ahead only has right once it's caught up.
+fn run(name: &str, source: &str, flags: &[&str]) -> (String, String) {
+    let dir = env::temp_dir();
+    let path = dir.join(format!("tis-jit-{}.tis", name));
+    let summary = dir.join(format!("tis-jit-{}-{}.json", name, flags.len()));
+    fs::write(&path, source).unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tis-cli"))
+        .arg(&path)
+        .args(["--max-cycles", CYCLES, "--summary-json"])
+        .arg(&summary)
+        .args(flags)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(INPUT.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{} failed with {:?}", name, flags);
+
+    let written = fs::read_to_string(&summary).unwrap();
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(summary);
+    let (head, rest) = written.split_once("\"nodes\"").unwrap();
+    let (_, outputs) = rest.split_once("\"outputs\"").unwrap();
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        format!("{}{}", head, outputs),
+    )
+}
+
+fn run_alike(name: &str, source: &str) {
+    let interpreted = run(name, source, &[]);
+    assert!(!interpreted.0.is_empty(), "{} didn't write anything", name);
+    assert_eq!(
+        interpreted,
+        run(name, source, &["--jit"]),
+        "{} ran differently with --jit",
+        name
+    );
+}
+
+#[test]
+fn countdown_runs_alike() {
+    run_alike("countdown", COUNTDOWN);
+}
+
+#[test]
+fn long_loops_run_alike() {
+    run_alike("long-loops", LONG_LOOPS);
+}
+
+#[test]
+fn branches_run_alike() {
+    run_alike("branches", BRANCHES);
+}
+
+#[test]
+fn ping_pong_runs_alike() {
+    run_alike("ping-pong", PING_PONG);
+}
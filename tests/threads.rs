@@ -0,0 +1,137 @@
+//! Runs generated grids big enough to be split between threads on one thread and on several, which
+//! have to do exactly the same every cycle.
+
+use std::{env, fs, process::Command};
+
+use tis_cli::{Load, Tis};
+
+/// How big a side of every generated grid is, enough nodes for every thread to get a region.
+const SIDE: usize = 16;
+const CYCLES: usize = 3000;
+const THREADS: usize = 4;
+
+/// Columns of nodes each passing a counter down, counting it down first.
+fn pipelines() -> String {
+    let mut source = String::new();
+    for x in 0..SIDE {
+        for y in 0..SIDE {
+            source += &format!("@{},{}\n", x, y);
+            source += &match y {
+                0 => "add 1\nmov acc down\n".to_owned(),
+                y if y == SIDE - 1 => "mov up nil\n".to_owned(),
+                _ => format!(
+                    "mov up acc\nsav\nloop: sub {}\njgz loop\nswp\nmov acc down\n",
+                    x + 1
+                ),
+            };
+        }
+    }
+    source
+}
+
+/// Columns of nodes reading from `any` and answering the column to their left.
+fn any_traffic() -> String {
+    let mut source = String::new();
+    for x in 0..SIDE {
+        for y in 0..SIDE {
+            source += &format!("@{},{}\n", x, y);
+            source += if x % 2 == 0 {
+                "mov acc right\nmov right acc\nadd 1\n"
+            } else {
+                "mov any acc\njro 2\nadd 5\nmov acc left\n"
+            };
+        }
+    }
+    source
+}
+
+/// Pairs of nodes sending a counter back and forth, with the first of every pair busy on its own
+/// in between.
+fn ping_pong() -> String {
+    let mut source = String::new();
+    for x in (0..SIDE).step_by(2) {
+        for y in 0..SIDE {
+            source += &format!(
+                "@{},{}\nmov acc right\nmov right acc\nadd 1\nsav\nloop: sub 3\njgz loop\nswp\n",
+                x, y
+            );
+            source += &format!("@{},{}\nmov left acc\nneg\nmov acc left\n", x + 1, y);
+        }
+    }
+    source
+}
+
+/// Writes `source` to a file of its own, named after `name`.
+fn write(name: &str, source: &str) -> String {
+    let path = env::temp_dir().join(format!("tis-threads-{}.tis", name));
+    fs::write(&path, source).unwrap();
+    path.to_str().unwrap().to_owned()
+}
+
+fn run_alike(name: &str, source: &str) {
+    let path = write(name, source);
+    let mut single = Tis::load(&path).unwrap();
+    let mut threaded = Tis::new()
+        .with_threads(THREADS)
+        .with_program(&path)
+        .unwrap();
+    for _ in 0..CYCLES {
+        let (a, b) = (single.step(), threaded.step());
+        let outputs = |report: &tis_cli::TickReport| {
+            report
+                .outputs
+                .iter()
+                .map(|output| (output.position, output.value, output.cycle))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            (a.cycle, a.transfers, a.blocked, outputs(&a)),
+            (b.cycle, b.transfers, b.blocked, outputs(&b)),
+            "{} ran differently on {} threads",
+            name,
+            THREADS
+        );
+        assert!(
+            single.nodes().eq(threaded.nodes()),
+            "{} ended up in a different state at cycle {} on {} threads",
+            name,
+            a.cycle,
+            THREADS
+        );
+    }
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn pipelines_run_alike() {
+    run_alike("pipelines", &pipelines());
+}
+
+#[test]
+fn any_traffic_runs_alike() {
+    run_alike("any-traffic", &any_traffic());
+}
+
+#[test]
+fn ping_pong_runs_alike() {
+    run_alike("ping-pong", &ping_pong());
+}
+
+/// The determinism checker runs a twin on a single thread next to the machine.
+#[test]
+fn determinism_checker_passes() {
+    let path = write("verified", &pipelines());
+    let status = Command::new(env!("CARGO_BIN_EXE_tis-cli"))
+        .args([
+            &path,
+            "--threads",
+            "4",
+            "--verify-determinism",
+            "--max-cycles",
+            "2000",
+        ])
+        .status()
+        .unwrap();
+    let _ = fs::remove_file(path);
+    assert!(status.success());
+}